@@ -11,12 +11,9 @@ use embedded_graphics::prelude::*;
 use embedded_graphics::{egcircle, egline, egrectangle, egtext, primitive_style, text_style};
 
 use epd::display::DisplayRotation;
+use rpi_pico_sdk::bytes::BytesWriter;
 use rpi_pico_sdk::gpio::Gpio;
-use rpi_pico_sdk::spi::SPI;
-
-use self::bytes::BytesWriter;
-
-pub mod bytes;
+use rpi_pico_sdk::println;
 
 /*
 GP18 => SCL
@@ -34,15 +31,27 @@ const PIN_LED: u32 = 25;
 // SPI0_CSn
 // const PIN_CS: uint = 17;
 
+rpi_pico_sdk::binary_info! {
+    name: "hello_epd",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "E-Paper display demo",
+    pins: [
+        PIN_LED => "LED",
+        20 => "EPD RST",
+        21 => "EPD DC",
+        22 => "EPD CS",
+        26 => "EPD BUSY",
+    ],
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn main() -> i32 {
+    rpi_pico_sdk::stdio::init_all();
+
     let mut led = Gpio::init(PIN_LED).into_push_pull_output();
 
-    let spi = SPI::spi0(4_000_000);
     // NoMiso
-    gpio_set_function(PIN_SCK, GPIO_FUNC_SPI);
-    gpio_set_function(PIN_MOSI, GPIO_FUNC_SPI);
-    //gpio_set_function(PIN_CS, GPIO_FUNC_SPI);
+    let spi = rpi_pico_sdk::spi0!(4_000_000, sck: PIN_SCK, tx: PIN_MOSI);
 
     let mut rst = Gpio::init(20).into_push_pull_output();
     let dc = Gpio::init(21).into_push_pull_output();
@@ -137,6 +146,7 @@ pub unsafe extern "C" fn main() -> i32 {
 
         buf.clear();
         write!(buf, "{}", i).unwrap();
+        println!("refresh #{}", i);
 
         egtext!(
             text = buf.as_str(),
@@ -157,8 +167,3 @@ pub unsafe extern "C" fn main() -> i32 {
         i += 1;
     }
 }
-
-#[panic_handler]
-fn panic(_info: &core::panic::PanicInfo) -> ! {
-    loop {}
-}
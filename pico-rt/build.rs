@@ -0,0 +1,16 @@
+//! Build script, puts memory.x and link.x on the linker search path.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+fn main() {
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+
+    for script in &["memory.x", "link.x"] {
+        fs::copy(script, out.join(script)).unwrap();
+        println!("cargo:rerun-if-changed={}", script);
+    }
+    println!("cargo:rustc-link-search={}", out.display());
+    println!("cargo:rerun-if-changed=build.rs");
+}
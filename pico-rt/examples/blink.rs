@@ -0,0 +1,31 @@
+//! blink, built by cargo alone:
+//!
+//! cargo build -p rpi-pico-rt --example blink --release
+#![no_std]
+#![no_main]
+
+use rpi_pico_rt::entry;
+use rpi_pico_sdk_sys::*;
+
+rpi_pico_sdk::binary_info! {
+    name: "blink",
+    description: "blink, built by cargo alone",
+    pins: [PICO_DEFAULT_LED_PIN => "LED"],
+}
+
+#[entry]
+fn main() -> ! {
+    const PIN_LED: u32 = PICO_DEFAULT_LED_PIN;
+
+    unsafe {
+        gpio_init(PIN_LED);
+        gpio_set_dir(PIN_LED, GPIO_OUT);
+
+        loop {
+            gpio_put(PIN_LED, true);
+            sleep_ms(500);
+            gpio_put(PIN_LED, false);
+            sleep_ms(500);
+        }
+    }
+}
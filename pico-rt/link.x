@@ -0,0 +1,183 @@
+/* Linker script for rpi-pico-rt, after pico-sdk's memmap_default.ld */
+
+INCLUDE memory.x
+
+ENTRY(Reset);
+
+/* Exception handlers, may be overridden by the application */
+PROVIDE(NMI = DefaultHandler);
+PROVIDE(HardFault = DefaultHandler);
+PROVIDE(SVCall = DefaultHandler);
+PROVIDE(PendSV = DefaultHandler);
+PROVIDE(SysTick = DefaultHandler);
+
+SECTIONS
+{
+    /* Stage-2 bootloader, 252 bytes of code + 4 bytes CRC, checked by the bootrom */
+    .boot2 ORIGIN(BOOT2) :
+    {
+        __boot2_start__ = .;
+        KEEP(*(.boot2));
+        __boot2_end__ = .;
+    } > BOOT2
+
+    ASSERT(__boot2_end__ - __boot2_start__ == 256,
+        "ERROR: Pico second stage bootloader must be 256 bytes in size")
+
+    /* boot2 sets VTOR here and jumps to the reset vector */
+    .vector_table ORIGIN(FLASH) :
+    {
+        __vectors = .;
+        LONG(__StackTop);
+        KEEP(*(.vector_table.reset_vector));
+        KEEP(*(.vector_table.exceptions));
+        KEEP(*(.vector_table.interrupts));
+        /* picotool looks for the binary info header in the first 256 bytes after boot2 */
+        KEEP(*(.binary_info_header));
+        __binary_info_header_end = .;
+    } > FLASH
+
+    .text :
+    {
+        *(.text .text.*)
+        *(.fini)
+        /* pico-sdk constructors */
+        . = ALIGN(4);
+        *(.init)
+        *(.eh_frame*)
+    } > FLASH
+
+    .rodata : ALIGN(4)
+    {
+        *(.rodata .rodata.*)
+        . = ALIGN(4);
+        *(SORT_BY_ALIGNMENT(SORT_BY_NAME(.flashdata*)))
+        . = ALIGN(4);
+    } > FLASH
+
+    .ARM.extab : { *(.ARM.extab* .gnu.linkonce.armextab.*) } > FLASH
+
+    __exidx_start = .;
+    .ARM.exidx : { *(.ARM.exidx* .gnu.linkonce.armexidx.*) } > FLASH
+    __exidx_end = .;
+
+    /* Binary info entries, see pico/binary_info */
+    . = ALIGN(4);
+    __binary_info_start = .;
+    .binary_info :
+    {
+        KEEP(*(.binary_info.keep.*))
+        *(.binary_info.*)
+    } > FLASH
+    __binary_info_end = .;
+    . = ALIGN(4);
+
+    /* Vector table copied here by runtime_init(), so irq.c can install handlers */
+    .ram_vector_table (NOLOAD) : ALIGN(256)
+    {
+        *(.ram_vector_table)
+    } > RAM
+
+    .data : ALIGN(4)
+    {
+        __data_start__ = .;
+        *(vtable)
+        /* __not_in_flash_func and flash programming routines */
+        *(.time_critical*)
+        . = ALIGN(4);
+        *(.data .data.*)
+
+        . = ALIGN(4);
+        *(.after_data.*)
+        . = ALIGN(4);
+        PROVIDE_HIDDEN(__mutex_array_start = .);
+        KEEP(*(SORT(.mutex_array.*)))
+        KEEP(*(.mutex_array))
+        PROVIDE_HIDDEN(__mutex_array_end = .);
+
+        . = ALIGN(4);
+        PROVIDE_HIDDEN(__preinit_array_start = .);
+        KEEP(*(SORT(.preinit_array.*)))
+        KEEP(*(.preinit_array))
+        PROVIDE_HIDDEN(__preinit_array_end = .);
+
+        . = ALIGN(4);
+        PROVIDE_HIDDEN(__init_array_start = .);
+        KEEP(*(SORT(.init_array.*)))
+        KEEP(*(.init_array))
+        PROVIDE_HIDDEN(__init_array_end = .);
+
+        . = ALIGN(4);
+        PROVIDE_HIDDEN(__fini_array_start = .);
+        *(SORT(.fini_array.*))
+        *(.fini_array)
+        PROVIDE_HIDDEN(__fini_array_end = .);
+
+        . = ALIGN(4);
+        __data_end__ = .;
+    } > RAM AT> FLASH
+    __etext = LOADADDR(.data);
+
+    .uninitialized_data (NOLOAD) : ALIGN(4)
+    {
+        *(.uninitialized_data*)
+    } > RAM
+
+    .scratch_x : ALIGN(4)
+    {
+        __scratch_x_start__ = .;
+        *(.scratch_x.*)
+        . = ALIGN(4);
+        __scratch_x_end__ = .;
+    } > SCRATCH_X AT> FLASH
+    __scratch_x_source__ = LOADADDR(.scratch_x);
+
+    .scratch_y : ALIGN(4)
+    {
+        __scratch_y_start__ = .;
+        *(.scratch_y.*)
+        . = ALIGN(4);
+        __scratch_y_end__ = .;
+    } > SCRATCH_Y AT> FLASH
+    __scratch_y_source__ = LOADADDR(.scratch_y);
+
+    /* end of the image in flash, rpi_pico_sdk::flash starts past it */
+    .flash_end : {
+        __flash_binary_end = .;
+    } > FLASH
+
+    .bss (NOLOAD) : ALIGN(4)
+    {
+        __bss_start__ = .;
+        *(SORT_BY_ALIGNMENT(SORT_BY_NAME(.bss*)))
+        *(COMMON)
+        . = ALIGN(4);
+        __bss_end__ = .;
+    } > RAM
+
+    .heap (NOLOAD) :
+    {
+        __end__ = .;
+        end = __end__;
+        KEEP(*(.heap*))
+        __HeapLimit = .;
+    } > RAM
+
+    /* Core 0 stack at the top of SCRATCH_Y, core 1 stack in SCRATCH_X */
+    .stack1_dummy (NOLOAD) : { *(.stack1*) } > SCRATCH_X
+    .stack_dummy (NOLOAD) : { KEEP(*(.stack*)) } > SCRATCH_Y
+
+    __StackTop = ORIGIN(SCRATCH_Y) + LENGTH(SCRATCH_Y);
+    __StackOneTop = ORIGIN(SCRATCH_X) + LENGTH(SCRATCH_X);
+    __StackBottom = __StackTop - SIZEOF(.stack_dummy);
+    __StackOneBottom = __StackOneTop - SIZEOF(.stack1_dummy);
+    __StackLimit = ORIGIN(SCRATCH_Y);
+    PROVIDE(__stack = __StackTop);
+
+    ASSERT(__StackLimit >= __HeapLimit, "region RAM overflowed")
+
+    /DISCARD/ :
+    {
+        *(.ARM.attributes)
+    }
+}
@@ -0,0 +1,68 @@
+//! Attribute macros for rpi-pico-rt.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Error, ItemFn, ReturnType, Type, Visibility};
+
+/// Marks the program entry point, called by `Reset` after `runtime_init()`.
+///
+/// The function must have signature `fn() -> !`.
+///
+/// ```ignore
+/// #[entry]
+/// fn main() -> ! {
+///     loop {}
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn entry(args: TokenStream, input: TokenStream) -> TokenStream {
+    let f = parse_macro_input!(input as ItemFn);
+
+    if !args.is_empty() {
+        return Error::new(Span::call_site(), "this attribute accepts no arguments")
+            .to_compile_error()
+            .into();
+    }
+
+    let valid_signature = f.sig.constness.is_none()
+        && f.sig.asyncness.is_none()
+        && f.sig.abi.is_none()
+        && f.sig.inputs.is_empty()
+        && f.sig.generics.params.is_empty()
+        && f.sig.generics.where_clause.is_none()
+        && f.sig.variadic.is_none()
+        && matches!(f.vis, Visibility::Inherited)
+        && match f.sig.output {
+            ReturnType::Default => false,
+            ReturnType::Type(_, ref ty) => matches!(**ty, Type::Never(_)),
+        };
+
+    if !valid_signature {
+        return Error::new(
+            f.sig.span(),
+            "`#[entry]` function must have signature `[unsafe] fn() -> !`",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let attrs = f.attrs;
+    let unsafety = f.sig.unsafety;
+    let ident = f.sig.ident;
+    let block = f.block;
+
+    quote!(
+        #[doc(hidden)]
+        #[export_name = "main"]
+        pub unsafe extern "C" fn __rpi_pico_rt_main() -> ! {
+            #ident()
+        }
+
+        #(#attrs)*
+        #unsafety fn #ident() -> ! #block
+    )
+    .into()
+}
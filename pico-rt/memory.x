@@ -0,0 +1,9 @@
+/* Raspberry Pi Pico: 2MB W25Q16JV flash, 264KB SRAM */
+MEMORY
+{
+    BOOT2 (rx) : ORIGIN = 0x10000000, LENGTH = 0x100
+    FLASH (rx) : ORIGIN = 0x10000100, LENGTH = 2048K - 0x100
+    RAM (rwx) : ORIGIN = 0x20000000, LENGTH = 256K
+    SCRATCH_X (rwx) : ORIGIN = 0x20040000, LENGTH = 4K
+    SCRATCH_Y (rwx) : ORIGIN = 0x20041000, LENGTH = 4K
+}
@@ -0,0 +1,237 @@
+//! Startup code for Raspberry Pi Pico, replaces pico-sdk's crt0.S.
+//!
+//! Provides the stage-2 bootloader, vector table, `Reset` handler and the
+//! `#[entry]` attribute, so an app can be built with `cargo build` alone:
+//!
+//! ```ignore
+//! #![no_std]
+//! #![no_main]
+//!
+//! use rpi_pico_rt::entry;
+//!
+//! #[entry]
+//! fn main() -> ! {
+//!     loop {}
+//! }
+//! ```
+//!
+//! `link.x` must be passed to the linker, see `.cargo/config`.
+#![no_std]
+
+use core::ptr::addr_of;
+
+pub use rpi_pico_rt_macros::entry;
+// pulls in libpico_sdk.a, runtime_init() lives there
+use rpi_pico_sdk_sys as _;
+
+/// Stage-2 bootloader, W25Q080 unless one of the `boot2-*` features is selected.
+#[cfg(not(any(
+    feature = "boot2-generic-03h",
+    feature = "boot2-is25lp080",
+    feature = "boot2-w25x10cl",
+    feature = "boot2-at25sf128a",
+    feature = "boot2-gd25q64cs"
+)))]
+#[link_section = ".boot2"]
+#[used]
+pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_W25Q080;
+
+#[cfg(feature = "boot2-generic-03h")]
+#[link_section = ".boot2"]
+#[used]
+pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_GENERIC_03H;
+
+#[cfg(feature = "boot2-is25lp080")]
+#[link_section = ".boot2"]
+#[used]
+pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_IS25LP080;
+
+#[cfg(feature = "boot2-w25x10cl")]
+#[link_section = ".boot2"]
+#[used]
+pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_W25X10CL;
+
+#[cfg(feature = "boot2-at25sf128a")]
+#[link_section = ".boot2"]
+#[used]
+pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_AT25SF128A;
+
+#[cfg(feature = "boot2-gd25q64cs")]
+#[link_section = ".boot2"]
+#[used]
+pub static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_GD25Q64CS;
+
+/// A vector table entry
+#[derive(Clone, Copy)]
+pub union Vector {
+    handler: unsafe extern "C" fn(),
+    reserved: usize,
+}
+
+extern "C" {
+    fn NMI();
+    fn HardFault();
+    fn SVCall();
+    fn PendSV();
+    fn SysTick();
+}
+
+#[doc(hidden)]
+#[link_section = ".vector_table.reset_vector"]
+#[no_mangle]
+pub static __RESET_VECTOR: unsafe extern "C" fn() -> ! = Reset;
+
+#[doc(hidden)]
+#[link_section = ".vector_table.exceptions"]
+#[no_mangle]
+pub static __EXCEPTIONS: [Vector; 14] = [
+    Vector { handler: NMI },
+    Vector { handler: HardFault },
+    Vector { reserved: 0 },
+    Vector { reserved: 0 },
+    Vector { reserved: 0 },
+    Vector { reserved: 0 },
+    Vector { reserved: 0 },
+    Vector { reserved: 0 },
+    Vector { reserved: 0 },
+    Vector { handler: SVCall },
+    Vector { reserved: 0 },
+    Vector { reserved: 0 },
+    Vector { handler: PendSV },
+    Vector { handler: SysTick },
+];
+
+/// IRQ 0-31. All start unhandled, pico-sdk's irq.c installs handlers
+/// at runtime into the RAM copy of this table.
+#[doc(hidden)]
+#[link_section = ".vector_table.interrupts"]
+#[no_mangle]
+pub static __INTERRUPTS: [Vector; 32] = [Vector {
+    handler: __unhandled_user_irq,
+}; 32];
+
+/// Default IRQ handler, also used by irq.c to detect a free slot.
+#[no_mangle]
+pub unsafe extern "C" fn __unhandled_user_irq() {
+    DefaultHandler();
+}
+
+/// Default exception handler, halts in the debugger.
+#[no_mangle]
+pub unsafe extern "C" fn DefaultHandler() {
+    loop {
+        asm_bkpt();
+    }
+}
+
+#[inline(always)]
+unsafe fn asm_bkpt() {
+    #[cfg(target_arch = "arm")]
+    core::arch::asm!("bkpt #0");
+}
+
+/// Read by picotool to find the binary info entries, see `rpi_pico_sdk::binary_info`.
+#[repr(C)]
+pub struct BinaryInfoHeader {
+    marker_start: u32,
+    start: *const u32,
+    end: *const u32,
+    mapping_table: *const [*const u32; 10],
+    marker_end: u32,
+}
+
+unsafe impl Sync for BinaryInfoHeader {}
+
+extern "C" {
+    static __binary_info_start: u32;
+    static __binary_info_end: u32;
+    /// (source, dest start, dest end) of each section `Reset` copies to RAM,
+    /// zero terminated: picotool follows pointers into `.data` with it too.
+    static data_cpy_table: [*const u32; 10];
+}
+
+#[doc(hidden)]
+#[link_section = ".binary_info_header"]
+#[used]
+pub static __BINARY_INFO_HEADER: BinaryInfoHeader = BinaryInfoHeader {
+    marker_start: 0x7188_ebf2,
+    start: addr_of!(__binary_info_start),
+    end: addr_of!(__binary_info_end),
+    mapping_table: addr_of!(data_cpy_table),
+    marker_end: 0xe71a_a390,
+};
+
+extern "C" {
+    /// Below, in assembly
+    fn Reset() -> !;
+}
+
+// Reset handler, boot2 jumps here with SP already loaded from the vector
+// table. It's assembly like crt0.S: until .data and the scratch banks are
+// copied and .bss zeroed, no Rust code may run.
+#[cfg(target_arch = "arm")]
+core::arch::global_asm!(
+    ".section .text.Reset, \"ax\"",
+    ".global Reset",
+    ".type Reset, %function",
+    ".thumb_func",
+    "Reset:",
+    // Only core 0 runs the startup. Core 1 normally waits in the bootrom, but
+    // after a reset from the debugger it can get here too: send it back.
+    "ldr r0, =0xd0000000", // SIO CPUID
+    "ldr r0, [r0]",
+    "cmp r0, #0",
+    "bne .Lhold_non_core0_in_bootrom",
+    // (source, start, end) triples from the table below
+    "adr r4, .Ldata_cpy_table",
+    ".Ldata_cpy_next:",
+    "ldmia r4!, {{r1, r2, r3}}",
+    "cmp r1, #0",
+    "beq .Lbss_fill",
+    "bl .Ldata_cpy",
+    "b .Ldata_cpy_next",
+    ".Lbss_fill:",
+    "ldr r1, =__bss_start__",
+    "ldr r2, =__bss_end__",
+    "movs r0, #0",
+    "b .Lbss_fill_test",
+    ".Lbss_fill_loop:",
+    "stm r1!, {{r0}}",
+    ".Lbss_fill_test:",
+    "cmp r1, r2",
+    "bne .Lbss_fill_loop",
+    // 32 bit jumps, in case these end up out of branch range
+    "ldr r1, =runtime_init",
+    "blx r1",
+    "ldr r1, =main",
+    "blx r1",
+    ".Lhang:",
+    "bkpt #0",
+    "b .Lhang",
+    ".Ldata_cpy_loop:",
+    "ldm r1!, {{r0}}",
+    "stm r2!, {{r0}}",
+    ".Ldata_cpy:",
+    "cmp r2, r3",
+    "blo .Ldata_cpy_loop",
+    "bx lr",
+    // rom_table_lookup(rom_func_table, 'W' | 'V' << 8), _wait_for_vector
+    ".Lhold_non_core0_in_bootrom:",
+    "movs r0, #0x14",
+    "ldrh r0, [r0]",
+    "ldr r1, =0x5657",
+    "movs r2, #0x18",
+    "ldrh r2, [r2]",
+    "blx r2",
+    "bx r0",
+    ".align 2",
+    ".global data_cpy_table",
+    ".Ldata_cpy_table:",
+    "data_cpy_table:",
+    ".word __etext, __data_start__, __data_end__",
+    ".word __scratch_x_source__, __scratch_x_start__, __scratch_x_end__",
+    ".word __scratch_y_source__, __scratch_y_start__, __scratch_y_end__",
+    ".word 0",
+    ".ltorg",
+    ".size Reset, . - Reset",
+);
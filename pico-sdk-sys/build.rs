@@ -0,0 +1,140 @@
+//! Build script.
+//!
+//! With the `link` feature, compile the pico-sdk C sources needed by the
+//! bindings into `libpico_sdk.a`, so an app can be linked by cargo alone
+//! instead of by CMakeLists.txt.
+
+#[cfg(feature = "link")]
+mod link {
+    use std::env;
+    use std::path::PathBuf;
+
+    /// Header directories, relative to $PICO_SDK_PATH/src. Keep in sync with gen.sh.
+    const INCLUDES: &[&str] = &[
+        "common/pico_base/include",
+        "common/pico_stdlib/include",
+        "common/pico_time/include",
+        "common/pico_sync/include",
+        "common/pico_util/include",
+        "common/pico_binary_info/include",
+        "rp2_common/pico_multicore/include",
+        "rp2_common/pico_platform/include",
+        "rp2_common/pico_runtime/include",
+        "rp2_common/pico_stdio/include",
+        "rp2_common/pico_stdio_uart/include",
+        "rp2_common/pico_printf/include",
+        "rp2_common/pico_bootrom/include",
+        "rp2_common/hardware_base/include",
+        "rp2_common/hardware_claim/include",
+        "rp2_common/hardware_clocks/include",
+        "rp2_common/hardware_flash/include",
+        "rp2_common/hardware_gpio/include",
+        "rp2_common/hardware_irq/include",
+        "rp2_common/hardware_pll/include",
+        "rp2_common/hardware_pwm/include",
+        "rp2_common/hardware_resets/include",
+        "rp2_common/hardware_rtc/include",
+        "rp2_common/hardware_spi/include",
+        "rp2_common/hardware_sync/include",
+        "rp2_common/hardware_timer/include",
+        "rp2_common/hardware_uart/include",
+        "rp2_common/hardware_watchdog/include",
+        "rp2_common/hardware_xosc/include",
+        "rp2040/hardware_regs/include",
+        "rp2040/hardware_structs/include",
+        "boards/include",
+    ];
+
+    /// Sources, relative to $PICO_SDK_PATH/src.
+    ///
+    /// crt0.S and the bit_ops/divider/float wrappers are left out, startup
+    /// is provided by rpi-pico-rt.
+    const SOURCES: &[&str] = &[
+        "common/pico_sync/critical_section.c",
+        "common/pico_sync/lock_core.c",
+        "common/pico_sync/mutex.c",
+        "common/pico_sync/sem.c",
+        "common/pico_time/time.c",
+        "common/pico_time/timeout_helper.c",
+        "common/pico_util/datetime.c",
+        "common/pico_util/pheap.c",
+        "common/pico_util/queue.c",
+        "rp2_common/pico_multicore/multicore.c",
+        "rp2_common/pico_platform/platform.c",
+        "rp2_common/pico_runtime/runtime.c",
+        "rp2_common/pico_stdlib/stdlib.c",
+        "rp2_common/pico_stdio/stdio.c",
+        "rp2_common/pico_stdio_uart/stdio_uart.c",
+        "rp2_common/pico_printf/printf.c",
+        "rp2_common/pico_bootrom/bootrom.c",
+        "rp2_common/hardware_claim/claim.c",
+        "rp2_common/hardware_clocks/clocks.c",
+        "rp2_common/hardware_flash/flash.c",
+        "rp2_common/hardware_gpio/gpio.c",
+        "rp2_common/hardware_irq/irq.c",
+        "rp2_common/hardware_irq/irq_handler_chain.S",
+        "rp2_common/hardware_pll/pll.c",
+        "rp2_common/hardware_rtc/rtc.c",
+        "rp2_common/hardware_spi/spi.c",
+        "rp2_common/hardware_sync/sync.c",
+        "rp2_common/hardware_timer/timer.c",
+        "rp2_common/hardware_uart/uart.c",
+        "rp2_common/hardware_watchdog/watchdog.c",
+        "rp2_common/hardware_xosc/xosc.c",
+    ];
+
+    pub fn main() {
+        println!("cargo:rerun-if-env-changed=PICO_SDK_PATH");
+
+        let target = env::var("TARGET").unwrap();
+        if !target.starts_with("thumbv6m") {
+            // host builds, e.g. `cargo doc`, have nothing to link
+            return;
+        }
+
+        let sdk = PathBuf::from(
+            env::var("PICO_SDK_PATH").expect("PICO_SDK_PATH must be set for the `link` feature"),
+        )
+        .join("src");
+        let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+
+        let mut build = cc::Build::new();
+        build
+            .compiler("arm-none-eabi-gcc")
+            .archiver("arm-none-eabi-ar")
+            .flag("-mcpu=cortex-m0plus")
+            .flag("-mthumb")
+            .flag("-ffunction-sections")
+            .flag("-fdata-sections")
+            .flag("-Wno-unused-parameter")
+            .define("PICO_ON_DEVICE", "1")
+            .define("PICO_NO_HARDWARE", "0")
+            .define("PICO_BUILD", "1")
+            .define("PICO_COPY_TO_RAM", "0")
+            .define("PICO_CXX_ENABLE_EXCEPTIONS", "0")
+            .define("PICO_NO_FLASH", "0")
+            .define("PICO_USE_BLOCKED_RAM", "0")
+            .define("PICO_TARGET_NAME", "\"rpi-pico-rt\"")
+            .define("LIB_PICO_STDIO_UART", "1")
+            .define("LIB_PICO_PRINTF_PICO", "1")
+            .include(manifest_dir.join("generated"));
+
+        for dir in INCLUDES {
+            build.include(sdk.join(dir));
+        }
+        for src in SOURCES {
+            let path = sdk.join(src);
+            println!("cargo:rerun-if-changed={}", path.display());
+            build.file(path);
+        }
+
+        build.compile("pico_sdk");
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "link")]
+    link::main();
+}
@@ -8,6 +8,14 @@ bindgen wrapper.h \
     --disable-untagged-union \
     --no-prepend-enum-name \
     --no-layout-tests \
+    --raw-line "use crate::stdio::stdio_driver_t;" \
+    --raw-line "use crate::volatile::*;" \
+    --blacklist-type "io_(rw|ro|wo)_(8|16|32)" \
+    --blacklist-type "(sio|interp|padsbank0|pwm|spi)_hw_t" \
+    --blacklist-type "pwm_slice_hw(_t)?" \
+    --blacklist-type "stdio_driver(_t)?" \
+    --no-copy ".*_hw_t" \
+    --no-debug ".*_hw_t" \
     -- \
     -I $PICO_SDK_PATH/src/rp2_common/pico_stdio/include \
     -I $PICO_SDK_PATH/src/common/pico_stdlib/include \
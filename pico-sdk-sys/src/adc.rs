@@ -0,0 +1,91 @@
+//! hardware_adc, not in the bindgen output yet.
+use crate::*;
+
+// #define adc_hw ((adc_hw_t *const)ADC_BASE)
+pub const adc_hw: *mut adc_hw_t = ADC_BASE as _;
+
+pub const RESETS_RESET_ADC_BITS: u32 = 1 << 0;
+
+/// ADC input of the on-chip temperature sensor
+pub const ADC_TEMPERATURE_CHANNEL_NUM: uint = 4;
+
+/// \brief  Initialise the ADC HW
+///  \ingroup hardware_adc
+#[inline]
+pub unsafe fn adc_init() {
+    // ADC is in an unknown state. We should start by resetting it
+    reset_block(RESETS_RESET_ADC_BITS);
+    unreset_block_wait(RESETS_RESET_ADC_BITS);
+
+    // Now turn it back on. Staging of clock etc is handled internally
+    (*adc_hw).cs.write(ADC_CS::EN.bits());
+
+    // Internal staging completes in a few cycles, but poll to be sure
+    while !(*adc_hw).cs.is_set(ADC_CS::READY) {}
+}
+
+/// \brief  Initialise the gpio for use as an ADC pin
+///  \ingroup hardware_adc
+///
+/// Prepare a GPIO for use with ADC by disabling all digital functions.
+///
+/// \param gpio The GPIO number to use. Allowable GPIO numbers are 26 to 29 inclusive.
+#[inline]
+pub unsafe fn adc_gpio_init(gpio: uint) {
+    assert!((26..=29).contains(&gpio));
+    // Select NULL function to make output driver hi-Z
+    gpio_set_function(gpio, GPIO_FUNC_NULL);
+    // Also disable digital pulls and digital receiver
+    gpio_disable_pulls(gpio);
+    gpio_set_input_enabled(gpio, false);
+}
+
+/// \brief  ADC input select
+///  \ingroup hardware_adc
+///
+/// Select an ADC input. 0...3 are GPIOs 26...29 respectively.
+/// Input 4 is the onboard temperature sensor.
+///
+/// \param input Input to select.
+#[inline]
+pub unsafe fn adc_select_input(input: uint) {
+    assert!(input <= ADC_TEMPERATURE_CHANNEL_NUM);
+    (*adc_hw).cs.write_field(ADC_CS::AINSEL, input);
+}
+
+/// \brief  Get the currently selected ADC input channel
+///  \ingroup hardware_adc
+///
+/// \return The currently selected input channel. 0...3 are GPIOs 26...29 respectively. Input 4 is the onboard temperature sensor.
+#[inline]
+pub unsafe fn adc_get_selected_input() -> uint {
+    (*adc_hw).cs.read_field(ADC_CS::AINSEL)
+}
+
+/// \brief Perform a single conversion
+///  \ingroup hardware_adc
+///
+///  Performs an ADC conversion, waits for the result, and then returns it.
+///
+/// \return Result of the conversion.
+#[inline]
+pub unsafe fn adc_read() -> u16 {
+    (*adc_hw).cs.set_field(ADC_CS::START_ONCE);
+
+    while !(*adc_hw).cs.is_set(ADC_CS::READY) {}
+
+    (*adc_hw).result.read() as u16
+}
+
+/// \brief Enable the onboard temperature sensor
+///  \ingroup hardware_adc
+///
+/// \param enable Set true to power on the onboard temperature sensor, false to power off.
+#[inline]
+pub unsafe fn adc_set_temp_sensor_enabled(enable: bool) {
+    if enable {
+        (*adc_hw).cs.set_field(ADC_CS::TS_EN);
+    } else {
+        (*adc_hw).cs.clear_field(ADC_CS::TS_EN);
+    }
+}
@@ -0,0 +1,98 @@
+//! pico_bootrom, not in the bindgen output yet: looking up functions and data
+//! in the bootrom's tables by their two-character codes.
+use crate::ctypes::c_void;
+
+/// \brief Return a bootrom lookup code based on two ASCII characters
+///  \ingroup pico_bootrom
+///
+/// These codes are uses to lookup data or function addresses in the bootrom
+///
+/// \param c1 the first character
+/// \param c2 the second character
+/// \return the 'code' to use in rom_func_lookup() or rom_data_lookup()
+#[inline(always)]
+pub const fn rom_table_code(c1: u8, c2: u8) -> u32 {
+    c1 as u32 | (c2 as u32) << 8
+}
+
+// function codes, from the datasheet's "Bootrom Contents"
+pub const ROM_FUNC_POPCOUNT32: u32 = rom_table_code(b'P', b'3');
+pub const ROM_FUNC_REVERSE32: u32 = rom_table_code(b'R', b'3');
+pub const ROM_FUNC_CLZ32: u32 = rom_table_code(b'L', b'3');
+pub const ROM_FUNC_CTZ32: u32 = rom_table_code(b'T', b'3');
+pub const ROM_FUNC_MEMSET: u32 = rom_table_code(b'M', b'S');
+pub const ROM_FUNC_MEMSET4: u32 = rom_table_code(b'S', b'4');
+pub const ROM_FUNC_MEMCPY: u32 = rom_table_code(b'M', b'C');
+pub const ROM_FUNC_MEMCPY44: u32 = rom_table_code(b'C', b'4');
+pub const ROM_FUNC_RESET_USB_BOOT: u32 = rom_table_code(b'U', b'B');
+pub const ROM_FUNC_CONNECT_INTERNAL_FLASH: u32 = rom_table_code(b'I', b'F');
+pub const ROM_FUNC_FLASH_EXIT_XIP: u32 = rom_table_code(b'E', b'X');
+pub const ROM_FUNC_FLASH_RANGE_ERASE: u32 = rom_table_code(b'R', b'E');
+pub const ROM_FUNC_FLASH_RANGE_PROGRAM: u32 = rom_table_code(b'R', b'P');
+pub const ROM_FUNC_FLASH_FLUSH_CACHE: u32 = rom_table_code(b'F', b'C');
+pub const ROM_FUNC_FLASH_ENTER_CMD_XIP: u32 = rom_table_code(b'C', b'X');
+pub const ROM_FUNC_DEBUG_TRAMPOLINE: u32 = rom_table_code(b'D', b'T');
+pub const ROM_FUNC_DEBUG_TRAMPOLINE_END: u32 = rom_table_code(b'D', b'E');
+pub const ROM_FUNC_WAIT_FOR_VECTOR: u32 = rom_table_code(b'W', b'V');
+
+// data codes
+pub const ROM_DATA_COPYRIGHT: u32 = rom_table_code(b'C', b'R');
+pub const ROM_DATA_GIT_REVISION: u32 = rom_table_code(b'G', b'R');
+pub const ROM_DATA_SOFT_FLOAT_TABLE: u32 = rom_table_code(b'S', b'F');
+pub const ROM_DATA_SOFT_DOUBLE_TABLE: u32 = rom_table_code(b'S', b'D');
+pub const ROM_DATA_FLOAT_TABLE_SIZE: u32 = rom_table_code(b'F', b'Z');
+
+/// `reset_usb_boot()`'s `disable_interface_mask`: no mass storage drive
+pub const RESET_USB_BOOT_DISABLE_MASS_STORAGE: u32 = 0x01;
+/// `reset_usb_boot()`'s `disable_interface_mask`: no PICOBOOT interface
+pub const RESET_USB_BOOT_DISABLE_PICOBOOT: u32 = 0x02;
+
+pub type rom_reset_usb_boot_fn =
+    unsafe extern "C" fn(usb_activity_gpio_pin_mask: u32, disable_interface_mask: u32) -> !;
+
+extern "C" {
+    /// \brief Lookup a bootrom function by code
+    ///  \ingroup pico_bootrom
+    /// \param code the code
+    /// \return a pointer to the function, or NULL if the code does not match any bootrom function
+    pub fn rom_func_lookup(code: u32) -> *mut c_void;
+
+    /// \brief Lookup a bootrom address by code
+    ///  \ingroup pico_bootrom
+    /// \param code the code
+    /// \return a pointer to the data, or NULL if the code does not match any bootrom function
+    pub fn rom_data_lookup(code: u32) -> *mut c_void;
+
+    /// \brief Helper function to lookup the addresses of multiple bootrom functions
+    ///  \ingroup pico_bootrom
+    ///
+    /// This method looks up the 'codes' in the table, and convert each table entry to the looked up
+    /// function pointer, if there is a function for that code in the bootrom.
+    ///
+    /// \param table an IN/OUT array, elements are codes on input, function pointers on success.
+    /// \param count the number of elements in the table
+    /// \return true if all the codes were found, and converted to function pointers, false otherwise
+    pub fn rom_funcs_lookup(table: *mut u32, count: crate::uint) -> bool;
+}
+
+/// \brief Reboot the device into BOOTSEL mode
+///  \ingroup pico_bootrom
+///
+/// This function reboots the device into the BOOTSEL mode ('usb boot").
+///
+/// Facilities are provided to enable an "activity light" via GPIO attached LED for the USB Mass Storage Device,
+/// and to limit the USB interfaces exposed.
+///
+/// \param usb_activity_gpio_pin_mask 0 No pins are used as per a cold boot. Otherwise a single bit set indicating which
+///                               GPIO pin should be set to output and raised whenever there is mass storage activity
+///                               from the host.
+/// \param disable_interface_mask value to control exposed interfaces
+///  - 0 To enable both interfaces (as per a cold boot)
+///  - 1 To disable the USB Mass Storage Interface
+///  - 2 To disable the USB PICOBOOT Interface
+#[inline]
+pub unsafe fn reset_usb_boot(usb_activity_gpio_pin_mask: u32, disable_interface_mask: u32) -> ! {
+    let func: rom_reset_usb_boot_fn =
+        core::mem::transmute(rom_func_lookup(ROM_FUNC_RESET_USB_BOOT));
+    func(usb_activity_gpio_pin_mask, disable_interface_mask)
+}
@@ -0,0 +1,192 @@
+//! hardware_clocks, hardware_pll and hardware_xosc, not in the bindgen output yet.
+use crate::*;
+
+pub const clk_gpout0: clock_index = 0;
+pub const clk_gpout1: clock_index = 1;
+pub const clk_gpout2: clock_index = 2;
+pub const clk_gpout3: clock_index = 3;
+pub const clk_ref: clock_index = 4;
+pub const clk_sys: clock_index = 5;
+pub const clk_peri: clock_index = 6;
+pub const clk_usb: clock_index = 7;
+pub const clk_adc: clock_index = 8;
+pub const clk_rtc: clock_index = 9;
+pub const CLK_COUNT: clock_index = 10;
+/// \brief Enumeration identifying a hardware clock
+///  \ingroup hardware_clocks
+pub type clock_index = crate::ctypes::c_uint;
+
+// hardware/regs/clocks.h is not in the bindgen output, the src and auxsrc
+// values clock_configure() takes
+
+pub const CLOCKS_CLK_REF_CTRL_SRC_VALUE_ROSC_CLKSRC_PH: u32 = 0x0;
+pub const CLOCKS_CLK_REF_CTRL_SRC_VALUE_CLKSRC_CLK_REF_AUX: u32 = 0x1;
+pub const CLOCKS_CLK_REF_CTRL_SRC_VALUE_XOSC_CLKSRC: u32 = 0x2;
+pub const CLOCKS_CLK_REF_CTRL_AUXSRC_VALUE_CLKSRC_PLL_USB: u32 = 0x0;
+pub const CLOCKS_CLK_REF_CTRL_AUXSRC_VALUE_CLKSRC_GPIN0: u32 = 0x1;
+pub const CLOCKS_CLK_REF_CTRL_AUXSRC_VALUE_CLKSRC_GPIN1: u32 = 0x2;
+
+pub const CLOCKS_CLK_SYS_CTRL_SRC_VALUE_CLK_REF: u32 = 0x0;
+pub const CLOCKS_CLK_SYS_CTRL_SRC_VALUE_CLKSRC_CLK_SYS_AUX: u32 = 0x1;
+pub const CLOCKS_CLK_SYS_CTRL_AUXSRC_VALUE_CLKSRC_PLL_SYS: u32 = 0x0;
+pub const CLOCKS_CLK_SYS_CTRL_AUXSRC_VALUE_CLKSRC_PLL_USB: u32 = 0x1;
+pub const CLOCKS_CLK_SYS_CTRL_AUXSRC_VALUE_ROSC_CLKSRC: u32 = 0x2;
+pub const CLOCKS_CLK_SYS_CTRL_AUXSRC_VALUE_XOSC_CLKSRC: u32 = 0x3;
+pub const CLOCKS_CLK_SYS_CTRL_AUXSRC_VALUE_CLKSRC_GPIN0: u32 = 0x4;
+pub const CLOCKS_CLK_SYS_CTRL_AUXSRC_VALUE_CLKSRC_GPIN1: u32 = 0x5;
+
+pub const CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_CLK_SYS: u32 = 0x0;
+pub const CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_CLKSRC_PLL_SYS: u32 = 0x1;
+pub const CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_CLKSRC_PLL_USB: u32 = 0x2;
+pub const CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_ROSC_CLKSRC_PH: u32 = 0x3;
+pub const CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_XOSC_CLKSRC: u32 = 0x4;
+pub const CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_CLKSRC_GPIN0: u32 = 0x5;
+pub const CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_CLKSRC_GPIN1: u32 = 0x6;
+
+// clk_usb, clk_adc and clk_rtc share their auxsrc values
+pub const CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_CLKSRC_PLL_USB: u32 = 0x0;
+pub const CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_CLKSRC_PLL_SYS: u32 = 0x1;
+pub const CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_ROSC_CLKSRC_PH: u32 = 0x2;
+pub const CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_XOSC_CLKSRC: u32 = 0x3;
+pub const CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_CLKSRC_GPIN0: u32 = 0x4;
+pub const CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_CLKSRC_GPIN1: u32 = 0x5;
+
+pub const CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLKSRC_PLL_SYS: u32 = 0x0;
+pub const CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLKSRC_GPIN0: u32 = 0x1;
+pub const CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLKSRC_GPIN1: u32 = 0x2;
+pub const CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLKSRC_PLL_USB: u32 = 0x3;
+pub const CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_ROSC_CLKSRC: u32 = 0x4;
+pub const CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_XOSC_CLKSRC: u32 = 0x5;
+pub const CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLK_SYS: u32 = 0x6;
+pub const CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLK_USB: u32 = 0x7;
+pub const CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLK_ADC: u32 = 0x8;
+pub const CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLK_RTC: u32 = 0x9;
+pub const CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLK_REF: u32 = 0xa;
+
+pub const CLOCKS_FC0_SRC_VALUE_NULL: u32 = 0x00;
+pub const CLOCKS_FC0_SRC_VALUE_PLL_SYS_CLKSRC_PRIMARY: u32 = 0x01;
+pub const CLOCKS_FC0_SRC_VALUE_PLL_USB_CLKSRC_PRIMARY: u32 = 0x02;
+pub const CLOCKS_FC0_SRC_VALUE_ROSC_CLKSRC: u32 = 0x03;
+pub const CLOCKS_FC0_SRC_VALUE_ROSC_CLKSRC_PH: u32 = 0x04;
+pub const CLOCKS_FC0_SRC_VALUE_XOSC_CLKSRC: u32 = 0x05;
+pub const CLOCKS_FC0_SRC_VALUE_CLKSRC_GPIN0: u32 = 0x06;
+pub const CLOCKS_FC0_SRC_VALUE_CLKSRC_GPIN1: u32 = 0x07;
+pub const CLOCKS_FC0_SRC_VALUE_CLK_REF: u32 = 0x08;
+pub const CLOCKS_FC0_SRC_VALUE_CLK_SYS: u32 = 0x09;
+pub const CLOCKS_FC0_SRC_VALUE_CLK_PERI: u32 = 0x0a;
+pub const CLOCKS_FC0_SRC_VALUE_CLK_USB: u32 = 0x0b;
+pub const CLOCKS_FC0_SRC_VALUE_CLK_ADC: u32 = 0x0c;
+pub const CLOCKS_FC0_SRC_VALUE_CLK_RTC: u32 = 0x0d;
+
+// #define clocks_hw ((clocks_hw_t *const)CLOCKS_BASE)
+pub const clocks_hw: *mut clocks_hw_t = CLOCKS_BASE as _;
+
+// #define pll_sys pll_sys_hw
+// #define pll_usb pll_usb_hw
+pub const pll_sys: PLL = PLL_SYS_BASE as _;
+pub const pll_usb: PLL = PLL_USB_BASE as _;
+pub type PLL = *mut pll_hw_t;
+
+extern "C" {
+    /// \brief Configure the specified clock
+    ///  \ingroup hardware_clocks
+    ///
+    /// See the tables in the description for details on the possible values for clock sources.
+    ///
+    /// \param clk_index The clock to configure
+    /// \param src The main clock source, can be 0.
+    /// \param auxsrc The auxiliary clock source, which depends on which clock is being set. Can be 0
+    /// \param src_freq Frequency of the input clock source
+    /// \param freq Requested frequency
+    pub fn clock_configure(
+        clk_index: clock_index,
+        src: u32,
+        auxsrc: u32,
+        src_freq: u32,
+        freq: u32,
+    ) -> bool;
+
+    /// \brief Stop the specified clock
+    ///  \ingroup hardware_clocks
+    ///
+    /// \param clk_index The clock to stop
+    pub fn clock_stop(clk_index: clock_index);
+
+    /// \brief Get the current frequency of the specified clock
+    ///  \ingroup hardware_clocks
+    ///
+    /// \param clk_index Clock
+    /// \return Clock frequency in Hz
+    pub fn clock_get_hz(clk_index: clock_index) -> u32;
+
+    /// \brief Measure a clocks frequency using the Frequency counter.
+    ///  \ingroup hardware_clocks
+    ///
+    /// Uses the inbuilt frequency counter to measure the specified clocks frequency.
+    /// Currently, this function is accurate to +-1KHz. See the datasheet for more details.
+    pub fn frequency_count_khz(src: uint) -> u32;
+
+    /// \brief Set the "current frequency" of the clock as reported by clock_get_hz without actually changing the clock
+    ///  \ingroup hardware_clocks
+    pub fn clock_set_reported_hz(clk_index: clock_index, hz: uint);
+
+    /// \brief Output an optionally divided clock to the specified gpio pin.
+    ///  \ingroup hardware_clocks
+    ///
+    /// \param gpio The GPIO pin to output the clock to. Valid GPIOs are: 21, 23, 24, 25. These GPIOs are connected to the GPOUT0-3 clock generators.
+    /// \param src  The source clock. See the register field CLOCKS_CLK_GPOUT0_CTRL_AUXSRC for a full list. The list is the same for each GPOUT clock generator.
+    /// \param div  The amount to divide the source clock by. This is useful to not overwhelm the GPIO pin with a fast clock.
+    pub fn clock_gpio_init(gpio: uint, src: uint, div: uint);
+
+    /// \brief Configure a clock to come from a gpio input
+    ///  \ingroup hardware_clocks
+    ///
+    /// \param clk_index The clock to configure
+    /// \param gpio The GPIO pin to run the clock from. Valid GPIOs are: 20 and 22.
+    /// \param src_freq Frequency of the input clock source
+    /// \param freq Requested frequency
+    pub fn clock_configure_gpin(
+        clk_index: clock_index,
+        gpio: uint,
+        src_freq: u32,
+        freq: u32,
+    ) -> bool;
+
+    /// \brief Initialise specified PLL.
+    ///  \ingroup hardware_pll
+    /// \param pll pll_sys or pll_usb
+    /// \param ref_div Input clock divider.
+    /// \param vco_freq  Requested output from the VCO (voltage controlled oscillator)
+    /// \param post_div1 Post Divider 1 - range 1-7. Must be >= post_div2
+    /// \param post_div2 Post Divider 2 - range 1-7
+    pub fn pll_init(pll: PLL, ref_div: uint, vco_freq: uint, post_div1: uint, post_div2: uint);
+
+    /// \brief Release/uninitialise specified PLL.
+    ///  \ingroup hardware_pll
+    ///
+    /// This will turn off the power to the specified PLL. Note this function does not currently check if
+    /// the PLL is in use before powering it off so should be used with care.
+    ///
+    /// \param pll pll_sys or pll_usb
+    pub fn pll_deinit(pll: PLL);
+
+    /// \brief  Initialise the crystal oscillator system
+    ///  \ingroup hardware_xosc
+    ///
+    /// This function will block until the crystal oscillator has stabilised.
+    pub fn xosc_init();
+
+    /// \brief  Disable the Crystal oscillator
+    ///  \ingroup hardware_xosc
+    ///
+    /// Turns off the crystal oscillator source, and waits for it to become unstable
+    pub fn xosc_disable();
+
+    /// \brief Set the crystal oscillator system to dormant
+    ///  \ingroup hardware_xosc
+    ///
+    /// Turns off the crystal oscillator until it is woken by an interrupt. This will block and hence
+    /// the entire system will stop, until an interrupt wakes it up. This function will
+    /// continue to block until the oscillator becomes stable after its wakeup.
+    pub fn xosc_dormant();
+}
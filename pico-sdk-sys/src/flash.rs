@@ -0,0 +1,63 @@
+//! hardware_flash, not in the bindgen output yet. Link `hardware_flash` to use it.
+//!
+//! The functions run from RAM (`.time_critical`), XIP is off while they run:
+//! nothing may execute from or read flash meanwhile, interrupt handlers and
+//! the other core included.
+
+pub const FLASH_PAGE_SIZE: u32 = 1 << 8;
+pub const FLASH_SECTOR_SIZE: u32 = 1 << 12;
+pub const FLASH_BLOCK_SIZE: u32 = 1 << 16;
+
+pub const FLASH_UNIQUE_ID_SIZE_BYTES: usize = 8;
+
+extern "C" {
+    /// \brief  Erase areas of flash
+    ///  \ingroup hardware_flash
+    ///
+    /// \param flash_offs Offset into flash, in bytes, to start the erase. Must be aligned to a 4096-byte flash sector.
+    /// \param count Number of bytes to be erased. Must be a multiple of 4096 bytes (one sector).
+    pub fn flash_range_erase(flash_offs: u32, count: usize);
+
+    /// \brief  Program flash
+    ///  \ingroup hardware_flash
+    ///
+    /// \param flash_offs Flash address of the first byte to be programmed. Must be aligned to a 256-byte flash page.
+    /// \param data Pointer to the data to program into flash
+    /// \param count Number of bytes to program. Must be a multiple of 256 bytes (one page).
+    pub fn flash_range_program(flash_offs: u32, data: *const u8, count: usize);
+
+    /// \brief Get flash unique 64 bit identifier
+    ///  \ingroup hardware_flash
+    ///
+    /// Use a standard 4Bh RUID instruction to retrieve the 64 bit unique
+    /// identifier from a flash device attached to the QSPI interface. Since there
+    /// is a 1:1 association between the MCU and this flash, this also serves as a
+    /// globally unique identifier for the board.
+    ///
+    ///  \param id_out Pointer to an 8-byte buffer to which the ID will be written
+    pub fn flash_get_unique_id(id_out: *mut u8);
+
+    /// \brief Execute bidirectional flash command
+    ///  \ingroup hardware_flash
+    ///
+    /// Low-level function to execute a serial command on a flash device attached
+    /// to the QSPI interface. Bytes are simultaneously transmitted and received
+    /// from txbuf and to rxbuf. Therefore, both buffers must be the same length,
+    /// count, which is the length of the overall transaction. This is useful for
+    /// reading metadata from the flash chip, such as device ID or SFDP
+    /// parameters.
+    ///
+    /// The XIP cache is flushed following each command, in case flash state
+    /// has been modified. Like other hardware_flash functions, the flash is not
+    /// accessible for execute-in-place transfers whilst the command is in
+    /// progress, so entering a flash-resident interrupt handler or executing flash
+    /// code on the second core concurrently will be fatal. To avoid these pitfalls
+    /// it is recommended that this function only be used to extract flash metadata
+    /// during startup, before the main application begins to run: see the
+    /// implementation of pico_get_unique_id() for an example of this.
+    ///
+    ///  \param txbuf Pointer to a byte buffer which will be transmitted to the flash
+    ///  \param rxbuf Pointer to a byte buffer where data received from the flash will be written. txbuf and rxbuf may be the same buffer.
+    ///  \param count Length in bytes of txbuf and of rxbuf
+    pub fn flash_do_cmd(txbuf: *const u8, rxbuf: *mut u8, count: usize);
+}
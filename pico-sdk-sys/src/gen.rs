@@ -1,5 +1,8 @@
 /* automatically generated by rust-bindgen 0.56.0 */
 
+use crate::stdio::stdio_driver_t;
+use crate::volatile::*;
+
 #[repr(C)]
 pub struct __BindgenUnionField<T>(::core::marker::PhantomData<T>);
 impl<T> __BindgenUnionField<T> {
@@ -4987,12 +4990,6 @@ pub const PICO_ERROR_GENERIC: crate::ctypes::c_int = -2;
 pub const PICO_ERROR_NO_DATA: crate::ctypes::c_int = -3;
 #[doc = " Common return codes from pico_sdk methods that return a status"]
 pub type _bindgen_ty_1 = crate::ctypes::c_int;
-#[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct stdio_driver {
-    _unused: [u8; 0],
-}
-pub type stdio_driver_t = stdio_driver;
 extern "C" {
     #[doc = " \\brief Initialize all of the present standard stdio types that are linked into the binary."]
     #[doc = " \\ingroup pico_stdio"]
@@ -5050,19 +5047,9 @@ extern "C" {
     #[doc = " \\param translate If true, convert line feeds to carriage return on transmissions"]
     pub fn stdio_set_translate_crlf(driver: *mut stdio_driver_t, translate: bool);
 }
-pub type io_rw_32 = u32;
-pub type io_ro_32 = u32;
-pub type io_wo_32 = u32;
-pub type io_rw_16 = u16;
-pub type io_ro_16 = u16;
-pub type io_wo_16 = u16;
-pub type io_rw_8 = u8;
-pub type io_ro_8 = u8;
-pub type io_wo_8 = u8;
 pub type ioptr = *mut u8;
 pub type const_ioptr = ioptr;
 #[repr(C)]
-#[derive(Debug, Copy, Clone)]
 pub struct timer_hw_t {
     pub timehw: io_wo_32,
     pub timelw: io_wo_32,
@@ -5409,60 +5396,6 @@ extern "C" {
     #[doc = " \\sa alarm_id_t for a note on reuse of IDs"]
     pub fn cancel_repeating_timer(timer: *mut repeating_timer_t) -> bool;
 }
-#[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct interp_hw_t {
-    pub accum: [io_rw_32; 2usize],
-    pub base: [io_rw_32; 3usize],
-    pub pop: [io_ro_32; 3usize],
-    pub peek: [io_ro_32; 3usize],
-    pub ctrl: [io_rw_32; 2usize],
-    pub add_raw: [io_rw_32; 2usize],
-    pub base01: io_wo_32,
-}
-#[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct sio_hw_t {
-    pub cpuid: io_ro_32,
-    pub gpio_in: io_ro_32,
-    pub gpio_hi_in: io_ro_32,
-    pub _pad: u32,
-    pub gpio_out: io_wo_32,
-    pub gpio_set: io_wo_32,
-    pub gpio_clr: io_wo_32,
-    pub gpio_togl: io_wo_32,
-    pub gpio_oe: io_wo_32,
-    pub gpio_oe_set: io_wo_32,
-    pub gpio_oe_clr: io_wo_32,
-    pub gpio_oe_togl: io_wo_32,
-    pub gpio_hi_out: io_wo_32,
-    pub gpio_hi_set: io_wo_32,
-    pub gpio_hi_clr: io_wo_32,
-    pub gpio_hi_togl: io_wo_32,
-    pub gpio_hi_oe: io_wo_32,
-    pub gpio_hi_oe_set: io_wo_32,
-    pub gpio_hi_oe_clr: io_wo_32,
-    pub gpio_hi_oe_togl: io_wo_32,
-    pub fifo_st: io_rw_32,
-    pub fifo_wr: io_wo_32,
-    pub fifo_rd: io_ro_32,
-    pub spinlock_st: io_ro_32,
-    pub div_udividend: io_rw_32,
-    pub div_udivisor: io_rw_32,
-    pub div_sdividend: io_rw_32,
-    pub div_sdivisor: io_rw_32,
-    pub div_quotient: io_rw_32,
-    pub div_remainder: io_rw_32,
-    pub div_csr: io_rw_32,
-    pub _pad2: u32,
-    pub interp: [interp_hw_t; 2usize],
-}
-#[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct padsbank0_hw_t {
-    pub voltage_select: io_rw_32,
-    pub io: [io_rw_32; 30usize],
-}
 pub const GPIO_FUNC_XIP: gpio_function = 0;
 pub const GPIO_FUNC_SPI: gpio_function = 1;
 pub const GPIO_FUNC_UART: gpio_function = 2;
@@ -5656,7 +5589,6 @@ extern "C" {
     pub fn gpio_debug_pins_init();
 }
 #[repr(C)]
-#[derive(Debug, Copy, Clone)]
 pub struct uart_hw_t {
     pub dr: io_rw_32,
     pub rsr: io_rw_32,
@@ -5951,26 +5883,6 @@ extern "C" {
     #[doc = " \\note This is an internal method and user should generally not call it."]
     pub fn irq_init_priorities();
 }
-#[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct pwm_slice_hw {
-    pub csr: io_rw_32,
-    pub div: io_rw_32,
-    pub ctr: io_rw_32,
-    pub cc: io_rw_32,
-    pub top: io_rw_32,
-}
-pub type pwm_slice_hw_t = pwm_slice_hw;
-#[repr(C)]
-#[derive(Debug, Copy, Clone)]
-pub struct pwm_hw_t {
-    pub slice: [pwm_slice_hw_t; 8usize],
-    pub en: io_rw_32,
-    pub intr: io_rw_32,
-    pub inte: io_rw_32,
-    pub intf: io_rw_32,
-    pub ints: io_rw_32,
-}
 #[doc = "< Free-running counting at rate dictated by fractional divider"]
 pub const PWM_DIV_FREE_RUNNING: pwm_clkdiv_mode = 0;
 #[doc = "< Fractional divider is gated by the PWM B pin"]
@@ -5995,20 +5907,6 @@ pub struct pwm_config {
 }
 #[repr(C)]
 #[derive(Debug, Copy, Clone)]
-pub struct spi_hw_t {
-    pub cr0: io_rw_32,
-    pub cr1: io_rw_32,
-    pub dr: io_rw_32,
-    pub sr: io_rw_32,
-    pub cpsr: io_rw_32,
-    pub imsc: io_rw_32,
-    pub ris: io_rw_32,
-    pub mis: io_rw_32,
-    pub icr: io_rw_32,
-    pub dmacr: io_rw_32,
-}
-#[repr(C)]
-#[derive(Debug, Copy, Clone)]
 pub struct spi_inst {
     _unused: [u8; 0],
 }
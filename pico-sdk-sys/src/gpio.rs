@@ -12,7 +12,7 @@ const padsbank0_hw: *mut padsbank0_hw_t = PADS_BANK0_BASE as *mut padsbank0_hw_t
 /// \\return Current state of the GPIO. 0 for low, non-zero for high
 #[inline]
 pub unsafe fn gpio_get(gpio: uint) -> bool {
-    ((1 << gpio) & (*sio_hw).gpio_in) != 0
+    ((1 << gpio) & (*sio_hw).gpio_in.read()) != 0
 }
 
 /// \brief Get raw value of all GPIOs
@@ -21,7 +21,7 @@ pub unsafe fn gpio_get(gpio: uint) -> bool {
 /// \return Bitmask of raw GPIO values, as bits 0-29
 #[inline]
 pub unsafe fn gpio_get_all() -> u32 {
-    (*sio_hw).gpio_in
+    (*sio_hw).gpio_in.read()
 }
 
 /// \brief Drive high every GPIO appearing in mask
@@ -30,7 +30,7 @@ pub unsafe fn gpio_get_all() -> u32 {
 /// \param mask Bitmask of GPIO values to set, as bits 0-29
 #[inline]
 pub unsafe fn gpio_set_mask(mask: u32) {
-    (*sio_hw).gpio_set = mask;
+    (*sio_hw).gpio_set.write(mask);
 }
 
 /// \brief Drive low every GPIO appearing in mask
@@ -39,7 +39,7 @@ pub unsafe fn gpio_set_mask(mask: u32) {
 /// \param mask Bitmask of GPIO values to clear, as bits 0-29
 #[inline]
 pub unsafe fn gpio_clr_mask(mask: u32) {
-    (*sio_hw).gpio_clr = mask;
+    (*sio_hw).gpio_clr.write(mask);
 }
 
 /// \brief Toggle every GPIO appearing in mask
@@ -48,7 +48,7 @@ pub unsafe fn gpio_clr_mask(mask: u32) {
 /// \param mask Bitmask of GPIO values to toggle, as bits 0-29
 #[inline]
 pub unsafe fn gpio_xor_mask(mask: u32) {
-    (*sio_hw).gpio_togl = mask;
+    (*sio_hw).gpio_togl.write(mask);
 }
 
 /// \brief Drive GPIO high/low depending on parameters
@@ -63,7 +63,9 @@ pub unsafe fn gpio_xor_mask(mask: u32) {
 /// bashing different pins from the same core.
 #[inline]
 pub unsafe fn gpio_put_masked(mask: u32, value: u32) {
-    (*sio_hw).gpio_togl = ((*sio_hw).gpio_out ^ value) & mask;
+    (*sio_hw)
+        .gpio_togl
+        .write(((*sio_hw).gpio_out.read() ^ value) & mask);
 }
 
 /// \brief Drive all pins simultaneously
@@ -72,7 +74,7 @@ pub unsafe fn gpio_put_masked(mask: u32, value: u32) {
 /// \param value Bitmask of GPIO values to change, as bits 0-29
 #[inline]
 pub unsafe fn gpio_put_all(value: u32) {
-    (*sio_hw).gpio_out = value;
+    (*sio_hw).gpio_out.write(value);
 }
 
 /// \brief Drive a single GPIO high/low
@@ -98,7 +100,7 @@ pub unsafe fn gpio_put(gpio: uint, value: bool) {
 /// \param mask Bitmask of GPIO to set to output, as bits 0-29
 #[inline]
 pub unsafe fn gpio_set_dir_out_masked(mask: u32) {
-    (*sio_hw).gpio_oe_set = mask;
+    (*sio_hw).gpio_oe_set.write(mask);
 }
 
 /// \brief Set a number of GPIOs to input
@@ -107,7 +109,7 @@ pub unsafe fn gpio_set_dir_out_masked(mask: u32) {
 /// \param mask Bitmask of GPIO to set to input, as bits 0-29
 #[inline]
 pub unsafe fn gpio_set_dir_in_masked(mask: u32) {
-    (*sio_hw).gpio_oe_clr = mask;
+    (*sio_hw).gpio_oe_clr.write(mask);
 }
 
 /// \brief Set multiple GPIO directions
@@ -122,7 +124,9 @@ pub unsafe fn gpio_set_dir_in_masked(mask: u32) {
 /// simultaneously.
 #[inline]
 pub unsafe fn gpio_set_dir_masked(mask: u32, value: u32) {
-    (*sio_hw).gpio_oe_togl = ((*sio_hw).gpio_oe ^ value) & mask;
+    (*sio_hw)
+        .gpio_oe_togl
+        .write(((*sio_hw).gpio_oe.read() ^ value) & mask);
 }
 
 /// \brief Set direction of all pins simultaneously.
@@ -131,7 +135,7 @@ pub unsafe fn gpio_set_dir_masked(mask: u32, value: u32) {
 /// \param values individual settings for each gpio; for GPIO N, bit N is 1 for out, 0 for in
 #[inline]
 pub unsafe fn gpio_set_dir_all_bits(values: u32) {
-    (*sio_hw).gpio_oe = values;
+    (*sio_hw).gpio_oe.write(values);
 }
 
 /// \brief Set a single GPIO direction
@@ -157,7 +161,7 @@ pub unsafe fn gpio_set_dir(gpio: uint, out: u32) {
 /// \return true if the direction for the pin is OUT
 #[inline]
 pub unsafe fn gpio_is_dir_out(gpio: uint) -> bool {
-    (*sio_hw).gpio_oe & (1 << (gpio)) != 0
+    (*sio_hw).gpio_oe.read() & (1 << (gpio)) != 0
 }
 
 /// \brief Get a specific GPIO direction
@@ -185,7 +189,7 @@ pub unsafe fn gpio_pull_up(gpio: uint) {
 /// \\return true if the GPIO is pulled up
 #[inline]
 pub unsafe fn gpio_is_pulled_up(gpio: uint) -> bool {
-    (*padsbank0_hw).io[gpio as usize] & PADS_BANK0_GPIO0_PUE_BITS != 0
+    (*padsbank0_hw).io[gpio as usize].is_set(PADS_BANK0_GPIO::PUE)
 }
 /// \\brief Set specified GPIO to be pulled down.
 ///  \\ingroup hardware_gpio
@@ -202,7 +206,7 @@ pub unsafe fn gpio_pull_down(gpio: uint) {
 /// \\return true if the GPIO is pulled down
 #[inline]
 pub unsafe fn gpio_is_pulled_down(gpio: uint) -> bool {
-    (*padsbank0_hw).io[gpio as usize] & PADS_BANK0_GPIO0_PDE_BITS != 0
+    (*padsbank0_hw).io[gpio as usize].is_set(PADS_BANK0_GPIO::PDE)
 }
 /// \\brief Disable pulls on specified GPIO
 ///  \\ingroup hardware_gpio
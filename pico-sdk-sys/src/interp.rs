@@ -0,0 +1,11 @@
+//! hardware_interp, not in the bindgen output yet: the per-core interpolators.
+use crate::*;
+
+// #define interp0_hw ((interp_hw_t *const)(SIO_BASE + SIO_INTERP0_ACCUM0_OFFSET))
+pub const interp0_hw: *mut interp_hw_t = (SIO_BASE + SIO_INTERP0_ACCUM0_OFFSET) as _;
+
+// #define interp1_hw ((interp_hw_t *const)(SIO_BASE + SIO_INTERP1_ACCUM0_OFFSET))
+pub const interp1_hw: *mut interp_hw_t = (SIO_BASE + SIO_INTERP1_ACCUM0_OFFSET) as _;
+
+pub const NUM_INTERPOLATORS: uint = 2;
+pub const NUM_INTERP_LANES: uint = 2;
@@ -7,34 +7,44 @@
 )]
 
 pub mod ctypes;
+pub mod rom;
+#[macro_use]
+mod volatile;
+mod adc;
+mod bootrom;
+mod clocks;
+mod flash;
 mod gen;
 mod gpio;
+mod interp;
+mod multicore;
 mod pwm;
+mod rtc;
+mod sleep;
 mod spi;
+mod stdio;
+mod structs;
+mod sync;
+mod uart;
+mod usb;
+mod watchdog;
 
-use self::ctypes::*;
+pub use self::adc::*;
+pub use self::bootrom::*;
+pub use self::clocks::*;
+pub use self::flash::*;
 pub use self::gen::*;
 pub use self::gpio::*;
+pub use self::interp::*;
+pub use self::multicore::*;
 pub use self::pwm::*;
+pub use self::rtc::*;
+pub use self::sleep::*;
 pub use self::spi::*;
-
-// hardware/base
-#[inline]
-unsafe fn hw_set_bits(addr: *mut io_rw_32, mask: uint32_t) {
-    let addr = ((addr as u32) | REG_ALIAS_SET_BITS) as *mut uint32_t;
-    *addr = mask;
-}
-#[inline]
-unsafe fn hw_clear_bits(addr: *mut io_rw_32, mask: uint32_t) {
-    let addr = ((addr as u32) | REG_ALIAS_CLR_BITS) as *mut uint32_t;
-    *addr = mask;
-}
-#[inline]
-unsafe fn hw_xor_bits(addr: *mut io_rw_32, mask: uint32_t) {
-    let addr = ((addr as u32) | REG_ALIAS_XOR_BITS) as *mut uint32_t;
-    *addr = mask;
-}
-#[inline]
-unsafe fn hw_write_masked(addr: *mut io_rw_32, values: uint32_t, write_mask: uint32_t) {
-    hw_xor_bits(addr, (*addr ^ values) & write_mask);
-}
+pub use self::stdio::*;
+pub use self::structs::*;
+pub use self::sync::*;
+pub use self::uart::*;
+pub use self::usb::*;
+pub use self::volatile::*;
+pub use self::watchdog::*;
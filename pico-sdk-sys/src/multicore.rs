@@ -0,0 +1,139 @@
+//! pico_multicore, not in the bindgen output yet. Link `pico_multicore` to use it.
+use crate::*;
+
+// #define sio_hw ((sio_hw_t *const)SIO_BASE)
+const sio_hw: *mut sio_hw_t = SIO_BASE as _;
+
+extern "C" {
+    /// \brief  Reset Core 1
+    ///  \ingroup pico_multicore
+    ///
+    pub fn multicore_reset_core1();
+
+    /// \brief  Run code on core 1
+    ///  \ingroup pico_multicore
+    ///
+    /// Reset core1 and enter the given function on core 1 using the default core 1 stack (below core 0 stack)
+    ///
+    /// \param entry Function entry point, this function should not return.
+    pub fn multicore_launch_core1(entry: Option<unsafe extern "C" fn()>);
+
+    /// \brief  Launch code on core 1 with stack
+    ///  \ingroup pico_multicore
+    ///
+    /// Reset core1 and enter the given function on core 1 using the passed stack for core 1
+    pub fn multicore_launch_core1_with_stack(
+        entry: Option<unsafe extern "C" fn()>,
+        stack_bottom: *mut u32,
+        stack_size_bytes: usize,
+    );
+
+    /// \brief  Launch code on core 1 with no stack protection
+    ///  \ingroup pico_multicore
+    ///
+    /// Reset core1 and enter the given function using the passed sp as the initial stack pointer.
+    /// This is a bare bones functions that does not provide a stack guard even if USE_STACK_GUARDS is defined
+    ///
+    pub fn multicore_launch_core1_raw(
+        entry: Option<unsafe extern "C" fn()>,
+        sp: *mut u32,
+        vector_table: u32,
+    );
+
+    /// \brief Push data on to the FIFO.
+    ///  \ingroup multicore_fifo
+    ///
+    /// This function will block until there is space for the data to be sent.
+    /// Use multicore_fifo_wready() to check if it is possible to write to the
+    /// FIFO if you don't want to block.
+    ///
+    /// \param data A 32 bit value to push on to the FIFO
+    pub fn multicore_fifo_push_blocking(data: u32);
+
+    pub fn multicore_fifo_push_timeout_us(data: u32, timeout_us: u64) -> bool;
+
+    /// \brief Pop data from the FIFO.
+    ///  \ingroup multicore_fifo
+    ///
+    /// This function will block until there is data ready to be read
+    /// Use multicore_fifo_rvalid() to check if data is ready to be read if you don't
+    /// want to block.
+    ///
+    /// \return 32 bit unsigned data from the FIFO.
+    pub fn multicore_fifo_pop_blocking() -> u32;
+
+    pub fn multicore_fifo_pop_timeout_us(timeout_us: u64, out: *mut u32) -> bool;
+}
+
+/// \brief Check the read FIFO to see if there is data waiting
+///  \ingroup multicore_fifo
+///
+/// \return true if the FIFO has data in it, false otherwise
+#[inline]
+pub unsafe fn multicore_fifo_rvalid() -> bool {
+    (*sio_hw).fifo_st.read() & SIO_FIFO_ST_VLD_BITS != 0
+}
+
+/// \brief Check the write FIFO to see if it is ready for more data
+///  \ingroup multicore_fifo
+///
+/// @return true if the FIFO has room for more data, false otherwise
+#[inline]
+pub unsafe fn multicore_fifo_wready() -> bool {
+    (*sio_hw).fifo_st.read() & SIO_FIFO_ST_RDY_BITS != 0
+}
+
+/// \brief Write to the FIFO without checking for space, then signal the other core
+///
+/// Only call after multicore_fifo_wready() returned true.
+#[inline]
+pub unsafe fn multicore_fifo_push_unchecked(data: u32) {
+    (*sio_hw).fifo_wr.write(data);
+    // Fire off an event to the other core
+    __sev();
+}
+
+/// \brief Read from the FIFO without checking for data
+///
+/// Only call after multicore_fifo_rvalid() returned true.
+#[inline]
+pub unsafe fn multicore_fifo_pop_unchecked() -> u32 {
+    (*sio_hw).fifo_rd.read()
+}
+
+/// \brief Flush any data in the outgoing FIFO
+///  \ingroup multicore_fifo
+///
+#[inline]
+pub unsafe fn multicore_fifo_drain() {
+    while multicore_fifo_rvalid() {
+        let _ = (*sio_hw).fifo_rd.read();
+    }
+}
+
+/// \brief Clear FIFO interrupt
+///  \ingroup multicore_fifo
+///
+/// Note that this only clears an interrupt that was caused by the ROE or WOF flags.
+/// To clear the VLD flag you need to use one of the 'pop' or 'drain' functions.
+#[inline]
+pub unsafe fn multicore_fifo_clear_irq() {
+    // Write any value to clear the error flags
+    (*sio_hw).fifo_st.write(0xff);
+}
+
+/// \brief Get FIFO status
+///  \ingroup multicore_fifo
+///
+/// \return The status as a bitfield
+///
+/// Bit | Description
+/// ----|------------
+/// 3 | Sticky flag indicating the RX FIFO was read when empty (ROE). This read was ignored by the FIFO.
+/// 2 | Sticky flag indicating the TX FIFO was written when full (WOF). This write was ignored by the FIFO.
+/// 1 | Value is 1 if this core’s TX FIFO is not full (i.e. if FIFO_WR is ready for more data)
+/// 0 | Value is 1 if this core’s RX FIFO is not empty (i.e. if FIFO_RD is valid)
+#[inline]
+pub unsafe fn multicore_fifo_get_status() -> u32 {
+    (*sio_hw).fifo_st.read()
+}
@@ -109,14 +109,14 @@ pub unsafe fn pwm_config_set_wrap(c: &mut pwm_config, wrap: u16) {
 ///  manually using \\ref pwm_set_enabled() or \\ref pwm_set_mask_enabled()
 #[inline]
 pub unsafe fn pwm_init(slice_num: uint, c: &pwm_config, start: bool) {
-    let slice_num = slice_num as usize;
-    (*pwm_hw).slice[slice_num].csr = 0;
+    let slice = &(*pwm_hw).slice[slice_num as usize];
+    slice.csr.write(0);
 
-    (*pwm_hw).slice[slice_num].ctr = PWM_CH0_CTR_RESET;
-    (*pwm_hw).slice[slice_num].cc = PWM_CH0_CC_RESET;
-    (*pwm_hw).slice[slice_num].top = c.top;
-    (*pwm_hw).slice[slice_num].div = c.div;
-    (*pwm_hw).slice[slice_num].csr = c.csr | ((start as u32) << PWM_CH0_CSR_EN_LSB);
+    slice.ctr.write(PWM_CH0_CTR_RESET);
+    slice.cc.write(PWM_CH0_CC_RESET);
+    slice.top.write(c.top);
+    slice.div.write(c.div);
+    slice.csr.write(c.csr | PWM_CH0_CSR::EN.value(start as u32));
 }
 /// \\brief Get a set of default values for PWM configuration
 ///  \\ingroup hardware_pwm
@@ -144,7 +144,7 @@ pub unsafe fn pwm_get_default_config() -> pwm_config {
 /// \\param wrap Value to set wrap to
 #[inline]
 pub unsafe fn pwm_set_wrap(slice_num: uint, wrap: u16) {
-    (*pwm_hw).slice[slice_num as usize].top = wrap as u32;
+    (*pwm_hw).slice[slice_num as usize].top.write(wrap as u32);
 }
 /// \\brief Set the current PWM counter compare value for one channel
 ///  \\ingroup hardware_pwm
@@ -156,19 +156,13 @@ pub unsafe fn pwm_set_wrap(slice_num: uint, wrap: u16) {
 /// \\param level new level for the selected output
 #[inline]
 pub unsafe fn pwm_set_chan_level(slice_num: uint, chan: uint, level: u16) {
-    hw_write_masked(
-        &mut (*pwm_hw).slice[slice_num as usize].cc,
-        (level as u32)
-            << (if chan != 0 {
-                PWM_CH0_CC_B_LSB
-            } else {
-                PWM_CH0_CC_A_LSB
-            }),
+    (*pwm_hw).slice[slice_num as usize].cc.write_field(
         if chan != 0 {
-            PWM_CH0_CC_B_BITS
+            PWM_CH0_CC::B
         } else {
-            PWM_CH0_CC_A_BITS
+            PWM_CH0_CC::A
         },
+        level as u32,
     );
 }
 /// \\brief Set PWM counter compare values
@@ -181,8 +175,9 @@ pub unsafe fn pwm_set_chan_level(slice_num: uint, chan: uint, level: u16) {
 /// \\param level_b Value to set compare B to. When the counter reaches this value the B output is deasserted
 #[inline]
 pub unsafe fn pwm_set_both_levels(slice_num: uint, level_a: u16, level_b: u16) {
-    (*pwm_hw).slice[slice_num as usize].cc =
-        ((level_b as u32) << PWM_CH0_CC_B_LSB) | ((level_a as u32) << PWM_CH0_CC_A_LSB);
+    (*pwm_hw).slice[slice_num as usize]
+        .cc
+        .write(PWM_CH0_CC::B.value(level_b as u32) | PWM_CH0_CC::A.value(level_a as u32));
 }
 /// \\brief Helper function to set the PWM level for the slice and channel associated with a GPIO.
 ///  \\ingroup hardware_pwm
@@ -212,7 +207,7 @@ pub unsafe fn pwm_set_gpio_level(gpio: uint, level: u16) {
 /// \\return Current value of PWM counter
 #[inline]
 pub unsafe fn pwm_get_counter(slice_num: uint) -> i16 {
-    (*pwm_hw).slice[slice_num as usize].ctr as i16
+    (*pwm_hw).slice[slice_num as usize].ctr.read() as i16
 }
 /// \\brief Set PWM counter
 ///  \\ingroup hardware_pwm
@@ -224,7 +219,7 @@ pub unsafe fn pwm_get_counter(slice_num: uint) -> i16 {
 ///
 #[inline]
 pub unsafe fn pwm_set_counter(slice_num: uint, c: u16) {
-    (*pwm_hw).slice[slice_num as usize].ctr = c as u32;
+    (*pwm_hw).slice[slice_num as usize].ctr.write(c as u32);
 }
 /// \\brief Advance PWM count
 ///  \\ingroup hardware_pwm
@@ -236,11 +231,9 @@ pub unsafe fn pwm_set_counter(slice_num: uint, c: u16) {
 /// \\param slice_num PWM slice number
 #[inline]
 pub unsafe fn pwm_advance_count(slice_num: uint) {
-    hw_set_bits(
-        &mut (*pwm_hw).slice[slice_num as usize].csr,
-        PWM_CH0_CSR_PH_ADV_BITS,
-    );
-    while ((*pwm_hw).slice[slice_num as usize].csr & PWM_CH0_CSR_PH_ADV_BITS) != 0 {}
+    let csr = &(*pwm_hw).slice[slice_num as usize].csr;
+    csr.set_field(PWM_CH0_CSR::PH_ADV);
+    while csr.is_set(PWM_CH0_CSR::PH_ADV) {}
 }
 /// \\brief Retard PWM count
 ///  \\ingroup hardware_pwm
@@ -252,11 +245,9 @@ pub unsafe fn pwm_advance_count(slice_num: uint) {
 /// \\param slice_num PWM slice number
 #[inline]
 pub unsafe fn pwm_retard_count(slice_num: uint) {
-    hw_set_bits(
-        &mut (*pwm_hw).slice[slice_num as usize].csr,
-        PWM_CH0_CSR_PH_RET_BITS,
-    );
-    while ((*pwm_hw).slice[slice_num as usize].csr & PWM_CH0_CSR_PH_RET_BITS) != 0 {}
+    let csr = &(*pwm_hw).slice[slice_num as usize].csr;
+    csr.set_field(PWM_CH0_CSR::PH_RET);
+    while csr.is_set(PWM_CH0_CSR::PH_RET) {}
 }
 /// \\brief Set PWM clock divider using an 8:4 fractional value
 ///  \\ingroup hardware_pwm
@@ -268,8 +259,9 @@ pub unsafe fn pwm_retard_count(slice_num: uint) {
 /// \\param fract 4 bit fractional part of the clock divider
 #[inline]
 pub unsafe fn pwm_set_clkdiv_int_frac(slice_num: uint, integer: u8, fract: u8) {
-    (*pwm_hw).slice[slice_num as usize].div =
-        ((integer as u32) << PWM_CH0_DIV_INT_LSB) | ((fract as u32) << PWM_CH0_DIV_FRAC_LSB);
+    (*pwm_hw).slice[slice_num as usize]
+        .div
+        .write(PWM_CH0_DIV::INT.value(integer as u32) | PWM_CH0_DIV::FRAC.value(fract as u32));
 }
 /// \\brief Set PWM clock divider
 ///  \\ingroup hardware_pwm
@@ -292,10 +284,10 @@ pub unsafe fn pwm_set_clkdiv(slice_num: uint, divider: f32) {
 /// \\param b true to invert output B
 #[inline]
 pub unsafe fn pwm_set_output_polarity(slice_num: uint, a: bool, b: bool) {
-    hw_write_masked(
-        &mut (*pwm_hw).slice[slice_num as usize].csr,
-        (a as u32) << PWM_CH0_CSR_A_INV_LSB | (b as u32) << PWM_CH0_CSR_B_INV_LSB,
-        PWM_CH0_CSR_A_INV_BITS | PWM_CH0_CSR_B_INV_BITS,
+    let csr = &(*pwm_hw).slice[slice_num as usize].csr;
+    csr.write_masked(
+        PWM_CH0_CSR::A_INV.value(a as u32) | PWM_CH0_CSR::B_INV.value(b as u32),
+        PWM_CH0_CSR::A_INV.bits() | PWM_CH0_CSR::B_INV.bits(),
     );
 }
 /// \\brief Set PWM divider mode
@@ -305,11 +297,9 @@ pub unsafe fn pwm_set_output_polarity(slice_num: uint, a: bool, b: bool) {
 /// \\param mode Required divider mode
 #[inline]
 pub unsafe fn pwm_set_clkdiv_mode(slice_num: uint, mode: pwm_clkdiv_mode) {
-    hw_write_masked(
-        &mut (*pwm_hw).slice[slice_num as usize].csr,
-        mode << PWM_CH0_CSR_DIVMODE_LSB,
-        PWM_CH0_CSR_DIVMODE_BITS,
-    );
+    (*pwm_hw).slice[slice_num as usize]
+        .csr
+        .write_field(PWM_CH0_CSR::DIVMODE, mode);
 }
 /// \\brief Set PWM phase correct on/off
 ///  \\ingroup hardware_pwm
@@ -321,11 +311,9 @@ pub unsafe fn pwm_set_clkdiv_mode(slice_num: uint, mode: pwm_clkdiv_mode) {
 /// the PWM starts counting back down. The output frequency is halved when phase-correct mode is enabled.
 #[inline]
 pub unsafe fn pwm_set_phase_correct(slice_num: uint, phase_correct: bool) {
-    hw_write_masked(
-        &mut (*pwm_hw).slice[slice_num as usize].csr,
-        (phase_correct as u32) << PWM_CH0_CSR_PH_CORRECT_LSB,
-        PWM_CH0_CSR_PH_CORRECT_BITS,
-    );
+    (*pwm_hw).slice[slice_num as usize]
+        .csr
+        .write_field(PWM_CH0_CSR::PH_CORRECT, phase_correct as u32);
 }
 /// \\brief Enable/Disable PWM
 ///  \\ingroup hardware_pwm
@@ -334,11 +322,9 @@ pub unsafe fn pwm_set_phase_correct(slice_num: uint, phase_correct: bool) {
 /// \\param enabled true to enable the specified PWM, false to disable
 #[inline]
 pub unsafe fn pwm_set_enabled(slice_num: uint, enabled: bool) {
-    hw_write_masked(
-        &mut (*pwm_hw).slice[slice_num as usize].csr,
-        (enabled as u32) << PWM_CH0_CSR_EN_LSB,
-        PWM_CH0_CSR_EN_BITS,
-    );
+    (*pwm_hw).slice[slice_num as usize]
+        .csr
+        .write_field(PWM_CH0_CSR::EN, enabled as u32);
 }
 /// \\brief Enable/Disable multiple PWM slices simultaneously
 ///  \\ingroup hardware_pwm
@@ -346,7 +332,7 @@ pub unsafe fn pwm_set_enabled(slice_num: uint, enabled: bool) {
 /// \\param mask Bitmap of PWMs to enable/disable. Bits 0 to 7 enable slices 0-7 respectively
 #[inline]
 pub unsafe fn pwm_set_mask_enabled(mask: u32) {
-    (*pwm_hw).en = mask;
+    (*pwm_hw).en.write(mask);
 }
 /// \\brief  Enable PWM instance interrupt
 ///  \\ingroup hardware_pwm
@@ -358,9 +344,9 @@ pub unsafe fn pwm_set_mask_enabled(mask: u32) {
 #[inline]
 pub unsafe fn pwm_set_irq_enabled(slice_num: uint, enabled: bool) {
     if enabled {
-        hw_set_bits(&mut (*pwm_hw).inte, 1 << slice_num);
+        (*pwm_hw).inte.set_bits(1 << slice_num);
     } else {
-        hw_clear_bits(&mut (*pwm_hw).inte, 1 << slice_num);
+        (*pwm_hw).inte.clear_bits(1 << slice_num);
     }
 }
 /// \\brief  Enable multiple PWM instance interrupts
@@ -373,9 +359,9 @@ pub unsafe fn pwm_set_irq_enabled(slice_num: uint, enabled: bool) {
 #[inline]
 pub unsafe fn pwm_set_irq_mask_enabled(slice_mask: u32, enabled: bool) {
     if enabled {
-        hw_set_bits(&mut (*pwm_hw).inte, slice_mask);
+        (*pwm_hw).inte.set_bits(slice_mask);
     } else {
-        hw_clear_bits(&mut (*pwm_hw).inte, slice_mask);
+        (*pwm_hw).inte.clear_bits(slice_mask);
     }
 }
 /// \\brief  Clear single PWM channel interrupt
@@ -384,7 +370,7 @@ pub unsafe fn pwm_set_irq_mask_enabled(slice_mask: u32, enabled: bool) {
 /// \\param slice_num PWM slice number
 #[inline]
 pub unsafe fn pwm_clear_irq(slice_num: uint) {
-    (*pwm_hw).intr = 1 << slice_num;
+    (*pwm_hw).intr.write(1 << slice_num);
 }
 /// \\brief  Get PWM interrupt status, raw
 ///  \\ingroup hardware_pwm
@@ -392,7 +378,7 @@ pub unsafe fn pwm_clear_irq(slice_num: uint) {
 /// \\return Bitmask of all PWM interrupts currently set
 #[inline]
 pub unsafe fn pwm_get_irq_status_mask() -> u32 {
-    (*pwm_hw).ints
+    (*pwm_hw).ints.read()
 }
 /// \\brief  Force PWM interrupt
 ///  \\ingroup hardware_pwm
@@ -400,5 +386,5 @@ pub unsafe fn pwm_get_irq_status_mask() -> u32 {
 /// \\param slice_num PWM slice number
 #[inline]
 pub unsafe fn pwm_force_irq(slice_num: uint) {
-    (*pwm_hw).intf = 1 << slice_num;
+    (*pwm_hw).intf.write(1 << slice_num);
 }
@@ -0,0 +1,412 @@
+//! The bootrom's functions, typed. Each is looked up by its code on first use
+//! and cached, `init()` looks them all up at once.
+//!
+//! ```ignore
+//! use rpi_pico_sdk_sys::rom;
+//!
+//! let bits = rom::popcount32(0xf0f0);
+//! rom::memset4(&mut buf, 0);
+//! let y = rom::float::sin(x);
+//! ```
+//!
+//! With the `rom-float-intrinsics` feature, the compiler-builtins float
+//! routines `+ - * /` and the int conversions go to the ROM soft-float
+//! instead, which is smaller and faster on the Cortex-M0+.
+
+use core::mem;
+use core::ptr;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{rom_data_lookup, rom_func_lookup};
+use crate::{
+    ROM_DATA_SOFT_DOUBLE_TABLE, ROM_DATA_SOFT_FLOAT_TABLE, ROM_FUNC_CLZ32,
+    ROM_FUNC_CONNECT_INTERNAL_FLASH, ROM_FUNC_CTZ32, ROM_FUNC_FLASH_ENTER_CMD_XIP,
+    ROM_FUNC_FLASH_EXIT_XIP, ROM_FUNC_FLASH_FLUSH_CACHE, ROM_FUNC_FLASH_RANGE_ERASE,
+    ROM_FUNC_FLASH_RANGE_PROGRAM, ROM_FUNC_MEMCPY, ROM_FUNC_MEMCPY44, ROM_FUNC_MEMSET,
+    ROM_FUNC_MEMSET4, ROM_FUNC_POPCOUNT32, ROM_FUNC_REVERSE32,
+};
+
+/// Address of the bootrom version byte: 1 for the B0 chip, 2 for B1, 3 for B2.
+const ROM_VERSION_ADDR: usize = 0x13;
+
+/// Version of the bootrom.
+#[inline]
+pub fn version() -> u8 {
+    unsafe { ptr::read_volatile(ROM_VERSION_ADDR as *const u8) }
+}
+
+/// A function or table address in the bootrom, looked up once
+struct Cached {
+    code: u32,
+    data: bool,
+    addr: AtomicUsize,
+}
+
+impl Cached {
+    const fn func(code: u32) -> Self {
+        Cached {
+            code,
+            data: false,
+            addr: AtomicUsize::new(0),
+        }
+    }
+
+    const fn data(code: u32) -> Self {
+        Cached {
+            code,
+            data: true,
+            addr: AtomicUsize::new(0),
+        }
+    }
+
+    /// Either core may look it up, both find the same
+    #[inline(always)]
+    fn addr(&self) -> usize {
+        match self.addr.load(Ordering::Relaxed) {
+            0 => self.lookup(),
+            addr => addr,
+        }
+    }
+
+    #[inline(never)]
+    fn lookup(&self) -> usize {
+        let addr = unsafe {
+            if self.data {
+                rom_data_lookup(self.code)
+            } else {
+                rom_func_lookup(self.code)
+            }
+        } as usize;
+        assert!(addr != 0, "not in this bootrom");
+        self.addr.store(addr, Ordering::Relaxed);
+        addr
+    }
+
+    /// As a function pointer type `F`
+    #[inline(always)]
+    unsafe fn get<F: Copy>(&self) -> F {
+        let addr = self.addr();
+        debug_assert_eq!(mem::size_of::<F>(), mem::size_of::<usize>());
+        mem::transmute_copy(&addr)
+    }
+}
+
+static POPCOUNT32: Cached = Cached::func(ROM_FUNC_POPCOUNT32);
+static REVERSE32: Cached = Cached::func(ROM_FUNC_REVERSE32);
+static CLZ32: Cached = Cached::func(ROM_FUNC_CLZ32);
+static CTZ32: Cached = Cached::func(ROM_FUNC_CTZ32);
+static MEMSET: Cached = Cached::func(ROM_FUNC_MEMSET);
+static MEMSET4: Cached = Cached::func(ROM_FUNC_MEMSET4);
+static MEMCPY: Cached = Cached::func(ROM_FUNC_MEMCPY);
+static MEMCPY44: Cached = Cached::func(ROM_FUNC_MEMCPY44);
+static CONNECT_INTERNAL_FLASH: Cached = Cached::func(ROM_FUNC_CONNECT_INTERNAL_FLASH);
+static FLASH_EXIT_XIP: Cached = Cached::func(ROM_FUNC_FLASH_EXIT_XIP);
+static FLASH_RANGE_ERASE: Cached = Cached::func(ROM_FUNC_FLASH_RANGE_ERASE);
+static FLASH_RANGE_PROGRAM: Cached = Cached::func(ROM_FUNC_FLASH_RANGE_PROGRAM);
+static FLASH_FLUSH_CACHE: Cached = Cached::func(ROM_FUNC_FLASH_FLUSH_CACHE);
+static FLASH_ENTER_CMD_XIP: Cached = Cached::func(ROM_FUNC_FLASH_ENTER_CMD_XIP);
+static SOFT_FLOAT_TABLE: Cached = Cached::data(ROM_DATA_SOFT_FLOAT_TABLE);
+static SOFT_DOUBLE_TABLE: Cached = Cached::data(ROM_DATA_SOFT_DOUBLE_TABLE);
+
+static ALL: [&Cached; 16] = [
+    &POPCOUNT32,
+    &REVERSE32,
+    &CLZ32,
+    &CTZ32,
+    &MEMSET,
+    &MEMSET4,
+    &MEMCPY,
+    &MEMCPY44,
+    &CONNECT_INTERNAL_FLASH,
+    &FLASH_EXIT_XIP,
+    &FLASH_RANGE_ERASE,
+    &FLASH_RANGE_PROGRAM,
+    &FLASH_FLUSH_CACHE,
+    &FLASH_ENTER_CMD_XIP,
+    &SOFT_FLOAT_TABLE,
+    &SOFT_DOUBLE_TABLE,
+];
+
+/// Look every function up now. Needed before the flash functions run with
+/// XIP off, the lookup itself runs from flash.
+pub fn init() {
+    for cached in ALL.iter() {
+        cached.addr();
+    }
+}
+
+/// Number of bits set.
+#[inline]
+pub fn popcount32(value: u32) -> u32 {
+    unsafe { POPCOUNT32.get::<extern "C" fn(u32) -> u32>()(value) }
+}
+
+/// Bit order reversed.
+#[inline]
+pub fn reverse32(value: u32) -> u32 {
+    unsafe { REVERSE32.get::<extern "C" fn(u32) -> u32>()(value) }
+}
+
+/// Leading zeros, 32 for 0.
+#[inline]
+pub fn clz32(value: u32) -> u32 {
+    unsafe { CLZ32.get::<extern "C" fn(u32) -> u32>()(value) }
+}
+
+/// Trailing zeros, 32 for 0.
+#[inline]
+pub fn ctz32(value: u32) -> u32 {
+    unsafe { CTZ32.get::<extern "C" fn(u32) -> u32>()(value) }
+}
+
+/// Fill `dst` with `value`.
+#[inline]
+pub fn memset(dst: &mut [u8], value: u8) {
+    unsafe {
+        MEMSET.get::<unsafe extern "C" fn(*mut u8, u8, u32) -> *mut u8>()(
+            dst.as_mut_ptr(),
+            value,
+            dst.len() as u32,
+        );
+    }
+}
+
+/// Fill `dst` with `value`, a word at a time.
+#[inline]
+pub fn memset4(dst: &mut [u32], value: u32) {
+    // the ROM takes the byte, repeated in every byte of the word
+    let [b, ..] = value.to_le_bytes();
+    if value == u32::from_ne_bytes([b; 4]) {
+        unsafe {
+            MEMSET4.get::<unsafe extern "C" fn(*mut u32, u8, u32) -> *mut u32>()(
+                dst.as_mut_ptr(),
+                b,
+                (dst.len() * 4) as u32,
+            );
+        }
+    } else {
+        dst.iter_mut().for_each(|w| *w = value);
+    }
+}
+
+/// Copy `src` into `dst`, of the same length.
+#[inline]
+pub fn memcpy(dst: &mut [u8], src: &[u8]) {
+    assert_eq!(dst.len(), src.len(), "lengths differ");
+    unsafe {
+        MEMCPY.get::<unsafe extern "C" fn(*mut u8, *const u8, u32) -> *mut u8>()(
+            dst.as_mut_ptr(),
+            src.as_ptr(),
+            dst.len() as u32,
+        );
+    }
+}
+
+/// Copy `src` into `dst`, of the same length, a word at a time.
+#[inline]
+pub fn memcpy44(dst: &mut [u32], src: &[u32]) {
+    assert_eq!(dst.len(), src.len(), "lengths differ");
+    unsafe {
+        MEMCPY44.get::<unsafe extern "C" fn(*mut u32, *const u32, u32) -> *mut u32>()(
+            dst.as_mut_ptr(),
+            src.as_ptr(),
+            (dst.len() * 4) as u32,
+        );
+    }
+}
+
+/// The low-level flash functions of the bootrom, what hardware_flash is built
+/// on. In this order: `connect_internal_flash()`, `flash_exit_xip()`, then
+/// erase and program, then `flash_flush_cache()` and `flash_enter_cmd_xip()`.
+///
+/// # Safety
+///
+/// XIP is off in between: call `init()` first, then run from RAM with
+/// interrupts off and the other core out of flash.
+pub mod flash {
+    use super::*;
+
+    /// Restore the QSPI pads and the SSI to talk to the flash.
+    #[inline(always)]
+    pub unsafe fn connect_internal_flash() {
+        CONNECT_INTERNAL_FLASH.get::<unsafe extern "C" fn()>()()
+    }
+
+    /// Leave XIP mode, the flash then takes serial commands.
+    #[inline(always)]
+    pub unsafe fn flash_exit_xip() {
+        FLASH_EXIT_XIP.get::<unsafe extern "C" fn()>()()
+    }
+
+    /// Erase `count` bytes at `addr`, in blocks of `block_size` erased with
+    /// `block_cmd` where possible (e.g. 1 << 16 and 0xd8), 4 KB sectors otherwise.
+    #[inline(always)]
+    pub unsafe fn flash_range_erase(addr: u32, count: usize, block_size: u32, block_cmd: u8) {
+        FLASH_RANGE_ERASE.get::<unsafe extern "C" fn(u32, usize, u32, u8)>()(
+            addr, count, block_size, block_cmd,
+        )
+    }
+
+    /// Program `data` at `addr`, both on 256-byte pages.
+    #[inline(always)]
+    pub unsafe fn flash_range_program(addr: u32, data: &[u8]) {
+        FLASH_RANGE_PROGRAM.get::<unsafe extern "C" fn(u32, *const u8, usize)>()(
+            addr,
+            data.as_ptr(),
+            data.len(),
+        )
+    }
+
+    /// Flush and enable the XIP cache, after erasing or programming.
+    #[inline(always)]
+    pub unsafe fn flash_flush_cache() {
+        FLASH_FLUSH_CACHE.get::<unsafe extern "C" fn()>()()
+    }
+
+    /// Back to XIP, with the slow 03h read command: boot2 sets up a faster one.
+    #[inline(always)]
+    pub unsafe fn flash_enter_cmd_xip() {
+        FLASH_ENTER_CMD_XIP.get::<unsafe extern "C" fn()>()()
+    }
+}
+
+/// Entry `offset` of a soft-float table, as function pointer type `F`
+#[inline(always)]
+unsafe fn table_fn<F: Copy>(table: &Cached, offset: usize) -> F {
+    let entry = (table.addr() + offset) as *const usize;
+    let addr = ptr::read(entry);
+    mem::transmute_copy(&addr)
+}
+
+macro_rules! rom_table_fns {
+    ($table:ident, $t:ty { $($(#[$meta:meta])* $name:ident($($arg:ident),*) = $offset:expr;)* }) => {
+        $(
+            $(#[$meta])*
+            #[inline]
+            pub fn $name($($arg: $t),*) -> $t {
+                unsafe { super::table_fn::<extern "C" fn($($arg: $t),*) -> $t>(&super::$table, $offset)($($arg),*) }
+            }
+        )*
+    };
+}
+
+/// The single precision functions of the soft-float table, correctly rounded
+/// where IEEE 754 requires it.
+pub mod float {
+    rom_table_fns!(SOFT_FLOAT_TABLE, f32 {
+        fadd(a, b) = 0x00;
+        fsub(a, b) = 0x04;
+        fmul(a, b) = 0x08;
+        fdiv(a, b) = 0x0c;
+        sqrt(x) = 0x14;
+        /// For |x| < 128π
+        cos(x) = 0x38;
+        /// For |x| < 128π
+        sin(x) = 0x3c;
+        /// For |x| < 128π
+        tan(x) = 0x40;
+        exp(x) = 0x48;
+        /// Natural logarithm
+        ln(x) = 0x4c;
+    });
+
+    /// `a` converted, exact or correctly rounded.
+    #[inline]
+    pub fn int2float(a: i32) -> f32 {
+        unsafe { super::table_fn::<extern "C" fn(i32) -> f32>(&super::SOFT_FLOAT_TABLE, 0x28)(a) }
+    }
+
+    #[inline]
+    pub fn uint2float(a: u32) -> f32 {
+        unsafe { super::table_fn::<extern "C" fn(u32) -> f32>(&super::SOFT_FLOAT_TABLE, 0x30)(a) }
+    }
+
+    /// Rounded towards -infinity, clamped to the `i32` range.
+    #[inline]
+    pub fn float2int(a: f32) -> i32 {
+        unsafe { super::table_fn::<extern "C" fn(f32) -> i32>(&super::SOFT_FLOAT_TABLE, 0x18)(a) }
+    }
+
+    /// Rounded towards -infinity, clamped to the `u32` range.
+    #[inline]
+    pub fn float2uint(a: f32) -> u32 {
+        unsafe { super::table_fn::<extern "C" fn(f32) -> u32>(&super::SOFT_FLOAT_TABLE, 0x20)(a) }
+    }
+}
+
+/// The double precision functions of the soft-double table.
+pub mod double {
+    rom_table_fns!(SOFT_DOUBLE_TABLE, f64 {
+        dadd(a, b) = 0x00;
+        dsub(a, b) = 0x04;
+        dmul(a, b) = 0x08;
+        ddiv(a, b) = 0x0c;
+        sqrt(x) = 0x14;
+        /// For |x| < 128π
+        cos(x) = 0x38;
+        /// For |x| < 128π
+        sin(x) = 0x3c;
+        /// For |x| < 128π
+        tan(x) = 0x40;
+        exp(x) = 0x48;
+        /// Natural logarithm
+        ln(x) = 0x4c;
+    });
+
+    #[inline]
+    pub fn int2double(a: i32) -> f64 {
+        unsafe { super::table_fn::<extern "C" fn(i32) -> f64>(&super::SOFT_DOUBLE_TABLE, 0x28)(a) }
+    }
+
+    #[inline]
+    pub fn uint2double(a: u32) -> f64 {
+        unsafe { super::table_fn::<extern "C" fn(u32) -> f64>(&super::SOFT_DOUBLE_TABLE, 0x30)(a) }
+    }
+
+    /// Rounded towards -infinity, clamped to the `i32` range.
+    #[inline]
+    pub fn double2int(a: f64) -> i32 {
+        unsafe { super::table_fn::<extern "C" fn(f64) -> i32>(&super::SOFT_DOUBLE_TABLE, 0x18)(a) }
+    }
+
+    /// Rounded towards -infinity, clamped to the `u32` range.
+    #[inline]
+    pub fn double2uint(a: f64) -> u32 {
+        unsafe { super::table_fn::<extern "C" fn(f64) -> u32>(&super::SOFT_DOUBLE_TABLE, 0x20)(a) }
+    }
+}
+
+/// The compiler-builtins routines LLVM calls for `f32` and `f64` arithmetic
+/// on thumbv6m, overridden: ours are found first at link time. Conversions to
+/// integers are left out, Rust truncates towards zero where the ROM rounds
+/// down.
+#[cfg(all(feature = "rom-float-intrinsics", target_arch = "arm"))]
+mod intrinsics {
+    use super::{double, float};
+
+    macro_rules! intrinsics {
+        ($($name:ident($($arg:ident: $t:ty),*) -> $r:ty => $rom:path;)*) => {
+            $(
+                #[no_mangle]
+                pub extern "aapcs" fn $name($($arg: $t),*) -> $r {
+                    $rom($($arg),*)
+                }
+            )*
+        };
+    }
+
+    intrinsics! {
+        __aeabi_fadd(a: f32, b: f32) -> f32 => float::fadd;
+        __aeabi_fsub(a: f32, b: f32) -> f32 => float::fsub;
+        __aeabi_fmul(a: f32, b: f32) -> f32 => float::fmul;
+        __aeabi_fdiv(a: f32, b: f32) -> f32 => float::fdiv;
+        __aeabi_i2f(a: i32) -> f32 => float::int2float;
+        __aeabi_ui2f(a: u32) -> f32 => float::uint2float;
+        __aeabi_dadd(a: f64, b: f64) -> f64 => double::dadd;
+        __aeabi_dsub(a: f64, b: f64) -> f64 => double::dsub;
+        __aeabi_dmul(a: f64, b: f64) -> f64 => double::dmul;
+        __aeabi_ddiv(a: f64, b: f64) -> f64 => double::ddiv;
+        __aeabi_i2d(a: i32) -> f64 => double::int2double;
+        __aeabi_ui2d(a: u32) -> f64 => double::uint2double;
+    }
+}
@@ -0,0 +1,48 @@
+//! hardware_rtc, not in the bindgen output yet. Link `hardware_rtc` to use it.
+use crate::*;
+
+/// Callback function type for RTC alarms
+///  \ingroup hardware_rtc
+///
+/// \sa rtc_set_alarm()
+pub type rtc_callback_t = Option<unsafe extern "C" fn()>;
+
+extern "C" {
+    /// \brief Initialise the RTC system
+    ///  \ingroup hardware_rtc
+    pub fn rtc_init();
+
+    /// \brief Set the RTC to the specified time
+    ///  \ingroup hardware_rtc
+    ///
+    /// \param t Pointer to a \ref datetime_t structure contains time to set
+    /// \return true if set, false if the passed in datetime was invalid.
+    pub fn rtc_set_datetime(t: *mut datetime_t) -> bool;
+
+    /// \brief Get the current time from the RTC
+    ///  \ingroup hardware_rtc
+    ///
+    /// \param t Pointer to a \ref datetime_t structure to receive the current RTC time
+    /// \return true if datetime is valid, false if the RTC is not running.
+    pub fn rtc_get_datetime(t: *mut datetime_t) -> bool;
+
+    /// \brief Is the RTC running?
+    ///  \ingroup hardware_rtc
+    ///
+    pub fn rtc_running() -> bool;
+
+    /// \brief Set a time in the future for the RTC to call a user provided callback
+    ///  \ingroup hardware_rtc
+    ///
+    ///  \param t Pointer to a \ref datetime_t structure containing a time in the future to fire the alarm. Any values set to -1 will not be matched on.
+    ///  \param user_callback pointer to a \ref rtc_callback_t to call when the alarm fires
+    pub fn rtc_set_alarm(t: *mut datetime_t, user_callback: rtc_callback_t);
+
+    /// \brief Enable the RTC alarm (if inactive)
+    ///  \ingroup hardware_rtc
+    pub fn rtc_enable_alarm();
+
+    /// \brief Disable the RTC alarm (if active)
+    ///  \ingroup hardware_rtc
+    pub fn rtc_disable_alarm();
+}
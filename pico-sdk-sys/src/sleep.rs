@@ -0,0 +1,101 @@
+//! What pico-extras' hardware_sleep and hardware_rosc need, neither is part of pico-sdk.
+use crate::*;
+
+// #define rosc_hw ((rosc_hw_t *const)ROSC_BASE)
+pub const rosc_hw: *mut rosc_hw_t = ROSC_BASE as _;
+
+// #define scb_hw ((armv6m_scb_t *const)(PPB_BASE + M0PLUS_CPUID_OFFSET))
+pub const scb_hw: *mut armv6m_scb_t = (PPB_BASE + M0PLUS_CPUID_OFFSET) as _;
+
+// Magic values for ROSC DORMANT and XOSC DORMANT
+pub const ROSC_DORMANT_VALUE_DORMANT: u32 = 0x636f_6d61;
+pub const ROSC_DORMANT_VALUE_WAKE: u32 = 0x7761_6b65;
+pub const ROSC_CTRL_ENABLE_VALUE_DISABLE: u32 = 0xd1e;
+pub const ROSC_CTRL_ENABLE_VALUE_ENABLE: u32 = 0xfab;
+
+// hardware/regs/clocks.h is not in the bindgen output, the clocks kept running
+// in sleep, a bit each in SLEEP_EN0/1 (and WAKE_EN0/1)
+
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_SRAM3_BITS: u32 = 1 << 31;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_SRAM2_BITS: u32 = 1 << 30;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_SRAM1_BITS: u32 = 1 << 29;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_SRAM0_BITS: u32 = 1 << 28;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_SPI1_BITS: u32 = 1 << 27;
+pub const CLOCKS_SLEEP_EN0_CLK_PERI_SPI1_BITS: u32 = 1 << 26;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_SPI0_BITS: u32 = 1 << 25;
+pub const CLOCKS_SLEEP_EN0_CLK_PERI_SPI0_BITS: u32 = 1 << 24;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_SIO_BITS: u32 = 1 << 23;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_RTC_BITS: u32 = 1 << 22;
+pub const CLOCKS_SLEEP_EN0_CLK_RTC_RTC_BITS: u32 = 1 << 21;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_ROSC_BITS: u32 = 1 << 20;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_ROM_BITS: u32 = 1 << 19;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_RESETS_BITS: u32 = 1 << 18;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_PWM_BITS: u32 = 1 << 17;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_PSM_BITS: u32 = 1 << 16;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_PLL_USB_BITS: u32 = 1 << 15;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_PLL_SYS_BITS: u32 = 1 << 14;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_PIO1_BITS: u32 = 1 << 13;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_PIO0_BITS: u32 = 1 << 12;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_PADS_BITS: u32 = 1 << 11;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_VREG_AND_CHIP_RESET_BITS: u32 = 1 << 10;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_JTAG_BITS: u32 = 1 << 9;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_IO_BITS: u32 = 1 << 8;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_I2C1_BITS: u32 = 1 << 7;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_I2C0_BITS: u32 = 1 << 6;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_DMA_BITS: u32 = 1 << 5;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_BUSFABRIC_BITS: u32 = 1 << 4;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_BUSCTRL_BITS: u32 = 1 << 3;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_ADC_BITS: u32 = 1 << 2;
+pub const CLOCKS_SLEEP_EN0_CLK_ADC_ADC_BITS: u32 = 1 << 1;
+pub const CLOCKS_SLEEP_EN0_CLK_SYS_CLOCKS_BITS: u32 = 1 << 0;
+
+pub const CLOCKS_SLEEP_EN1_CLK_SYS_XOSC_BITS: u32 = 1 << 14;
+pub const CLOCKS_SLEEP_EN1_CLK_SYS_XIP_BITS: u32 = 1 << 13;
+pub const CLOCKS_SLEEP_EN1_CLK_SYS_WATCHDOG_BITS: u32 = 1 << 12;
+pub const CLOCKS_SLEEP_EN1_CLK_USB_USBCTRL_BITS: u32 = 1 << 11;
+pub const CLOCKS_SLEEP_EN1_CLK_SYS_USBCTRL_BITS: u32 = 1 << 10;
+pub const CLOCKS_SLEEP_EN1_CLK_SYS_UART1_BITS: u32 = 1 << 9;
+pub const CLOCKS_SLEEP_EN1_CLK_PERI_UART1_BITS: u32 = 1 << 8;
+pub const CLOCKS_SLEEP_EN1_CLK_SYS_UART0_BITS: u32 = 1 << 7;
+pub const CLOCKS_SLEEP_EN1_CLK_PERI_UART0_BITS: u32 = 1 << 6;
+pub const CLOCKS_SLEEP_EN1_CLK_SYS_TIMER_BITS: u32 = 1 << 5;
+pub const CLOCKS_SLEEP_EN1_CLK_SYS_TBMAN_BITS: u32 = 1 << 4;
+pub const CLOCKS_SLEEP_EN1_CLK_SYS_SYSINFO_BITS: u32 = 1 << 3;
+pub const CLOCKS_SLEEP_EN1_CLK_SYS_SYSCFG_BITS: u32 = 1 << 2;
+pub const CLOCKS_SLEEP_EN1_CLK_SYS_SRAM5_BITS: u32 = 1 << 1;
+pub const CLOCKS_SLEEP_EN1_CLK_SYS_SRAM4_BITS: u32 = 1 << 0;
+
+/// \brief Set the ring oscillator to dormant
+///  \ingroup hardware_rosc
+///
+/// Stops the ring oscillator until a dormant wake up source fires, blocks till it is
+/// stable again.
+#[inline]
+pub unsafe fn rosc_set_dormant() {
+    (*rosc_hw).dormant.write(ROSC_DORMANT_VALUE_DORMANT);
+    while !(*rosc_hw).status.is_set(ROSC_STATUS::STABLE) {}
+}
+
+/// \brief Disable the ring oscillator
+///  \ingroup hardware_rosc
+///
+/// Nothing may be running from it, blocks till it has stopped.
+#[inline]
+pub unsafe fn rosc_disable() {
+    (*rosc_hw)
+        .ctrl
+        .write_field(ROSC_CTRL::ENABLE, ROSC_CTRL_ENABLE_VALUE_DISABLE);
+    while (*rosc_hw).status.is_set(ROSC_STATUS::STABLE) {}
+}
+
+/// \brief Enable the ring oscillator
+///  \ingroup hardware_rosc
+///
+/// Blocks till it is stable.
+#[inline]
+pub unsafe fn rosc_enable() {
+    (*rosc_hw)
+        .ctrl
+        .write_field(ROSC_CTRL::ENABLE, ROSC_CTRL_ENABLE_VALUE_ENABLE);
+    while !(*rosc_hw).status.is_set(ROSC_STATUS::STABLE) {}
+}
@@ -41,12 +41,11 @@ pub unsafe fn spi_set_format(
     _order: spi_order_t,
 ) {
     // LSB-first not supported on PL022
-    hw_write_masked(
-        &mut (*spi_get_hw(spi)).cr0,
-        (data_bits - 1) << SPI_SSPCR0_DSS_LSB
-            | cpol << SPI_SSPCR0_SPO_LSB
-            | cpha << SPI_SSPCR0_SPH_LSB,
-        SPI_SSPCR0_DSS_BITS | SPI_SSPCR0_SPO_BITS | SPI_SSPCR0_SPH_BITS,
+    (*spi_get_hw(spi)).cr0.write_masked(
+        SPI_SSPCR0::DSS.value(data_bits - 1)
+            | SPI_SSPCR0::SPO.value(cpol)
+            | SPI_SSPCR0::SPH.value(cpha),
+        SPI_SSPCR0::DSS.bits() | SPI_SSPCR0::SPO.bits() | SPI_SSPCR0::SPH.bits(),
     );
 }
 /// \\brief Set SPI master/slave
@@ -59,9 +58,9 @@ pub unsafe fn spi_set_format(
 /// \\param slave true to set SPI device as a slave device, false for master.
 pub unsafe fn spi_set_slave(spi: *mut spi_inst_t, slave: bool) {
     if slave {
-        hw_set_bits(&mut (*spi_get_hw(spi)).cr1, SPI_SSPCR1_MS_BITS);
+        (*spi_get_hw(spi)).cr1.set_field(SPI_SSPCR1::MS);
     } else {
-        hw_clear_bits(&mut (*spi_get_hw(spi)).cr1, SPI_SSPCR1_MS_BITS);
+        (*spi_get_hw(spi)).cr1.clear_field(SPI_SSPCR1::MS);
     }
 }
 /// \\brief Check whether a write can be done on SPI device
@@ -73,7 +72,7 @@ pub unsafe fn spi_set_slave(spi: *mut spi_inst_t, slave: bool) {
 /// \\note Although the controllers each have a 8 deep TX FIFO, the current HW implementation can only return 0 or 1
 /// rather than the space available.
 pub unsafe fn spi_is_writable(spi: *mut spi_inst_t) -> bool {
-    (((*spi_get_hw(spi)).sr & SPI_SSPSR_TNF_BITS) >> SPI_SSPSR_TNF_LSB) != 0
+    (*spi_get_hw(spi)).sr.is_set(SPI_SSPSR::TNF)
 }
 /// \\brief Check whether a read can be done on SPI device
 ///  \\ingroup hardware_spi
@@ -84,5 +83,5 @@ pub unsafe fn spi_is_writable(spi: *mut spi_inst_t) -> bool {
 /// \\note Although the controllers each have a 8 deep RX FIFO, the current HW implementation can only return 0 or 1
 /// rather than the data available.
 pub unsafe fn spi_is_readable(spi: *mut spi_inst_t) -> bool {
-    (((*spi_get_hw(spi)).sr & SPI_SSPSR_RNE_BITS) >> SPI_SSPSR_RNE_LSB) != 0
+    (*spi_get_hw(spi)).sr.is_set(SPI_SSPSR::RNE)
 }
@@ -0,0 +1,52 @@
+//! pico_stdio: its replacements of the libc functions, and the drivers.
+//!
+//! CMake builds link with `-Wl,--wrap=putchar` etc., so `putchar` ends up
+//! in `__wrap_putchar`. Link against those directly, so output goes through
+//! the stdio drivers (UART, USB) with or without the wrapping.
+use crate::ctypes::*;
+
+/// pico/stdio/driver.h, opaque in the headers bindgen sees.
+#[repr(C)]
+pub struct stdio_driver {
+    pub out_chars: Option<unsafe extern "C" fn(buf: *const c_char, len: c_int)>,
+    pub out_flush: Option<unsafe extern "C" fn()>,
+    pub in_chars: Option<unsafe extern "C" fn(buf: *mut c_char, len: c_int) -> c_int>,
+    pub next: *mut stdio_driver_t,
+    // PICO_STDIO_ENABLE_CRLF_SUPPORT
+    pub last_ended_with_cr: bool,
+    pub crlf_enabled: bool,
+}
+pub type stdio_driver_t = stdio_driver;
+
+extern "C" {
+    /// Write a character to all enabled stdio drivers, with CR/LF translation if enabled.
+    #[link_name = "__wrap_putchar"]
+    pub fn putchar(c: c_int) -> c_int;
+
+    /// Write a NUL-terminated string and a newline.
+    #[link_name = "__wrap_puts"]
+    pub fn puts(s: *const c_char) -> c_int;
+
+    /// Block until a character is available on any stdio driver.
+    #[link_name = "__wrap_getchar"]
+    pub fn getchar() -> c_int;
+}
+
+extern "C" {
+    /// pico_stdio_uart's driver, only present when linked in
+    pub static mut stdio_uart: stdio_driver_t;
+
+    /// pico_stdio_usb's driver, only present when linked in
+    pub static mut stdio_usb: stdio_driver_t;
+
+    /// \brief Explicitly initialize stdin/stdout over UART and add it to the current set of stdin/stdout drivers
+    ///  \ingroup pico_stdio_uart
+    ///
+    /// This method sets up PICO_DEFAULT_UART_TX_PIN for UART output (if defined), PICO_DEFAULT_UART_RX_PIN for input (if defined)
+    /// and configures the baud rate as \ref PICO_DEFAULT_UART_BAUD_RATE
+    pub fn stdio_uart_init();
+
+    /// \brief Explicitly initialize USB stdio and add it to the current set of stdin drivers
+    ///  \ingroup pico_stdio_uart
+    pub fn stdio_usb_init() -> bool;
+}
@@ -0,0 +1,648 @@
+//! Hand-written hardware_structs, with typed registers.
+//!
+//! These replace the bindgen output for the register blocks we port helpers
+//! for, so bitfield accesses are checked against the register they belong to.
+
+use crate::volatile::*;
+use crate::*;
+
+register! {
+    /// Control register for an interpolator lane, lane 0's layout: BLEND is
+    /// only on INTERP0, CLAMP only on INTERP1, both only in lane 0
+    SIO_INTERP_CTRL_LANE {
+        OVERF => SIO_INTERP0_CTRL_LANE0_OVERF_LSB, SIO_INTERP0_CTRL_LANE0_OVERF_BITS;
+        OVERF1 => SIO_INTERP0_CTRL_LANE0_OVERF1_LSB, SIO_INTERP0_CTRL_LANE0_OVERF1_BITS;
+        OVERF0 => SIO_INTERP0_CTRL_LANE0_OVERF0_LSB, SIO_INTERP0_CTRL_LANE0_OVERF0_BITS;
+        CLAMP => SIO_INTERP1_CTRL_LANE0_CLAMP_LSB, SIO_INTERP1_CTRL_LANE0_CLAMP_BITS;
+        BLEND => SIO_INTERP0_CTRL_LANE0_BLEND_LSB, SIO_INTERP0_CTRL_LANE0_BLEND_BITS;
+        FORCE_MSB => SIO_INTERP0_CTRL_LANE0_FORCE_MSB_LSB, SIO_INTERP0_CTRL_LANE0_FORCE_MSB_BITS;
+        ADD_RAW => SIO_INTERP0_CTRL_LANE0_ADD_RAW_LSB, SIO_INTERP0_CTRL_LANE0_ADD_RAW_BITS;
+        CROSS_RESULT => SIO_INTERP0_CTRL_LANE0_CROSS_RESULT_LSB, SIO_INTERP0_CTRL_LANE0_CROSS_RESULT_BITS;
+        CROSS_INPUT => SIO_INTERP0_CTRL_LANE0_CROSS_INPUT_LSB, SIO_INTERP0_CTRL_LANE0_CROSS_INPUT_BITS;
+        SIGNED => SIO_INTERP0_CTRL_LANE0_SIGNED_LSB, SIO_INTERP0_CTRL_LANE0_SIGNED_BITS;
+        MASK_MSB => SIO_INTERP0_CTRL_LANE0_MASK_MSB_LSB, SIO_INTERP0_CTRL_LANE0_MASK_MSB_BITS;
+        MASK_LSB => SIO_INTERP0_CTRL_LANE0_MASK_LSB_LSB, SIO_INTERP0_CTRL_LANE0_MASK_LSB_BITS;
+        SHIFT => SIO_INTERP0_CTRL_LANE0_SHIFT_LSB, SIO_INTERP0_CTRL_LANE0_SHIFT_BITS;
+    }
+}
+
+#[repr(C)]
+pub struct interp_hw_t {
+    pub accum: [io_rw_32; 2usize],
+    pub base: [io_rw_32; 3usize],
+    pub pop: [io_ro_32; 3usize],
+    pub peek: [io_ro_32; 3usize],
+    pub ctrl: [RW<u32, SIO_INTERP_CTRL_LANE>; 2usize],
+    pub add_raw: [io_rw_32; 2usize],
+    pub base01: io_wo_32,
+}
+
+#[repr(C)]
+pub struct sio_hw_t {
+    pub cpuid: io_ro_32,
+    pub gpio_in: io_ro_32,
+    pub gpio_hi_in: io_ro_32,
+    pub _pad: u32,
+    // NOTE: GPIO_OUT/GPIO_OE are RW in hardware, io_wo_32 in older SDK headers
+    pub gpio_out: io_rw_32,
+    pub gpio_set: io_wo_32,
+    pub gpio_clr: io_wo_32,
+    pub gpio_togl: io_wo_32,
+    pub gpio_oe: io_rw_32,
+    pub gpio_oe_set: io_wo_32,
+    pub gpio_oe_clr: io_wo_32,
+    pub gpio_oe_togl: io_wo_32,
+    pub gpio_hi_out: io_rw_32,
+    pub gpio_hi_set: io_wo_32,
+    pub gpio_hi_clr: io_wo_32,
+    pub gpio_hi_togl: io_wo_32,
+    pub gpio_hi_oe: io_rw_32,
+    pub gpio_hi_oe_set: io_wo_32,
+    pub gpio_hi_oe_clr: io_wo_32,
+    pub gpio_hi_oe_togl: io_wo_32,
+    pub fifo_st: io_rw_32,
+    pub fifo_wr: io_wo_32,
+    pub fifo_rd: io_ro_32,
+    pub spinlock_st: io_ro_32,
+    pub div_udividend: io_rw_32,
+    pub div_udivisor: io_rw_32,
+    pub div_sdividend: io_rw_32,
+    pub div_sdivisor: io_rw_32,
+    pub div_quotient: io_rw_32,
+    pub div_remainder: io_rw_32,
+    pub div_csr: io_rw_32,
+    pub _pad2: u32,
+    pub interp: [interp_hw_t; 2usize],
+    pub spinlock: [io_rw_32; 32usize],
+}
+
+register! {
+    /// Pad control register for a single GPIO
+    PADS_BANK0_GPIO {
+        OD => PADS_BANK0_GPIO0_OD_LSB, PADS_BANK0_GPIO0_OD_BITS;
+        IE => PADS_BANK0_GPIO0_IE_LSB, PADS_BANK0_GPIO0_IE_BITS;
+        DRIVE => PADS_BANK0_GPIO0_DRIVE_LSB, PADS_BANK0_GPIO0_DRIVE_BITS;
+        PUE => PADS_BANK0_GPIO0_PUE_LSB, PADS_BANK0_GPIO0_PUE_BITS;
+        PDE => PADS_BANK0_GPIO0_PDE_LSB, PADS_BANK0_GPIO0_PDE_BITS;
+        SCHMITT => PADS_BANK0_GPIO0_SCHMITT_LSB, PADS_BANK0_GPIO0_SCHMITT_BITS;
+        SLEWFAST => PADS_BANK0_GPIO0_SLEWFAST_LSB, PADS_BANK0_GPIO0_SLEWFAST_BITS;
+    }
+}
+
+#[repr(C)]
+pub struct padsbank0_hw_t {
+    pub voltage_select: io_rw_32,
+    pub io: [RW<u32, PADS_BANK0_GPIO>; 30usize],
+}
+
+register! {
+    /// PWM slice control and status register
+    PWM_CH0_CSR {
+        PH_ADV => PWM_CH0_CSR_PH_ADV_LSB, PWM_CH0_CSR_PH_ADV_BITS;
+        PH_RET => PWM_CH0_CSR_PH_RET_LSB, PWM_CH0_CSR_PH_RET_BITS;
+        DIVMODE => PWM_CH0_CSR_DIVMODE_LSB, PWM_CH0_CSR_DIVMODE_BITS;
+        B_INV => PWM_CH0_CSR_B_INV_LSB, PWM_CH0_CSR_B_INV_BITS;
+        A_INV => PWM_CH0_CSR_A_INV_LSB, PWM_CH0_CSR_A_INV_BITS;
+        PH_CORRECT => PWM_CH0_CSR_PH_CORRECT_LSB, PWM_CH0_CSR_PH_CORRECT_BITS;
+        EN => PWM_CH0_CSR_EN_LSB, PWM_CH0_CSR_EN_BITS;
+    }
+}
+
+register! {
+    /// PWM slice 8.4 fractional clock divider
+    PWM_CH0_DIV {
+        INT => PWM_CH0_DIV_INT_LSB, PWM_CH0_DIV_INT_BITS;
+        FRAC => PWM_CH0_DIV_FRAC_LSB, PWM_CH0_DIV_FRAC_BITS;
+    }
+}
+
+register! {
+    /// PWM slice counter
+    PWM_CH0_CTR {
+        CTR => PWM_CH0_CTR_LSB, PWM_CH0_CTR_BITS;
+    }
+}
+
+register! {
+    /// PWM slice counter compare values
+    PWM_CH0_CC {
+        B => PWM_CH0_CC_B_LSB, PWM_CH0_CC_B_BITS;
+        A => PWM_CH0_CC_A_LSB, PWM_CH0_CC_A_BITS;
+    }
+}
+
+register! {
+    /// PWM slice counter wrap value
+    PWM_CH0_TOP {
+        TOP => PWM_CH0_TOP_LSB, PWM_CH0_TOP_BITS;
+    }
+}
+
+#[repr(C)]
+pub struct pwm_slice_hw {
+    pub csr: RW<u32, PWM_CH0_CSR>,
+    pub div: RW<u32, PWM_CH0_DIV>,
+    pub ctr: RW<u32, PWM_CH0_CTR>,
+    pub cc: RW<u32, PWM_CH0_CC>,
+    pub top: RW<u32, PWM_CH0_TOP>,
+}
+pub type pwm_slice_hw_t = pwm_slice_hw;
+
+#[repr(C)]
+pub struct pwm_hw_t {
+    pub slice: [pwm_slice_hw_t; 8usize],
+    pub en: io_rw_32,
+    pub intr: io_rw_32,
+    pub inte: io_rw_32,
+    pub intf: io_rw_32,
+    pub ints: io_rw_32,
+}
+
+register! {
+    /// SPI control register 0
+    SPI_SSPCR0 {
+        SCR => SPI_SSPCR0_SCR_LSB, SPI_SSPCR0_SCR_BITS;
+        SPH => SPI_SSPCR0_SPH_LSB, SPI_SSPCR0_SPH_BITS;
+        SPO => SPI_SSPCR0_SPO_LSB, SPI_SSPCR0_SPO_BITS;
+        FRF => SPI_SSPCR0_FRF_LSB, SPI_SSPCR0_FRF_BITS;
+        DSS => SPI_SSPCR0_DSS_LSB, SPI_SSPCR0_DSS_BITS;
+    }
+}
+
+register! {
+    /// SPI control register 1
+    SPI_SSPCR1 {
+        SOD => SPI_SSPCR1_SOD_LSB, SPI_SSPCR1_SOD_BITS;
+        MS => SPI_SSPCR1_MS_LSB, SPI_SSPCR1_MS_BITS;
+        SSE => SPI_SSPCR1_SSE_LSB, SPI_SSPCR1_SSE_BITS;
+        LBM => SPI_SSPCR1_LBM_LSB, SPI_SSPCR1_LBM_BITS;
+    }
+}
+
+register! {
+    /// SPI status register
+    SPI_SSPSR {
+        BSY => SPI_SSPSR_BSY_LSB, SPI_SSPSR_BSY_BITS;
+        RFF => SPI_SSPSR_RFF_LSB, SPI_SSPSR_RFF_BITS;
+        RNE => SPI_SSPSR_RNE_LSB, SPI_SSPSR_RNE_BITS;
+        TNF => SPI_SSPSR_TNF_LSB, SPI_SSPSR_TNF_BITS;
+        TFE => SPI_SSPSR_TFE_LSB, SPI_SSPSR_TFE_BITS;
+    }
+}
+
+register! {
+    /// SPI clock prescale register
+    SPI_SSPCPSR {
+        CPSDVSR => SPI_SSPCPSR_CPSDVSR_LSB, SPI_SSPCPSR_CPSDVSR_BITS;
+    }
+}
+
+#[repr(C)]
+pub struct spi_hw_t {
+    pub cr0: RW<u32, SPI_SSPCR0>,
+    pub cr1: RW<u32, SPI_SSPCR1>,
+    pub dr: io_rw_32,
+    pub sr: RO<u32, SPI_SSPSR>,
+    pub cpsr: RW<u32, SPI_SSPCPSR>,
+    pub imsc: io_rw_32,
+    pub ris: io_ro_32,
+    pub mis: io_ro_32,
+    pub icr: io_rw_32,
+    pub dmacr: io_rw_32,
+}
+
+// hardware/regs/watchdog.h is not in the bindgen output, lsb and mask from the datasheet
+
+register! {
+    /// Watchdog control register
+    WATCHDOG_CTRL {
+        TRIGGER => 31, 0x8000_0000;
+        ENABLE => 30, 0x4000_0000;
+        PAUSE_DBG1 => 26, 0x0400_0000;
+        PAUSE_DBG0 => 25, 0x0200_0000;
+        PAUSE_JTAG => 24, 0x0100_0000;
+        TIME => 0, 0x00ff_ffff;
+    }
+}
+
+register! {
+    /// Logs the reason for the last reset, both bits are zero for the case of a hardware reset
+    WATCHDOG_REASON {
+        FORCE => 1, 0x2;
+        TIMER => 0, 0x1;
+    }
+}
+
+register! {
+    /// Controls the tick generator
+    WATCHDOG_TICK {
+        COUNT => 11, 0x000f_f800;
+        RUNNING => 10, 0x400;
+        ENABLE => 9, 0x200;
+        CYCLES => 0, 0x1ff;
+    }
+}
+
+#[repr(C)]
+pub struct watchdog_hw_t {
+    pub ctrl: RW<u32, WATCHDOG_CTRL>,
+    pub load: io_wo_32,
+    pub reason: RO<u32, WATCHDOG_REASON>,
+    pub scratch: [io_rw_32; 8usize],
+    pub tick: RW<u32, WATCHDOG_TICK>,
+}
+
+// hardware/regs/vreg_and_chip_reset.h is not in the bindgen output either
+
+register! {
+    /// Chip reset control and status
+    VREG_AND_CHIP_RESET_CHIP_RESET {
+        PSM_RESTART_FLAG => 24, 0x0100_0000;
+        HAD_PSM_RESTART => 20, 0x0010_0000;
+        HAD_RUN => 16, 0x0001_0000;
+        HAD_POR => 8, 0x100;
+    }
+}
+
+#[repr(C)]
+pub struct vreg_and_chip_reset_hw_t {
+    pub vreg: io_rw_32,
+    pub bod: io_rw_32,
+    pub chip_reset: RW<u32, VREG_AND_CHIP_RESET_CHIP_RESET>,
+}
+
+// hardware/regs/psm.h is not in the bindgen output, lsb and mask from the datasheet
+
+register! {
+    /// Blocks the watchdog resets, one bit each
+    PSM_WDSEL {
+        PROC1 => 16, 0x0001_0000;
+        PROC0 => 15, 0x8000;
+        SIO => 14, 0x4000;
+        VREG_AND_CHIP_RESET => 13, 0x2000;
+        XIP => 12, 0x1000;
+        SRAM5 => 11, 0x800;
+        SRAM4 => 10, 0x400;
+        SRAM3 => 9, 0x200;
+        SRAM2 => 8, 0x100;
+        SRAM1 => 7, 0x80;
+        SRAM0 => 6, 0x40;
+        ROM => 5, 0x20;
+        BUSFABRIC => 4, 0x10;
+        RESETS => 3, 0x8;
+        CLOCKS => 2, 0x4;
+        XOSC => 1, 0x2;
+        ROSC => 0, 0x1;
+    }
+}
+
+#[repr(C)]
+pub struct psm_hw_t {
+    pub frce_on: io_rw_32,
+    pub frce_off: io_rw_32,
+    pub wdsel: RW<u32, PSM_WDSEL>,
+    pub done: io_ro_32,
+}
+
+#[repr(C)]
+pub struct pll_hw_t {
+    pub cs: io_rw_32,
+    pub pwr: io_rw_32,
+    pub fbdiv_int: io_rw_32,
+    pub prim: io_rw_32,
+}
+
+#[repr(C)]
+pub struct clock_hw_t {
+    pub ctrl: io_rw_32,
+    pub div: io_rw_32,
+    pub selected: io_ro_32,
+}
+
+#[repr(C)]
+pub struct fc_hw_t {
+    pub ref_khz: io_rw_32,
+    pub min_khz: io_rw_32,
+    pub max_khz: io_rw_32,
+    pub delay: io_rw_32,
+    pub interval: io_rw_32,
+    pub src: io_rw_32,
+    pub status: io_ro_32,
+    pub result: io_ro_32,
+}
+
+#[repr(C)]
+pub struct clocks_hw_t {
+    pub clk: [clock_hw_t; 10usize],
+    pub resus_ctrl: io_rw_32,
+    pub resus_status: io_ro_32,
+    pub fc0: fc_hw_t,
+    pub wake_en0: io_rw_32,
+    pub wake_en1: io_rw_32,
+    pub sleep_en0: io_rw_32,
+    pub sleep_en1: io_rw_32,
+    pub enabled0: io_ro_32,
+    pub enabled1: io_ro_32,
+    pub intr: io_ro_32,
+    pub inte: io_rw_32,
+    pub intf: io_rw_32,
+    pub ints: io_ro_32,
+}
+
+// hardware/regs/rosc.h is not in the bindgen output, lsb and mask from the datasheet
+
+register! {
+    /// Ring oscillator control
+    ROSC_CTRL {
+        ENABLE => 12, 0x00ff_f000;
+        FREQ_RANGE => 0, 0xfff;
+    }
+}
+
+register! {
+    /// Ring oscillator status
+    ROSC_STATUS {
+        STABLE => 31, 0x8000_0000;
+        BADWRITE => 24, 0x0100_0000;
+        DIV_RUNNING => 16, 0x0001_0000;
+        ENABLED => 12, 0x1000;
+    }
+}
+
+#[repr(C)]
+pub struct rosc_hw_t {
+    pub ctrl: RW<u32, ROSC_CTRL>,
+    pub freqa: io_rw_32,
+    pub freqb: io_rw_32,
+    pub dormant: io_rw_32,
+    pub div: io_rw_32,
+    pub phase: io_rw_32,
+    pub status: RO<u32, ROSC_STATUS>,
+    pub randombit: io_ro_32,
+    pub count: io_rw_32,
+}
+
+/// The start of the M0+ System Control Block, up to SCR
+#[repr(C)]
+pub struct armv6m_scb_t {
+    pub cpuid: io_ro_32,
+    pub icsr: io_rw_32,
+    pub vtor: io_rw_32,
+    pub aircr: io_rw_32,
+    pub scr: io_rw_32,
+}
+
+// hardware/regs/resets.h is not in the bindgen output
+
+#[repr(C)]
+pub struct resets_hw_t {
+    pub reset: io_rw_32,
+    pub wdsel: io_rw_32,
+    pub reset_done: io_ro_32,
+}
+
+// hardware/regs/usb.h and usb_device_dpram.h are not in the bindgen output,
+// lsb and mask from the datasheet
+
+register! {
+    /// Device address and endpoint control
+    USB_ADDR_ENDP {
+        ENDPOINT => 16, 0x000f_0000;
+        ADDRESS => 0, 0x7f;
+    }
+}
+
+register! {
+    /// Main control register
+    USB_MAIN_CTRL {
+        SIM_TIMING => 31, 0x8000_0000;
+        HOST_NDEVICE => 1, 0x2;
+        CONTROLLER_EN => 0, 0x1;
+    }
+}
+
+register! {
+    /// SIE control register
+    USB_SIE_CTRL {
+        EP0_INT_STALL => 31, 0x8000_0000;
+        EP0_DOUBLE_BUF => 30, 0x4000_0000;
+        EP0_INT_1BUF => 29, 0x2000_0000;
+        EP0_INT_2BUF => 28, 0x1000_0000;
+        EP0_INT_NAK => 27, 0x0800_0000;
+        DIRECT_EN => 26, 0x0400_0000;
+        DIRECT_DP => 25, 0x0200_0000;
+        DIRECT_DM => 24, 0x0100_0000;
+        TRANSCEIVER_PD => 18, 0x0004_0000;
+        RPU_OPT => 17, 0x0002_0000;
+        PULLUP_EN => 16, 0x0001_0000;
+        PULLDOWN_EN => 15, 0x8000;
+        RESET_BUS => 13, 0x2000;
+        RESUME => 12, 0x1000;
+        VBUS_EN => 11, 0x800;
+        KEEP_ALIVE_EN => 10, 0x400;
+        SOF_EN => 9, 0x200;
+        SOF_SYNC => 8, 0x100;
+        PREAMBLE_EN => 6, 0x40;
+        STOP_TRANS => 4, 0x10;
+        RECEIVE_DATA => 3, 0x8;
+        SEND_DATA => 2, 0x4;
+        SEND_SETUP => 1, 0x2;
+        START_TRANS => 0, 0x1;
+    }
+}
+
+register! {
+    /// SIE status register, write 1 to clear
+    USB_SIE_STATUS {
+        DATA_SEQ_ERROR => 31, 0x8000_0000;
+        ACK_REC => 30, 0x4000_0000;
+        STALL_REC => 29, 0x2000_0000;
+        NAK_REC => 28, 0x1000_0000;
+        RX_TIMEOUT => 27, 0x0800_0000;
+        RX_OVERFLOW => 26, 0x0400_0000;
+        BIT_STUFF_ERROR => 25, 0x0200_0000;
+        CRC_ERROR => 24, 0x0100_0000;
+        BUS_RESET => 19, 0x0008_0000;
+        TRANS_COMPLETE => 18, 0x0004_0000;
+        SETUP_REC => 17, 0x0002_0000;
+        CONNECTED => 16, 0x0001_0000;
+        RESUME => 11, 0x800;
+        VBUS_OVER_CURR => 10, 0x400;
+        SPEED => 8, 0x300;
+        SUSPENDED => 4, 0x10;
+        LINE_STATE => 2, 0xc;
+        VBUS_DETECTED => 0, 0x1;
+    }
+}
+
+register! {
+    /// Device: stall arm for EP0, cleared on the next SETUP
+    USB_EP_STALL_ARM {
+        EP0_OUT => 1, 0x2;
+        EP0_IN => 0, 0x1;
+    }
+}
+
+register! {
+    /// Where to connect the USB controller
+    USB_USB_MUXING {
+        SOFTCON => 3, 0x8;
+        TO_DIGITAL_PAD => 2, 0x4;
+        TO_EXTPHY => 1, 0x2;
+        TO_PHY => 0, 0x1;
+    }
+}
+
+register! {
+    /// Overrides for the power signals in the event that the VBUS signals are not hooked up to GPIO
+    USB_USB_PWR {
+        OVERCURR_DETECT_EN => 5, 0x20;
+        OVERCURR_DETECT => 4, 0x10;
+        VBUS_DETECT_OVERRIDE_EN => 3, 0x8;
+        VBUS_DETECT => 2, 0x4;
+        VBUS_EN_OVERRIDE_EN => 1, 0x2;
+        VBUS_EN => 0, 0x1;
+    }
+}
+
+register! {
+    /// Interrupts, the same bits in INTR, INTE, INTF and INTS
+    USB_INTR {
+        EP_STALL_NAK => 19, 0x0008_0000;
+        ABORT_DONE => 18, 0x0004_0000;
+        DEV_SOF => 17, 0x0002_0000;
+        SETUP_REQ => 16, 0x0001_0000;
+        DEV_RESUME_FROM_HOST => 15, 0x8000;
+        DEV_SUSPEND => 14, 0x4000;
+        DEV_CONN_DIS => 13, 0x2000;
+        BUS_RESET => 12, 0x1000;
+        VBUS_DETECT => 11, 0x800;
+        STALL => 10, 0x400;
+        ERROR_CRC => 9, 0x200;
+        ERROR_BIT_STUFF => 8, 0x100;
+        ERROR_RX_OVERFLOW => 7, 0x80;
+        ERROR_RX_TIMEOUT => 6, 0x40;
+        ERROR_DATA_SEQ => 5, 0x20;
+        BUFF_STATUS => 4, 0x10;
+        TRANS_COMPLETE => 3, 0x8;
+        HOST_SOF => 2, 0x4;
+        HOST_RESUME => 1, 0x2;
+        HOST_CONN_DIS => 0, 0x1;
+    }
+}
+
+#[repr(C)]
+pub struct usb_hw_t {
+    pub dev_addr_ctrl: RW<u32, USB_ADDR_ENDP>,
+    pub int_ep_addr_ctrl: [io_rw_32; 15usize],
+    pub main_ctrl: RW<u32, USB_MAIN_CTRL>,
+    pub sof_rw: io_rw_32,
+    pub sof_rd: io_ro_32,
+    pub sie_ctrl: RW<u32, USB_SIE_CTRL>,
+    pub sie_status: RW<u32, USB_SIE_STATUS>,
+    pub int_ep_ctrl: io_rw_32,
+    /// A bit per endpoint and direction, IN at 2n, OUT at 2n + 1. Write 1 to clear
+    pub buf_status: io_rw_32,
+    pub buf_cpu_should_handle: io_ro_32,
+    pub abort: io_rw_32,
+    pub abort_done: io_rw_32,
+    pub ep_stall_arm: RW<u32, USB_EP_STALL_ARM>,
+    pub nak_poll: io_rw_32,
+    pub ep_nak_stall_status: io_rw_32,
+    pub muxing: RW<u32, USB_USB_MUXING>,
+    pub pwr: RW<u32, USB_USB_PWR>,
+    pub phy_direct: io_rw_32,
+    pub phy_direct_override: io_rw_32,
+    pub phy_trim: io_rw_32,
+    pub _pad0: u32,
+    pub intr: RO<u32, USB_INTR>,
+    pub inte: RW<u32, USB_INTR>,
+    pub intf: RW<u32, USB_INTR>,
+    pub ints: RO<u32, USB_INTR>,
+}
+
+register! {
+    /// Endpoint control, in DPRAM: EP1 to EP15, IN and OUT
+    USB_EP_CTRL {
+        ENABLE => 31, 0x8000_0000;
+        DOUBLE_BUFFERED => 30, 0x4000_0000;
+        INTERRUPT_PER_BUFF => 29, 0x2000_0000;
+        INTERRUPT_PER_DOUBLE_BUFF => 28, 0x1000_0000;
+        ENDPOINT_TYPE => 26, 0x0c00_0000;
+        INTERRUPT_ON_STALL => 17, 0x0002_0000;
+        INTERRUPT_ON_NAK => 16, 0x0001_0000;
+        BUFFER_ADDRESS => 0, 0xffff;
+    }
+}
+
+register! {
+    /// Endpoint buffer control, in DPRAM: buffer 0 in the low half, buffer 1 in the high half
+    USB_BUF_CTRL {
+        FULL_1 => 31, 0x8000_0000;
+        LAST_1 => 30, 0x4000_0000;
+        DATA_PID_1 => 29, 0x2000_0000;
+        AVAILABLE_1 => 26, 0x0400_0000;
+        LEN_1 => 16, 0x03ff_0000;
+        FULL => 15, 0x8000;
+        LAST => 14, 0x4000;
+        DATA_PID => 13, 0x2000;
+        RESET => 12, 0x1000;
+        STALL => 11, 0x800;
+        AVAILABLE => 10, 0x400;
+        LEN => 0, 0x3ff;
+    }
+}
+
+#[repr(C)]
+pub struct usb_ep_ctrl_t {
+    pub in_: RW<u32, USB_EP_CTRL>,
+    pub out: RW<u32, USB_EP_CTRL>,
+}
+
+#[repr(C)]
+pub struct usb_buf_ctrl_t {
+    pub in_: RW<u32, USB_BUF_CTRL>,
+    pub out: RW<u32, USB_BUF_CTRL>,
+}
+
+/// The 4 KB of USB DPRAM, in device mode
+#[repr(C)]
+pub struct usb_device_dpram_t {
+    pub setup_packet: [io_ro_8; 8usize],
+    pub ep_ctrl: [usb_ep_ctrl_t; 15usize],
+    pub ep_buf_ctrl: [usb_buf_ctrl_t; 16usize],
+    /// EP0 IN and OUT share buffer 0
+    pub ep0_buf_a: [u8; 64usize],
+    pub ep0_buf_b: [u8; 64usize],
+    pub epx_data: [u8; 3712usize],
+}
+
+// hardware/regs/adc.h is not in the bindgen output, lsb and mask from the datasheet
+
+register! {
+    /// ADC control and status
+    ADC_CS {
+        RROBIN => 16, 0x001f_0000;
+        AINSEL => 12, 0x7000;
+        ERR_STICKY => 10, 0x400;
+        ERR => 9, 0x200;
+        READY => 8, 0x100;
+        START_MANY => 3, 0x8;
+        START_ONCE => 2, 0x4;
+        TS_EN => 1, 0x2;
+        EN => 0, 0x1;
+    }
+}
+
+#[repr(C)]
+pub struct adc_hw_t {
+    pub cs: RW<u32, ADC_CS>,
+    pub result: io_ro_32,
+    pub fcs: io_rw_32,
+    pub fifo: io_ro_32,
+    pub div: io_rw_32,
+    pub intr: io_ro_32,
+    pub inte: io_rw_32,
+    pub intf: io_rw_32,
+    pub ints: io_ro_32,
+}
@@ -0,0 +1,309 @@
+//! hardware_sync, not in the bindgen output yet: the inline asm parts and spin locks.
+
+use crate::uint;
+
+/// \brief Save and disable interrupts
+///  \ingroup hardware_sync
+///
+/// \return The prior interrupt enable status for restoration later via restore_interrupts()
+#[inline(always)]
+pub unsafe fn save_and_disable_interrupts() -> u32 {
+    #[cfg(target_arch = "arm")]
+    {
+        let status: u32;
+        core::arch::asm!("mrs {}, PRIMASK", "cpsid i", out(reg) status);
+        status
+    }
+    #[cfg(not(target_arch = "arm"))]
+    0
+}
+
+/// \brief Restore interrupts to a specified state
+///  \ingroup hardware_sync
+///
+/// \param status Previous interrupt status from save_and_disable_interrupts()
+#[inline(always)]
+pub unsafe fn restore_interrupts(status: u32) {
+    #[cfg(target_arch = "arm")]
+    core::arch::asm!("msr PRIMASK, {}", in(reg) status);
+    #[cfg(not(target_arch = "arm"))]
+    let _ = status;
+}
+
+/// \brief Insert a DMB instruction in to the code path.
+///  \ingroup hardware_sync
+///
+/// The DMB (data memory barrier) acts as a memory barrier, all memory accesses prior to this
+/// instruction will be observed before any explicit access after the instruction.
+#[inline(always)]
+pub fn __dmb() {
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("dmb");
+    }
+    #[cfg(not(target_arch = "arm"))]
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+/// \brief Insert a SEV instruction in to the code path.
+///  \ingroup hardware_sync
+///
+/// The SEV (send event) instruction sends an event to both cores.
+#[inline(always)]
+pub fn __sev() {
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("sev");
+    }
+}
+
+/// \brief Insert a WFE instruction in to the code path.
+///  \ingroup hardware_sync
+///
+/// The WFE (wait for event) instruction waits until one of a number of
+/// events occurs, including events signalled by the SEV instruction on either core.
+#[inline(always)]
+pub fn __wfe() {
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("wfe");
+    }
+}
+
+/// \brief Insert a WFI instruction in to the code path.
+///  \ingroup hardware_sync
+///
+/// The WFI (wait for interrupt) instruction waits for an interrupt to wake up the core.
+#[inline(always)]
+pub fn __wfi() {
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("wfi");
+    }
+}
+
+/// \brief Busy wait wasting cycles for the given (32 bit) number of cycles
+///  \ingroup pico_platform
+///
+/// \param minimum_cycles the minimum number of system clock cycles to delay for
+#[inline(always)]
+pub fn busy_wait_at_least_cycles(minimum_cycles: u32) {
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!(
+            "1: subs {0}, #3",
+            "bcs 1b",
+            inout(reg) minimum_cycles => _,
+            options(nostack),
+        );
+    }
+    #[cfg(not(target_arch = "arm"))]
+    let _ = minimum_cycles;
+}
+
+/// \brief Get the current core number
+///  \ingroup hardware_sync
+///
+/// \return The core number the call was made from
+///
+/// # Safety
+///
+/// Reads the SIO block, only on the RP2040 itself.
+#[inline(always)]
+pub unsafe fn get_core_num() -> uint {
+    (*(crate::SIO_BASE as *const crate::sio_hw_t)).cpuid.read()
+}
+
+/// A hardware spin lock, reading claims it, writing releases it.
+pub type spin_lock_t = crate::io_rw_32;
+
+// Spin locks used by the SDK, 0-15 are reserved for it
+pub const PICO_SPINLOCK_ID_IRQ: uint = 9;
+pub const PICO_SPINLOCK_ID_TIMER: uint = 10;
+pub const PICO_SPINLOCK_ID_HARDWARE_CLAIM: uint = 11;
+/// Reserved for an RTOS, or similar
+pub const PICO_SPINLOCK_ID_OS1: uint = 14;
+pub const PICO_SPINLOCK_ID_OS2: uint = 15;
+pub const PICO_SPINLOCK_ID_STRIPED_FIRST: uint = 16;
+pub const PICO_SPINLOCK_ID_STRIPED_LAST: uint = 23;
+pub const PICO_SPINLOCK_ID_CLAIM_FREE_FIRST: uint = 24;
+pub const PICO_SPINLOCK_ID_CLAIM_FREE_END: uint = 31;
+
+/// \brief Acquire a memory fence
+///  \ingroup hardware_sync
+#[inline(always)]
+pub fn __mem_fence_acquire() {
+    __dmb();
+}
+
+/// \brief Release a memory fence
+///  \ingroup hardware_sync
+#[inline(always)]
+pub fn __mem_fence_release() {
+    __dmb();
+}
+
+/// \brief Get HW Spinlock instance from number
+///  \ingroup hardware_sync
+///
+/// \param lock_num Spinlock ID
+/// \return The spinlock instance
+///
+/// Panics if there's no such spinlock, `lock_num` 32 or more.
+#[inline(always)]
+pub fn spin_lock_instance(lock_num: uint) -> *mut spin_lock_t {
+    assert!(
+        lock_num < crate::NUM_SPIN_LOCKS,
+        "no spin lock {}",
+        lock_num
+    );
+    unsafe {
+        core::ptr::addr_of_mut!(
+            (*(crate::SIO_BASE as *mut crate::sio_hw_t)).spinlock[lock_num as usize]
+        )
+    }
+}
+
+/// \brief Get HW Spinlock number from instance
+///  \ingroup hardware_sync
+///
+/// \param lock The Spinlock instance
+/// \return The Spinlock ID
+#[inline(always)]
+pub fn spin_lock_get_num(lock: *mut spin_lock_t) -> uint {
+    ((lock as usize - crate::SIO_BASE as usize - crate::SIO_SPINLOCK0_OFFSET as usize)
+        / core::mem::size_of::<spin_lock_t>()) as uint
+}
+
+/// \brief Acquire a spin lock without disabling interrupts (hence unsafe)
+///  \ingroup hardware_sync
+///
+/// \param lock Spinlock instance
+#[inline(always)]
+pub unsafe fn spin_lock_unsafe_blocking(lock: *mut spin_lock_t) {
+    // Note we don't do a wfe or anything, because by convention these spin_locks are VERY SHORT LIVED and NEVER BLOCK and run
+    // with INTERRUPTS disabled (to ensure that)... therefore nothing on our core could be blocking us, so we just need to wait on another core
+    // anyway which should be finished soon
+    while (*lock).read() == 0 {}
+    __mem_fence_acquire();
+}
+
+/// \brief Release a spin lock without re-enabling interrupts
+///  \ingroup hardware_sync
+///
+/// \param lock Spinlock instance
+#[inline(always)]
+pub unsafe fn spin_unlock_unsafe(lock: *mut spin_lock_t) {
+    __mem_fence_release();
+    (*lock).write(0);
+}
+
+/// \brief Acquire a spin lock safely
+///  \ingroup hardware_sync
+///
+/// This function will disable interrupts prior to acquiring the spinlock
+///
+/// \param lock Spinlock instance
+/// \return interrupt status to be used when unlocking, to restore to original state
+#[inline(always)]
+pub unsafe fn spin_lock_blocking(lock: *mut spin_lock_t) -> u32 {
+    let save = save_and_disable_interrupts();
+    spin_lock_unsafe_blocking(lock);
+    save
+}
+
+/// \brief Check to see if a spinlock is currently acquired elsewhere.
+///  \ingroup hardware_sync
+///
+/// \param lock Spinlock instance
+#[inline(always)]
+pub unsafe fn is_spin_locked(lock: *mut spin_lock_t) -> bool {
+    let lock_num = spin_lock_get_num(lock);
+    (*(crate::SIO_BASE as *const crate::sio_hw_t))
+        .spinlock_st
+        .read()
+        & (1 << lock_num)
+        != 0
+}
+
+/// \brief Release a spin lock safely
+///  \ingroup hardware_sync
+///
+/// This function will re-enable interrupts according to the parameters.
+///
+/// \param lock Spinlock instance
+/// \param saved_irq Return value from the \ref spin_lock_blocking() function.
+/// \return interrupt status to be used when unlocking, to restore to original state
+///
+/// \sa spin_lock_blocking()
+#[inline(always)]
+pub unsafe fn spin_unlock(lock: *mut spin_lock_t, saved_irq: u32) {
+    spin_unlock_unsafe(lock);
+    restore_interrupts(saved_irq);
+}
+
+extern "C" {
+    /// \brief Initialise a spin lock
+    ///  \ingroup hardware_sync
+    ///
+    /// The spin lock is initially unlocked
+    ///
+    /// \param lock_num The spin lock number
+    /// \return The spin lock instance
+    pub fn spin_lock_init(lock_num: uint) -> *mut spin_lock_t;
+
+    /// \brief Release all spin locks
+    ///  \ingroup hardware_sync
+    pub fn spin_locks_reset();
+
+    /// \brief Return a spin lock number from the _striped_ range
+    ///  \ingroup hardware_sync
+    ///
+    /// Returns a spin lock number in the range PICO_SPINLOCK_ID_STRIPED_FIRST to PICO_SPINLOCK_ID_STRIPED_LAST
+    /// in a round robin fashion. This does not grant the caller exclusive access to the spin lock, so the caller
+    /// must:
+    ///
+    /// -# Abide (with other callers) by the contract of only holding this spin lock briefly (and with IRQs off - the default via \ref spin_lock_blocking()),
+    /// and not whilst holding other spin locks.
+    /// -# Be OK with any contention caused by the - brief due to the above requirement - contention with other possible users of the spin lock.
+    ///
+    /// \return lock_num a spin lock number the caller may use (non exclusively)
+    /// \see PICO_SPINLOCK_ID_STRIPED_FIRST
+    /// \see PICO_SPINLOCK_ID_STRIPED_LAST
+    pub fn next_striped_spin_lock_num() -> uint;
+
+    /// \brief Mark a spin lock as used
+    ///  \ingroup hardware_sync
+    ///
+    /// Method for cooperative claiming of hardware. Will cause a panic if the spin lock
+    /// is already claimed. Use of this method by libraries detects accidental
+    /// configurations that would fail in unpredictable ways.
+    ///
+    /// \param lock_num the spin lock number
+    pub fn spin_lock_claim(lock_num: uint);
+
+    /// \brief Mark multiple spin locks as used
+    ///  \ingroup hardware_sync
+    ///
+    /// Method for cooperative claiming of hardware. Will cause a panic if any of the spin locks
+    /// are already claimed. Use of this method by libraries detects accidental
+    /// configurations that would fail in unpredictable ways.
+    ///
+    /// \param lock_num_mask Bitfield of all required spin locks to claim (bit 0 == spin lock 0, bit 1 == spin lock 1 etc)
+    pub fn spin_lock_claim_mask(lock_num_mask: u32);
+
+    /// \brief Mark a spin lock as no longer used
+    ///  \ingroup hardware_sync
+    ///
+    /// Method for cooperative claiming of hardware.
+    ///
+    /// \param lock_num the spin lock number to release
+    pub fn spin_lock_unclaim(lock_num: uint);
+
+    /// \brief Claim a free spin lock
+    ///  \ingroup hardware_sync
+    ///
+    /// \param required if true the function will panic if none are available
+    /// \return the spin lock number or -1 if required was false, and none were free
+    pub fn spin_lock_claim_unused(required: bool) -> crate::ctypes::c_int;
+}
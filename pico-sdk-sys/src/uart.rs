@@ -0,0 +1,88 @@
+use crate::*;
+
+// #define uart0_hw ((uart_hw_t *const)UART0_BASE)
+// #define uart1_hw ((uart_hw_t *const)UART1_BASE)
+const uart0_hw: *mut uart_hw_t = UART0_BASE as _;
+const uart1_hw: *mut uart_hw_t = UART1_BASE as _;
+
+pub const uart0: *mut uart_inst_t = uart0_hw as *mut uart_inst_t;
+pub const uart1: *mut uart_inst_t = uart1_hw as *mut uart_inst_t;
+
+/// \brief Convert UART instance to hardware instance number
+///  \ingroup hardware_uart
+///
+/// \param uart UART instance
+/// \return Number of UART, 0 or 1.
+#[inline]
+pub unsafe fn uart_get_index(uart: *mut uart_inst_t) -> uint {
+    (uart == uart1) as uint
+}
+
+#[inline]
+pub unsafe fn uart_get_hw(uart: *mut uart_inst_t) -> *mut uart_hw_t {
+    // NOTE: skip check
+    uart as *mut uart_hw_t
+}
+
+/// \brief Determine if space is available in the TX FIFO
+///  \ingroup hardware_uart
+///
+/// \param uart UART instance. \ref uart0 or \ref uart1
+/// \return false if no space available, true otherwise
+#[inline]
+pub unsafe fn uart_is_writable(uart: *mut uart_inst_t) -> bool {
+    (*uart_get_hw(uart)).fr.read() & UART_UARTFR_TXFF_BITS == 0
+}
+
+/// \brief Wait for the UART TX fifo to be drained
+///  \ingroup hardware_uart
+///
+/// \param uart UART instance. \ref uart0 or \ref uart1
+#[inline]
+pub unsafe fn uart_tx_wait_blocking(uart: *mut uart_inst_t) {
+    while (*uart_get_hw(uart)).fr.read() & UART_UARTFR_BUSY_BITS != 0 {}
+}
+
+/// \brief Determine whether data is waiting in the RX FIFO
+///  \ingroup hardware_uart
+///
+/// \param uart UART instance. \ref uart0 or \ref uart1
+/// \return 0 if no data available, otherwise the number of bytes, at least, that can be read
+///
+/// \note HW limitations mean this function will return either 0 or 1.
+#[inline]
+pub unsafe fn uart_is_readable(uart: *mut uart_inst_t) -> bool {
+    (*uart_get_hw(uart)).fr.read() & UART_UARTFR_RXFE_BITS == 0
+}
+
+/// \brief  Write to the UART for transmission.
+///  \ingroup hardware_uart
+///
+/// This function will block until all the data has been sent to the UART
+///
+/// \param uart UART instance. \ref uart0 or \ref uart1
+/// \param src The bytes to send
+/// \param len The number of bytes to send
+#[inline]
+pub unsafe fn uart_write_blocking(uart: *mut uart_inst_t, src: *const u8, len: size_t) {
+    for i in 0..len as usize {
+        while !uart_is_writable(uart) {}
+        (*uart_get_hw(uart)).dr.write(*src.add(i) as u32);
+    }
+}
+
+/// \brief  Read from the UART
+///  \ingroup hardware_uart
+///
+/// This function will block until all the data has been received from the UART
+///
+/// \param uart UART instance. \ref uart0 or \ref uart1
+/// \param dst Buffer to accept received bytes
+/// \param len The number of bytes to receive.
+#[inline]
+pub unsafe fn uart_read_blocking(uart: *mut uart_inst_t, dst: *mut u8, len: size_t) {
+    for i in 0..len as usize {
+        while !uart_is_readable(uart) {}
+        *dst.add(i) = (*uart_get_hw(uart)).dr.read() as u8;
+    }
+}
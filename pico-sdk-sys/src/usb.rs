@@ -0,0 +1,40 @@
+//! hardware_resets and the USB controller registers, not in the bindgen output yet.
+use crate::*;
+
+// #define resets_hw ((resets_hw_t *const)RESETS_BASE)
+pub const resets_hw: *mut resets_hw_t = RESETS_BASE as _;
+
+// #define usb_hw ((usb_hw_t *const)USBCTRL_REGS_BASE)
+pub const usb_hw: *mut usb_hw_t = USBCTRL_REGS_BASE as _;
+
+// #define usb_dpram ((usb_device_dpram_t *const)USBCTRL_DPRAM_BASE)
+pub const usb_dpram: *mut usb_device_dpram_t = USBCTRL_DPRAM_BASE as _;
+
+pub const USB_NUM_ENDPOINTS: usize = 16;
+pub const USB_DPRAM_SIZE: usize = 4096;
+
+pub const RESETS_RESET_USBCTRL_BITS: u32 = 1 << 24;
+
+pub const USB_EP_CTRL_ENDPOINT_TYPE_VALUE_CONTROL: u32 = 0x0;
+pub const USB_EP_CTRL_ENDPOINT_TYPE_VALUE_ISOCHRONOUS: u32 = 0x1;
+pub const USB_EP_CTRL_ENDPOINT_TYPE_VALUE_BULK: u32 = 0x2;
+pub const USB_EP_CTRL_ENDPOINT_TYPE_VALUE_INTERRUPT: u32 = 0x3;
+
+/// \brief Reset the specified HW blocks
+///  \ingroup hardware_resets
+///
+/// \param bits Bit pattern indicating blocks to reset. See \ref reset_bitmask
+#[inline]
+pub unsafe fn reset_block(bits: u32) {
+    (*resets_hw).reset.set_bits(bits);
+}
+
+/// \brief bring specified HW blocks out of reset and wait for completion
+///  \ingroup hardware_resets
+///
+/// \param bits Bit pattern indicating blocks to unreset. See \ref reset_bitmask
+#[inline]
+pub unsafe fn unreset_block_wait(bits: u32) {
+    (*resets_hw).reset.clear_bits(bits);
+    while !(*resets_hw).reset_done.read() & bits != 0 {}
+}
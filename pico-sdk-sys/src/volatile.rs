@@ -0,0 +1,270 @@
+//! Volatile register types, replacing the `io_rw_32` family from hardware/address_mapped.h.
+//!
+//! In C these are plain `volatile uint32_t`. Here every access goes through
+//! `read_volatile`/`write_volatile`, and read-only / write-only registers only
+//! expose the matching half of the API.
+//!
+//! Registers can be tagged with a marker type `R`. Bitfields declared with
+//! `register!` are `Field<R>` constants, so a field can only be used on the
+//! register it belongs to.
+
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::ptr;
+
+use crate::ctypes::*;
+use crate::{REG_ALIAS_CLR_BITS, REG_ALIAS_SET_BITS, REG_ALIAS_XOR_BITS};
+
+pub type io_rw_32 = RW<u32>;
+pub type io_ro_32 = RO<u32>;
+pub type io_wo_32 = WO<u32>;
+pub type io_rw_16 = RW<u16>;
+pub type io_ro_16 = RO<u16>;
+pub type io_wo_16 = WO<u16>;
+pub type io_rw_8 = RW<u8>;
+pub type io_ro_8 = RO<u8>;
+pub type io_wo_8 = WO<u8>;
+
+/// Read-write register
+#[repr(transparent)]
+pub struct RW<T: Copy, R = ()> {
+    value: UnsafeCell<T>,
+    _reg: PhantomData<R>,
+}
+
+/// Read-only register
+#[repr(transparent)]
+pub struct RO<T: Copy, R = ()> {
+    value: UnsafeCell<T>,
+    _reg: PhantomData<R>,
+}
+
+/// Write-only register
+#[repr(transparent)]
+pub struct WO<T: Copy, R = ()> {
+    value: UnsafeCell<T>,
+    _reg: PhantomData<R>,
+}
+
+impl<T: Copy, R> RW<T, R> {
+    #[inline]
+    pub fn read(&self) -> T {
+        unsafe { ptr::read_volatile(self.value.get()) }
+    }
+
+    #[inline]
+    pub fn write(&self, value: T) {
+        unsafe { ptr::write_volatile(self.value.get(), value) }
+    }
+
+    /// Read-modify-write, NOT atomic.
+    #[inline]
+    pub fn modify<F: FnOnce(T) -> T>(&self, f: F) {
+        self.write(f(self.read()));
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
+}
+
+impl<T: Copy, R> RO<T, R> {
+    #[inline]
+    pub fn read(&self) -> T {
+        unsafe { ptr::read_volatile(self.value.get()) }
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *const T {
+        self.value.get()
+    }
+}
+
+impl<T: Copy, R> WO<T, R> {
+    #[inline]
+    pub fn write(&self, value: T) {
+        unsafe { ptr::write_volatile(self.value.get(), value) }
+    }
+
+    #[inline]
+    pub fn as_ptr(&self) -> *mut T {
+        self.value.get()
+    }
+}
+
+impl<R> RW<u32, R> {
+    #[inline]
+    fn alias(&self, alias: u32) -> *mut uint32_t {
+        ((self.as_ptr() as usize) | alias as usize) as *mut uint32_t
+    }
+
+    /// \\brief Atomically set the specified bits to 1 in a HW register
+    ///  \\ingroup hardware_base
+    ///
+    /// Uses the SET alias of APB/AHB peripherals, not valid for SIO registers.
+    ///
+    /// \\param mask Bitmask of bits to set
+    #[inline]
+    pub fn set_bits(&self, mask: uint32_t) {
+        unsafe { ptr::write_volatile(self.alias(REG_ALIAS_SET_BITS), mask) }
+    }
+
+    /// \\brief Atomically clear the specified bits to 0 in a HW register
+    ///  \\ingroup hardware_base
+    ///
+    /// \\param mask Bitmask of bits to clear
+    #[inline]
+    pub fn clear_bits(&self, mask: uint32_t) {
+        unsafe { ptr::write_volatile(self.alias(REG_ALIAS_CLR_BITS), mask) }
+    }
+
+    /// \\brief Atomically flip the specified bits in a HW register
+    ///  \\ingroup hardware_base
+    ///
+    /// \\param mask Bitmask of bits to invert
+    #[inline]
+    pub fn xor_bits(&self, mask: uint32_t) {
+        unsafe { ptr::write_volatile(self.alias(REG_ALIAS_XOR_BITS), mask) }
+    }
+
+    /// \\brief Set new values for a sub-set of the bits in a HW register
+    ///  \\ingroup hardware_base
+    ///
+    /// Sets destination bits to values specified in \\p values, if and only if corresponding bit in \\p write_mask is set
+    ///
+    /// Note: this method allows safe concurrent modification of *different* bits of
+    /// a register, but multiple concurrent access to the same bits is still unsafe.
+    ///
+    /// \\param values Values to set
+    /// \\param write_mask Mask of bits to change
+    #[inline]
+    pub fn write_masked(&self, values: uint32_t, write_mask: uint32_t) {
+        self.xor_bits((self.read() ^ values) & write_mask);
+    }
+
+    /// Read a bitfield, shifted down to bit 0.
+    #[inline]
+    pub fn read_field(&self, field: Field<R>) -> u32 {
+        field.extract(self.read())
+    }
+
+    /// Write a bitfield via the XOR alias, leaving other fields untouched.
+    #[inline]
+    pub fn write_field(&self, field: Field<R>, value: u32) {
+        self.write_masked(field.value(value), field.bits());
+    }
+
+    /// Atomically set all bits of a field.
+    #[inline]
+    pub fn set_field(&self, field: Field<R>) {
+        self.set_bits(field.bits());
+    }
+
+    /// Atomically clear all bits of a field.
+    #[inline]
+    pub fn clear_field(&self, field: Field<R>) {
+        self.clear_bits(field.bits());
+    }
+
+    /// Check whether any bit of a field is set.
+    #[inline]
+    pub fn is_set(&self, field: Field<R>) -> bool {
+        self.read() & field.bits() != 0
+    }
+}
+
+impl<R> RO<u32, R> {
+    /// Read a bitfield, shifted down to bit 0.
+    #[inline]
+    pub fn read_field(&self, field: Field<R>) -> u32 {
+        field.extract(self.read())
+    }
+
+    /// Check whether any bit of a field is set.
+    #[inline]
+    pub fn is_set(&self, field: Field<R>) -> bool {
+        self.read() & field.bits() != 0
+    }
+}
+
+impl<R> WO<u32, R> {
+    /// Write a single bitfield, all other bits are written as 0.
+    #[inline]
+    pub fn write_field(&self, field: Field<R>, value: u32) {
+        self.write(field.value(value));
+    }
+}
+
+/// A bitfield of register `R`, built from the `*_LSB` and `*_BITS` constants.
+pub struct Field<R> {
+    lsb: u32,
+    bits: u32,
+    _reg: PhantomData<R>,
+}
+
+impl<R> Clone for Field<R> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R> Copy for Field<R> {}
+
+impl<R> Field<R> {
+    pub const fn new(lsb: u32, bits: u32) -> Self {
+        Field {
+            lsb,
+            bits,
+            _reg: PhantomData,
+        }
+    }
+
+    #[inline]
+    pub const fn lsb(self) -> u32 {
+        self.lsb
+    }
+
+    #[inline]
+    pub const fn bits(self) -> u32 {
+        self.bits
+    }
+
+    /// Shift `value` into position, masked to the field width.
+    #[inline]
+    pub const fn value(self, value: u32) -> u32 {
+        (value << self.lsb) & self.bits
+    }
+
+    /// Extract the field from a raw register value.
+    #[inline]
+    pub const fn extract(self, raw: u32) -> u32 {
+        (raw & self.bits) >> self.lsb
+    }
+}
+
+/// Declare a register marker type and its bitfields.
+///
+/// ```ignore
+/// register! {
+///     /// Control and status register
+///     PWM_CH0_CSR {
+///         EN => PWM_CH0_CSR_EN_LSB, PWM_CH0_CSR_EN_BITS;
+///     }
+/// }
+/// // PWM_CH0_CSR::EN: Field<PWM_CH0_CSR>
+/// ```
+#[macro_export]
+macro_rules! register {
+    ($(#[$meta:meta])* $reg:ident { $($field:ident => $lsb:expr, $bits:expr;)* }) => {
+        $(#[$meta])*
+        pub enum $reg {}
+
+        impl $reg {
+            $(
+                pub const $field: $crate::Field<$reg> = $crate::Field::new($lsb, $bits);
+            )*
+        }
+    };
+}
@@ -0,0 +1,62 @@
+//! hardware_watchdog, not in the bindgen output yet.
+use crate::*;
+
+// #define watchdog_hw ((watchdog_hw_t *const)WATCHDOG_BASE)
+pub const watchdog_hw: *mut watchdog_hw_t = WATCHDOG_BASE as _;
+
+// #define vreg_and_chip_reset_hw ((vreg_and_chip_reset_hw_t *const)VREG_AND_CHIP_RESET_BASE)
+pub const vreg_and_chip_reset_hw: *mut vreg_and_chip_reset_hw_t = VREG_AND_CHIP_RESET_BASE as _;
+
+// #define psm_hw ((psm_hw_t *const)PSM_BASE)
+pub const psm_hw: *mut psm_hw_t = PSM_BASE as _;
+
+extern "C" {
+    /// \brief Define actions to perform at watchdog timeout
+    ///  \ingroup hardware_watchdog
+    ///
+    /// \note If \ref watchdog_start_tick value does not give a 1MHz clock to the watchdog system, then the \p delay_ms
+    /// parameter will not be in microseconds. See the datasheet for more details.
+    ///
+    /// By default the SDK assumes a 12MHz XOSC and sets the \ref watchdog_start_tick appropriately.
+    ///
+    /// \param pc If Zero, a standard boot will be performed, if non-zero this is the program counter to jump to on reset.
+    /// \param sp If \p pc is non-zero, this will be the stack pointer used.
+    /// \param delay_ms Initial load value. Maximum value 0x7fffff, approximately 8.3s.
+    pub fn watchdog_reboot(pc: u32, sp: u32, delay_ms: u32);
+
+    /// \brief Start the watchdog tick
+    ///  \ingroup hardware_watchdog
+    ///
+    /// \param cycles This needs to be a divider that when applied to the XOSC input, produces a 1MHz clock. So if the XOSC is
+    /// 12MHz, this will need to be 12.
+    pub fn watchdog_start_tick(cycles: uint);
+
+    /// \brief Reload the watchdog counter with the amount of time set in watchdog_enable
+    ///  \ingroup hardware_watchdog
+    pub fn watchdog_update();
+
+    /// \brief Enable the watchdog
+    /// \ingroup hardware_watchdog
+    ///
+    /// \note If \ref watchdog_start_tick value does not give a 1MHz clock to the watchdog system, then the \p delay_ms
+    /// parameter will not be in microseconds. See the datasheet for more details.
+    ///
+    /// By default the SDK assumes a 12MHz XOSC and sets the \ref watchdog_start_tick appropriately.
+    ///
+    /// \param delay_ms Number of milliseconds before watchdog will reboot without watchdog_update being called. Maximum of 0x7fffff, which is approximately 8.3 seconds
+    /// \param pause_on_debug If the watchdog should be paused when the debugger is stepping through code
+    pub fn watchdog_enable(delay_ms: u32, pause_on_debug: bool);
+
+    /// \brief Did the watchdog cause the last reboot?
+    /// \ingroup hardware_watchdog
+    ///
+    /// @return true If the watchdog timer or a watchdog force caused the last reboot
+    /// @return false If there has been no watchdog reboot since the last power on reset. A power on reset is typically caused by a power cycle or the run pin (reset button) being toggled.
+    pub fn watchdog_caused_reboot() -> bool;
+
+    /// \brief Returns the number of microseconds before the watchdog will reboot the chip.
+    /// \ingroup hardware_watchdog
+    ///
+    /// @return The number of microseconds before the watchdog will reboot the chip.
+    pub fn watchdog_get_count() -> u32;
+}
@@ -0,0 +1,275 @@
+//! Binary info, what `picotool info` shows. Same layout as pico-sdk's `bi_decl()`.
+//!
+//! Each entry is a static struct, plus a pointer to it in a `.binary_info.keep.*`
+//! section. The linker script collects the pointers between `__binary_info_start`
+//! and `__binary_info_end`, the header (crt0.S, or rpi-pico-rt) points there.
+//!
+//! ```ignore
+//! rpi_pico_sdk::binary_info! {
+//!     name: "hello_epd",
+//!     version: env!("CARGO_PKG_VERSION"),
+//!     description: "E-Paper display demo",
+//!     pins: [25 => "LED", 20 => "EPD RST"],
+//! }
+//! ```
+//!
+//! Strings must be literals (or `env!`/`concat!`), values must be constants:
+//! everything is resolved at compile time.
+//!
+//! Nothing refers to the entries, they're `#[used]` and the linker script must
+//! `KEEP(*(.binary_info.keep.*))`, as pico-sdk's `memmap_*.ld` and
+//! rpi-pico-rt's `link.x` do. That keeps them only in objects that get linked:
+//! a cargo build links all of the crate's, but from the staticlib of a CMake
+//! build the linker takes just the objects it needs a symbol from. There,
+//! use `binary_info!`, and `spi0!` and the like, in the module that defines
+//! `main`.
+
+pub const TYPE_RAW_DATA: u16 = 1;
+pub const TYPE_SIZED_DATA: u16 = 2;
+pub const TYPE_BINARY_INFO_LIST_ZERO_TERMINATED: u16 = 3;
+pub const TYPE_BSON: u16 = 4;
+pub const TYPE_ID_AND_INT: u16 = 5;
+pub const TYPE_ID_AND_STRING: u16 = 6;
+pub const TYPE_BLOCK_DEVICE: u16 = 7;
+pub const TYPE_PINS_WITH_FUNC: u16 = 8;
+pub const TYPE_PINS_WITH_NAME: u16 = 9;
+pub const TYPE_NAMED_GROUP: u16 = 10;
+
+/// `BINARY_INFO_MAKE_TAG(c1, c2)`
+pub const fn make_tag(c1: u8, c2: u8) -> u16 {
+    (c2 as u16) << 8 | c1 as u16
+}
+
+pub const TAG_RASPBERRY_PI: u16 = make_tag(b'R', b'P');
+
+pub const ID_RP_PROGRAM_NAME: u32 = 0x0203_1c86;
+pub const ID_RP_PROGRAM_VERSION_STRING: u32 = 0x11a9_bc3a;
+pub const ID_RP_PROGRAM_BUILD_DATE_STRING: u32 = 0x9da2_2254;
+pub const ID_RP_BINARY_END: u32 = 0x68f4_65de;
+pub const ID_RP_PROGRAM_URL: u32 = 0x1856_239a;
+pub const ID_RP_PROGRAM_DESCRIPTION: u32 = 0xb6a0_7c19;
+pub const ID_RP_PROGRAM_FEATURE: u32 = 0xa1f4_b453;
+pub const ID_RP_PROGRAM_BUILD_ATTRIBUTE: u32 = 0x4275_f0d3;
+pub const ID_RP_SDK_VERSION: u32 = 0x5360_b3ab;
+pub const ID_RP_PICO_BOARD: u32 = 0xb63c_ffbb;
+pub const ID_RP_BOOT2_NAME: u32 = 0x7f88_82e1;
+
+pub const PINS_ENCODING_RANGE: u32 = 1;
+pub const PINS_ENCODING_MULTI: u32 = 2;
+
+/// Max pins in one `PinsWithFunc` entry, 5 bits each after the 7 bit header
+pub const MAX_PINS_WITH_FUNC: usize = 5;
+
+/// `binary_info_core_t`
+#[repr(C)]
+pub struct Core {
+    pub type_: u16,
+    pub tag: u16,
+}
+
+/// `binary_info_id_and_int_t`
+#[repr(C)]
+pub struct IdAndInt {
+    pub core: Core,
+    pub id: u32,
+    pub value: i32,
+}
+
+/// `binary_info_id_and_string_t`
+#[repr(C)]
+pub struct IdAndString {
+    pub core: Core,
+    pub id: u32,
+    pub value: *const u8,
+}
+
+/// `binary_info_pins_with_func_t`
+#[repr(C)]
+pub struct PinsWithFunc {
+    pub core: Core,
+    pub pin_encoding: u32,
+}
+
+/// `binary_info_pins_with_name_t`
+#[repr(C)]
+pub struct PinsWithName {
+    pub core: Core,
+    pub pin_mask: u32,
+    pub label: *const u8,
+}
+
+// only ever point to 'static data
+unsafe impl Sync for IdAndString {}
+unsafe impl Sync for PinsWithName {}
+
+impl IdAndInt {
+    pub const fn new(tag: u16, id: u32, value: i32) -> Self {
+        IdAndInt {
+            core: Core {
+                type_: TYPE_ID_AND_INT,
+                tag,
+            },
+            id,
+            value,
+        }
+    }
+}
+
+impl IdAndString {
+    /// `value` must be NUL-terminated.
+    pub const fn new(tag: u16, id: u32, value: &'static str) -> Self {
+        IdAndString {
+            core: Core {
+                type_: TYPE_ID_AND_STRING,
+                tag,
+            },
+            id,
+            value: value.as_ptr(),
+        }
+    }
+}
+
+impl PinsWithFunc {
+    /// Up to 5 pins sharing one GPIO function, panics (at compile time) otherwise.
+    pub const fn new(func: u32, pins: &[u32]) -> Self {
+        assert!(
+            !pins.is_empty() && pins.len() <= MAX_PINS_WITH_FUNC,
+            "1 to 5 pins per entry"
+        );
+        let mut encoding = PINS_ENCODING_MULTI | func << 3;
+        let mut i = 0;
+        while i < MAX_PINS_WITH_FUNC {
+            // the last pin is repeated to mark the end of the list
+            let pin = if i < pins.len() {
+                pins[i]
+            } else {
+                pins[pins.len() - 1]
+            };
+            assert!(pin < 30, "invalid GPIO");
+            encoding |= pin << (7 + 5 * i);
+            i += 1;
+        }
+        PinsWithFunc {
+            core: Core {
+                type_: TYPE_PINS_WITH_FUNC,
+                tag: TAG_RASPBERRY_PI,
+            },
+            pin_encoding: encoding,
+        }
+    }
+}
+
+impl PinsWithName {
+    /// `label` must be NUL-terminated, one `|`-separated label per pin in `pin_mask`.
+    pub const fn new(pin_mask: u32, label: &'static str) -> Self {
+        PinsWithName {
+            core: Core {
+                type_: TYPE_PINS_WITH_NAME,
+                tag: TAG_RASPBERRY_PI,
+            },
+            pin_mask,
+            label: label.as_ptr(),
+        }
+    }
+}
+
+/// What goes into `.binary_info.keep.*`: a pointer to an entry.
+#[repr(transparent)]
+pub struct EntryAddr(*const Core);
+
+unsafe impl Sync for EntryAddr {}
+
+impl EntryAddr {
+    /// `T` must be one of the `#[repr(C)]` entry structs, starting with a `Core`.
+    pub const fn new<T>(entry: &'static T) -> Self {
+        EntryAddr(entry as *const T as *const Core)
+    }
+}
+
+/// Declare a single entry, `bi_decl()`.
+#[macro_export]
+macro_rules! bi_decl {
+    ($ty:ty = $entry:expr) => {
+        const _: () = {
+            #[used]
+            static ENTRY: $ty = $entry;
+            #[link_section = ".binary_info.keep.rust"]
+            #[used]
+            static ENTRY_ADDR: $crate::binary_info::EntryAddr =
+                $crate::binary_info::EntryAddr::new(&ENTRY);
+        };
+    };
+}
+
+/// `bi_string(tag, id, value)`
+#[macro_export]
+macro_rules! bi_string {
+    ($id:expr, $value:expr) => {
+        $crate::bi_decl!(
+            $crate::binary_info::IdAndString = $crate::binary_info::IdAndString::new(
+                $crate::binary_info::TAG_RASPBERRY_PI,
+                $id,
+                concat!($value, "\0"),
+            )
+        );
+    };
+}
+
+/// `bi_pin_mask_with_name(mask, label)`, e.g. `bi_pin_with_name!(25, "LED")`
+#[macro_export]
+macro_rules! bi_pin_with_name {
+    ($pin:expr, $label:expr) => {
+        $crate::bi_decl!(
+            $crate::binary_info::PinsWithName =
+                $crate::binary_info::PinsWithName::new(1 << $pin, concat!($label, "\0"))
+        );
+    };
+}
+
+/// `bi_Npins_with_func(...)`, e.g. `bi_pins_with_func!(GPIO_FUNC_SPI, 18, 19)`
+#[macro_export]
+macro_rules! bi_pins_with_func {
+    ($func:expr, $($pin:expr),+ $(,)?) => {
+        $crate::bi_decl!(
+            $crate::binary_info::PinsWithFunc =
+                $crate::binary_info::PinsWithFunc::new($func as u32, &[$($pin as u32),+])
+        );
+    };
+}
+
+/// Program information for picotool, any subset of the keys, in any order.
+///
+/// `pins` are named pins; pins claimed by peripherals, e.g. `spi0!`, are
+/// recorded by those with their GPIO function.
+#[macro_export]
+macro_rules! binary_info {
+    () => {};
+    (name: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::bi_string!($crate::binary_info::ID_RP_PROGRAM_NAME, $value);
+        $crate::binary_info!($($($rest)*)?);
+    };
+    (version: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::bi_string!($crate::binary_info::ID_RP_PROGRAM_VERSION_STRING, $value);
+        $crate::binary_info!($($($rest)*)?);
+    };
+    (description: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::bi_string!($crate::binary_info::ID_RP_PROGRAM_DESCRIPTION, $value);
+        $crate::binary_info!($($($rest)*)?);
+    };
+    (url: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::bi_string!($crate::binary_info::ID_RP_PROGRAM_URL, $value);
+        $crate::binary_info!($($($rest)*)?);
+    };
+    (build_date: $value:expr $(, $($rest:tt)*)?) => {
+        $crate::bi_string!($crate::binary_info::ID_RP_PROGRAM_BUILD_DATE_STRING, $value);
+        $crate::binary_info!($($($rest)*)?);
+    };
+    (features: [$($value:expr),* $(,)?] $(, $($rest:tt)*)?) => {
+        $($crate::bi_string!($crate::binary_info::ID_RP_PROGRAM_FEATURE, $value);)*
+        $crate::binary_info!($($($rest)*)?);
+    };
+    (pins: [$($pin:expr => $label:expr),* $(,)?] $(, $($rest:tt)*)?) => {
+        $($crate::bi_pin_with_name!($pin, $label);)*
+        $crate::binary_info!($($($rest)*)?);
+    };
+}
@@ -0,0 +1,3 @@
+//! What's wired on the board, past the RP2040 itself.
+
+pub mod pico;
@@ -0,0 +1,213 @@
+//! The Raspberry Pi Pico: LED, SMPS mode pin, VBUS sense, VSYS/3 on ADC input
+//! 3, and the RP2040's temperature sensor on ADC input 4.
+//!
+//! ```ignore
+//! let mut pico = Pico::new()?;
+//! println!("{} °C, VSYS {} V", pico.temperature(), pico.vsys());
+//! if !pico.usb_present() {
+//!     // on battery
+//! }
+//! ```
+//!
+//! The conversions are on `Calibration`, no hardware needed: a board measured
+//! against a thermometer or a voltmeter gets its own constants with
+//! `Pico::with_calibration()`.
+
+use rpi_pico_sdk_sys::*;
+
+pub const LED_PIN: uint = PICO_DEFAULT_LED_PIN;
+/// Low: PFM mode, the most efficient at light load; high: PWM mode, less
+/// ripple, e.g. for ADC measurements
+pub const SMPS_MODE_PIN: uint = PICO_SMPS_MODE_PIN;
+/// High while VBUS is present, i.e. USB powered
+pub const VBUS_SENSE_PIN: uint = 24;
+/// VSYS through a 3:1 divider
+pub const VSYS_PIN: uint = 29;
+
+pub const VSYS_ADC_INPUT: uint = VSYS_PIN - 26;
+pub const TEMPERATURE_ADC_INPUT: uint = ADC_TEMPERATURE_CHANNEL_NUM;
+
+/// Conversions averaged per reading
+const SAMPLES: u32 = 8;
+
+/// ADC full scale, 12 bits
+const ADC_RANGE: f32 = 4096.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// clk_adc is stopped: the ADC would never finish a conversion.
+    AdcClock,
+}
+
+/// Constants turning ADC readings into volts and °C.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    /// ADC reference in V: 3V3 on the Pico, through an RC filter.
+    pub vref: f32,
+    /// Temperature sensor voltage at 27 °C.
+    pub sensor_v27: f32,
+    /// Temperature sensor slope in V per °C, falling.
+    pub sensor_slope: f32,
+    /// VSYS over the voltage at `VSYS_PIN`.
+    pub vsys_ratio: f32,
+}
+
+impl Calibration {
+    /// Typical values from the datasheet and the Pico schematic.
+    pub const DEFAULT: Calibration = Calibration {
+        vref: 3.3,
+        sensor_v27: 0.706,
+        sensor_slope: 0.001721,
+        vsys_ratio: 3.0,
+    };
+
+    /// Volts at the ADC input for a 12 bit reading.
+    pub fn volts(&self, raw: u16) -> f32 {
+        raw as f32 * self.vref / ADC_RANGE
+    }
+
+    /// Die temperature in °C for a reading of the temperature sensor,
+    /// `27 - (V - 0.706) / 0.001721` with the default constants.
+    pub fn temperature(&self, raw: u16) -> f32 {
+        27.0 - (self.volts(raw) - self.sensor_v27) / self.sensor_slope
+    }
+
+    /// VSYS in volts for a reading of `VSYS_PIN`.
+    pub fn vsys(&self, raw: u16) -> f32 {
+        self.volts(raw) * self.vsys_ratio
+    }
+
+    /// The same, with `sensor_v27` moved so that `raw` reads as `celsius`:
+    /// a one point calibration.
+    pub fn with_temperature_at(self, raw: u16, celsius: f32) -> Self {
+        Calibration {
+            sensor_v27: self.volts(raw) + (celsius - 27.0) * self.sensor_slope,
+            ..self
+        }
+    }
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// The board's sensing: the ADC, `VSYS_PIN` and `VBUS_SENSE_PIN`.
+pub struct Pico {
+    calibration: Calibration,
+}
+
+impl Pico {
+    /// Resets and takes over the ADC, turns the temperature sensor on and
+    /// sets up `VSYS_PIN` and `VBUS_SENSE_PIN`.
+    ///
+    /// clk_adc must run, as pico-sdk's runtime has it, or through
+    /// `clocks::ClockConfig`: `Error::AdcClock` otherwise.
+    pub fn new() -> Result<Self, Error> {
+        unsafe {
+            if clock_get_hz(clk_adc) == 0 {
+                return Err(Error::AdcClock);
+            }
+            adc_init();
+            adc_set_temp_sensor_enabled(true);
+            adc_gpio_init(VSYS_PIN);
+            // an input, no pulls
+            gpio_init(VBUS_SENSE_PIN);
+        }
+        Ok(Pico {
+            calibration: Calibration::DEFAULT,
+        })
+    }
+
+    pub fn with_calibration(mut self, calibration: Calibration) -> Self {
+        self.calibration = calibration;
+        self
+    }
+
+    pub fn calibration(&self) -> &Calibration {
+        &self.calibration
+    }
+
+    /// Average of a few conversions of ADC input `input`
+    pub fn read_raw(&mut self, input: uint) -> u16 {
+        unsafe {
+            adc_select_input(input);
+            let sum: u32 = (0..SAMPLES).map(|_| adc_read() as u32).sum();
+            (sum / SAMPLES) as u16
+        }
+    }
+
+    /// Die temperature in °C.
+    pub fn temperature(&mut self) -> f32 {
+        let raw = self.read_raw(TEMPERATURE_ADC_INPUT);
+        self.calibration.temperature(raw)
+    }
+
+    /// Supply voltage VSYS in V. Less noisy with `SMPS_MODE_PIN` high.
+    pub fn vsys(&mut self) -> f32 {
+        let raw = self.read_raw(VSYS_ADC_INPUT);
+        self.calibration.vsys(raw)
+    }
+
+    /// Whether USB, or anything else on VBUS, powers the board.
+    pub fn usb_present(&self) -> bool {
+        unsafe { gpio_get(VBUS_SENSE_PIN) }
+    }
+
+    /// Turns the temperature sensor off again, done with the board's sensing.
+    pub fn disable_temperature_sensor(self) {
+        unsafe { adc_set_temp_sensor_enabled(false) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(value: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (value - expected).abs() <= tolerance,
+            "{} is not {} ± {}",
+            value,
+            expected,
+            tolerance
+        );
+    }
+
+    #[test]
+    fn volts() {
+        let cal = Calibration::DEFAULT;
+        assert_eq!(cal.volts(0), 0.0);
+        assert_near(cal.volts(2048), 1.65, 1e-6);
+        assert_near(cal.volts(4095), 3.3 * 4095.0 / 4096.0, 1e-6);
+    }
+
+    #[test]
+    fn temperature() {
+        let cal = Calibration::DEFAULT;
+        // 0.706 V is 876 LSB
+        assert_near(cal.temperature(876), 27.0, 0.5);
+        // falling, 3.3 / 4096 / 0.001721 °C per LSB
+        let step = cal.temperature(876) - cal.temperature(877);
+        assert_near(step, 0.468, 0.001);
+    }
+
+    #[test]
+    fn vsys() {
+        assert_eq!(VSYS_ADC_INPUT, 3);
+        let cal = Calibration::DEFAULT;
+        assert_near(cal.vsys(2048), 4.95, 1e-5);
+        assert_near(cal.vsys(2069), 5.0, 0.01);
+    }
+
+    #[test]
+    fn one_point_calibration() {
+        for &(raw, celsius) in &[(900, 20.0), (876, 27.0), (850, 40.0)] {
+            let cal = Calibration::DEFAULT.with_temperature_at(raw, celsius);
+            assert_near(cal.temperature(raw), celsius, 1e-3);
+            assert_eq!(cal.sensor_slope, Calibration::DEFAULT.sensor_slope);
+            assert_eq!(cal.vsys(2048), Calibration::DEFAULT.vsys(2048));
+        }
+    }
+}
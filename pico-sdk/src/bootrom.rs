@@ -0,0 +1,28 @@
+//! The bootrom: rebooting into BOOTSEL mode, the USB drive and PICOBOOT,
+//! without holding the button down.
+//!
+//! ```ignore
+//! // blink the LED on drive activity, keep both interfaces
+//! bootrom::reset_to_usb_boot(Some(25), 0);
+//! ```
+//!
+//! From the host, see `usb::ResetInterface`.
+
+use rpi_pico_sdk_sys::*;
+
+/// For `interface_mask`: no RPI-RP2 mass storage drive.
+pub const DISABLE_MASS_STORAGE: u32 = RESET_USB_BOOT_DISABLE_MASS_STORAGE;
+/// For `interface_mask`: no PICOBOOT interface, what picotool talks to.
+pub const DISABLE_PICOBOOT: u32 = RESET_USB_BOOT_DISABLE_PICOBOOT;
+
+/// Reboot into BOOTSEL mode. `activity_led` is driven high on mass storage
+/// activity, ignored if it's not a GPIO, `interface_mask` disables
+/// `DISABLE_MASS_STORAGE` and/or `DISABLE_PICOBOOT`, 0 keeps both as the
+/// button does.
+pub fn reset_to_usb_boot(activity_led: Option<uint>, interface_mask: u32) -> ! {
+    let led_mask = match activity_led {
+        Some(pin) if pin < NUM_BANK0_GPIOS => 1 << pin,
+        _ => 0,
+    };
+    unsafe { reset_usb_boot(led_mask, interface_mask) }
+}
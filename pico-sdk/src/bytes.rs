@@ -0,0 +1,217 @@
+//! `fmt::Write` into a fixed byte buffer, for `write!` without an allocator.
+//!
+//! ```ignore
+//! let mut buf = [0u8; 16];
+//! let mut w = BytesWriter::new(&mut buf);
+//! write!(w, "refresh #{}", i)?;
+//! draw_text(w.as_str());
+//! ```
+//!
+//! The contents are always valid UTF-8: what doesn't fit is cut off at a char
+//! boundary, never in the middle of a char.
+
+use core::fmt;
+use core::str;
+
+/// What to do when a write doesn't fit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Keep what fits and fail with `fmt::Error`, so `write!` reports it.
+    Error,
+    /// Keep what fits and carry on, check `is_truncated()` afterwards.
+    Truncate,
+}
+
+/// A fmt::Write for bytes.
+pub struct BytesWriter<'a> {
+    buf: &'a mut [u8],
+    cursor: usize,
+    overflow: Overflow,
+    truncated: bool,
+}
+
+impl<'a> BytesWriter<'a> {
+    /// A writer failing on overflow, `Overflow::Error`.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Self::with_overflow(buf, Overflow::Error)
+    }
+
+    pub fn with_overflow(buf: &'a mut [u8], overflow: Overflow) -> Self {
+        BytesWriter {
+            buf,
+            cursor: 0,
+            overflow,
+            truncated: false,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        // only whole chars are ever written
+        unsafe { str::from_utf8_unchecked(&self.buf[..self.cursor]) }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.cursor]
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Bytes left.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.capacity() - self.cursor
+    }
+
+    /// Empty it, and reset the truncated flag.
+    pub fn clear(&mut self) {
+        self.cursor = 0;
+        self.truncated = false;
+    }
+
+    pub fn len(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cursor == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.capacity() == self.cursor
+    }
+
+    /// Something was cut off since the last `clear()`.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl fmt::Write for BytesWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        // after a cut nothing more, a shorter piece could still fit and leave a gap
+        let mut n = if self.truncated {
+            0
+        } else {
+            s.len().min(self.remaining())
+        };
+        while !s.is_char_boundary(n) {
+            n -= 1;
+        }
+        self.buf[self.cursor..self.cursor + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.cursor += n;
+
+        if n == s.len() {
+            return Ok(());
+        }
+        self.truncated = true;
+        match self.overflow {
+            Overflow::Error => Err(fmt::Error),
+            Overflow::Truncate => Ok(()),
+        }
+    }
+
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.write_str(c.encode_utf8(&mut [0; 4]))
+    }
+}
+
+impl fmt::Display for BytesWriter<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl AsRef<str> for BytesWriter<'_> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::fmt::Write;
+
+    #[test]
+    fn exact_fit() {
+        let mut buf = [0u8; 8];
+        let mut w = BytesWriter::new(&mut buf);
+        assert!(w.is_empty());
+        write!(w, "abc-{}", 1234).unwrap();
+        assert_eq!(w.as_str(), "abc-1234");
+        assert!(w.is_full());
+        assert!(!w.is_truncated());
+        assert_eq!(w.remaining(), 0);
+        // nothing more fits, not even nothing is a problem
+        w.write_str("").unwrap();
+        assert!(w.write_char('x').is_err());
+    }
+
+    #[test]
+    fn overflow_error() {
+        let mut buf = [0u8; 6];
+        let mut w = BytesWriter::new(&mut buf);
+        assert!(write!(w, "refresh #{}", 42).is_err());
+        assert_eq!(w.as_str(), "refres");
+        assert!(w.is_truncated());
+
+        w.clear();
+        assert!(!w.is_truncated());
+        write!(w, "ok").unwrap();
+        assert_eq!(w.as_str(), "ok");
+    }
+
+    #[test]
+    fn overflow_truncate() {
+        let mut buf = [0u8; 6];
+        let mut w = BytesWriter::with_overflow(&mut buf, Overflow::Truncate);
+        w.write_str("abcd").unwrap();
+        w.write_str("efgh").unwrap();
+        assert_eq!(w.as_str(), "abcdef");
+        assert!(w.is_truncated());
+
+        // after a cut, a piece that would fit isn't appended
+        let mut buf = [0u8; 6];
+        let mut w = BytesWriter::with_overflow(&mut buf, Overflow::Truncate);
+        w.write_str("abcdefg").unwrap();
+        w.write_str("h").unwrap();
+        assert_eq!(w.as_str(), "abcdef");
+    }
+
+    #[test]
+    fn char_boundary() {
+        // 'é' is 2 bytes, '€' 3
+        let mut buf = [0u8; 4];
+        let mut w = BytesWriter::with_overflow(&mut buf, Overflow::Truncate);
+        w.write_str("aé€").unwrap();
+        assert_eq!(w.as_str(), "aé");
+        assert_eq!(w.len(), 3);
+
+        let mut buf = [0u8; 2];
+        let mut w = BytesWriter::new(&mut buf);
+        assert!(w.write_char('€').is_err());
+        assert_eq!(w.as_str(), "");
+        assert!(w.is_truncated());
+    }
+
+    #[test]
+    fn numbers() {
+        let mut buf = [0u8; 64];
+        let mut w = BytesWriter::new(&mut buf);
+        write!(w, "{:5}|{:<4}|{:04x}|{:#x}|{:+}", 42, 7, 0xab, 255u8, -3i32).unwrap();
+        assert_eq!(w.as_str(), "   42|7   |00ab|0xff|-3");
+
+        w.clear();
+        write!(w, "{:.2} {:e} {}", 1.23456f32, 1500.0f64, u64::MAX).unwrap();
+        assert_eq!(w.as_str(), "1.23 1.5e3 18446744073709551615");
+        assert_eq!(w.as_bytes(), w.as_str().as_bytes());
+
+        let mut copy = [0u8; 64];
+        let mut c = BytesWriter::new(&mut copy);
+        write!(c, "{}", w).unwrap();
+        assert_eq!(c.as_str(), w.as_str());
+    }
+}
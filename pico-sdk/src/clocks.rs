@@ -0,0 +1,633 @@
+//! The clock tree: where clk_sys, clk_peri and the others come from, and how
+//! fast they run.
+//!
+//! pico-sdk's runtime sets up the defaults before `main`: pll_sys at 125 MHz
+//! for clk_sys and clk_peri, pll_usb at 48 MHz for clk_usb and clk_adc, and
+//! clk_rtc at 46.875 kHz. Change them with a `ClockConfig`:
+//!
+//! ```ignore
+//! let clocks = ClockConfig::new()
+//!     .pll_sys(Pll::new(Hertz::mhz(1596), 6, 2)) // 133 MHz
+//!     .clock(Clock::Sys, Source::PllSys, Hertz::mhz(133))
+//!     .clock(Clock::Peri, Source::Sys, Hertz::mhz(133))
+//!     .apply()?;
+//! let spi = SPI::spi0_with_clocks(&clocks, Hertz::mhz(4));
+//! ```
+//!
+//! Peripherals set up before the change keep their old dividers, set them up
+//! again with the new `ClockSet`. stdio's UART included.
+
+use core::fmt;
+
+use rpi_pico_sdk_sys::*;
+
+/// A frequency.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hertz(pub u32);
+
+impl Hertz {
+    pub const fn hz(hz: u32) -> Self {
+        Hertz(hz)
+    }
+
+    pub const fn khz(khz: u32) -> Self {
+        Hertz(khz * 1_000)
+    }
+
+    pub const fn mhz(mhz: u32) -> Self {
+        Hertz(mhz * 1_000_000)
+    }
+
+    pub const fn to_hz(self) -> u32 {
+        self.0
+    }
+
+    /// Rounded down.
+    pub const fn to_khz(self) -> u32 {
+        self.0 / 1_000
+    }
+
+    /// Rounded down.
+    pub const fn to_mhz(self) -> u32 {
+        self.0 / 1_000_000
+    }
+}
+
+impl From<u32> for Hertz {
+    fn from(hz: u32) -> Self {
+        Hertz(hz)
+    }
+}
+
+impl fmt::Display for Hertz {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} Hz", self.0)
+    }
+}
+
+/// The crystal on the Pico board.
+pub const XOSC_FREQ: Hertz = Hertz::mhz(XOSC_MHZ);
+
+/// The clocks peripherals run from. The GPOUT ones are in `gpout()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clock {
+    Ref,
+    Sys,
+    Peri,
+    Usb,
+    Adc,
+    Rtc,
+}
+
+impl Clock {
+    const ALL: [Clock; 6] = [
+        Clock::Ref,
+        Clock::Sys,
+        Clock::Peri,
+        Clock::Usb,
+        Clock::Adc,
+        Clock::Rtc,
+    ];
+
+    fn index(self) -> clock_index {
+        match self {
+            Clock::Ref => clk_ref,
+            Clock::Sys => clk_sys,
+            Clock::Peri => clk_peri,
+            Clock::Usb => clk_usb,
+            Clock::Adc => clk_adc,
+            Clock::Rtc => clk_rtc,
+        }
+    }
+
+    /// Frequency as last configured, through pico-sdk's bookkeeping.
+    pub fn freq(self) -> Hertz {
+        Hertz(unsafe { clock_get_hz(self.index()) })
+    }
+
+    /// Frequency as measured by the frequency counter, to ±1 kHz.
+    pub fn measure(self) -> Hertz {
+        let src = match self {
+            Clock::Ref => CLOCKS_FC0_SRC_VALUE_CLK_REF,
+            Clock::Sys => CLOCKS_FC0_SRC_VALUE_CLK_SYS,
+            Clock::Peri => CLOCKS_FC0_SRC_VALUE_CLK_PERI,
+            Clock::Usb => CLOCKS_FC0_SRC_VALUE_CLK_USB,
+            Clock::Adc => CLOCKS_FC0_SRC_VALUE_CLK_ADC,
+            Clock::Rtc => CLOCKS_FC0_SRC_VALUE_CLK_RTC,
+        };
+        Hertz::khz(unsafe { frequency_count_khz(src) })
+    }
+
+    /// Stop it, until configured again. Stopping clk_sys or clk_ref hangs the chip.
+    pub fn stop(self) {
+        unsafe { clock_stop(self.index()) }
+    }
+}
+
+/// A snapshot of the clock frequencies, to work out peripheral dividers from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSet {
+    pub reference: Hertz,
+    pub sys: Hertz,
+    pub peri: Hertz,
+    pub usb: Hertz,
+    pub adc: Hertz,
+    pub rtc: Hertz,
+}
+
+impl ClockSet {
+    /// The frequencies as configured now.
+    pub fn current() -> Self {
+        ClockSet {
+            reference: Clock::Ref.freq(),
+            sys: Clock::Sys.freq(),
+            peri: Clock::Peri.freq(),
+            usb: Clock::Usb.freq(),
+            adc: Clock::Adc.freq(),
+            rtc: Clock::Rtc.freq(),
+        }
+    }
+
+    pub fn get(&self, clock: Clock) -> Hertz {
+        match clock {
+            Clock::Ref => self.reference,
+            Clock::Sys => self.sys,
+            Clock::Peri => self.peri,
+            Clock::Usb => self.usb,
+            Clock::Adc => self.adc,
+            Clock::Rtc => self.rtc,
+        }
+    }
+}
+
+/// What a clock runs from. Not every clock takes every source, see `ClockConfig::clock()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// The crystal oscillator.
+    Xosc,
+    /// The ring oscillator, a few MHz and drifting with voltage and temperature.
+    Rosc,
+    PllSys,
+    PllUsb,
+    /// An external clock of the given frequency on GPIO20.
+    Gpin0(Hertz),
+    /// An external clock of the given frequency on GPIO22.
+    Gpin1(Hertz),
+    /// clk_ref, for clk_sys only.
+    Ref,
+    /// clk_sys, for clk_peri only.
+    Sys,
+}
+
+/// `(src, auxsrc)` for clock_configure(), `None` if `clock` can't run from `source`
+fn mux(clock: Clock, source: Source) -> Option<(u32, u32)> {
+    use Source::*;
+    Some(match clock {
+        Clock::Ref => match source {
+            Rosc => (CLOCKS_CLK_REF_CTRL_SRC_VALUE_ROSC_CLKSRC_PH, 0),
+            Xosc => (CLOCKS_CLK_REF_CTRL_SRC_VALUE_XOSC_CLKSRC, 0),
+            PllUsb | Gpin0(_) | Gpin1(_) => (
+                CLOCKS_CLK_REF_CTRL_SRC_VALUE_CLKSRC_CLK_REF_AUX,
+                match source {
+                    PllUsb => CLOCKS_CLK_REF_CTRL_AUXSRC_VALUE_CLKSRC_PLL_USB,
+                    Gpin0(_) => CLOCKS_CLK_REF_CTRL_AUXSRC_VALUE_CLKSRC_GPIN0,
+                    _ => CLOCKS_CLK_REF_CTRL_AUXSRC_VALUE_CLKSRC_GPIN1,
+                },
+            ),
+            _ => return None,
+        },
+        Clock::Sys => match source {
+            Ref => (CLOCKS_CLK_SYS_CTRL_SRC_VALUE_CLK_REF, 0),
+            _ => (
+                CLOCKS_CLK_SYS_CTRL_SRC_VALUE_CLKSRC_CLK_SYS_AUX,
+                match source {
+                    PllSys => CLOCKS_CLK_SYS_CTRL_AUXSRC_VALUE_CLKSRC_PLL_SYS,
+                    PllUsb => CLOCKS_CLK_SYS_CTRL_AUXSRC_VALUE_CLKSRC_PLL_USB,
+                    Rosc => CLOCKS_CLK_SYS_CTRL_AUXSRC_VALUE_ROSC_CLKSRC,
+                    Xosc => CLOCKS_CLK_SYS_CTRL_AUXSRC_VALUE_XOSC_CLKSRC,
+                    Gpin0(_) => CLOCKS_CLK_SYS_CTRL_AUXSRC_VALUE_CLKSRC_GPIN0,
+                    Gpin1(_) => CLOCKS_CLK_SYS_CTRL_AUXSRC_VALUE_CLKSRC_GPIN1,
+                    Sys | Ref => return None,
+                },
+            ),
+        },
+        Clock::Peri => (
+            0,
+            match source {
+                Sys => CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_CLK_SYS,
+                PllSys => CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_CLKSRC_PLL_SYS,
+                PllUsb => CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_CLKSRC_PLL_USB,
+                Rosc => CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_ROSC_CLKSRC_PH,
+                Xosc => CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_XOSC_CLKSRC,
+                Gpin0(_) => CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_CLKSRC_GPIN0,
+                Gpin1(_) => CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_CLKSRC_GPIN1,
+                Ref => return None,
+            },
+        ),
+        Clock::Usb | Clock::Adc | Clock::Rtc => (
+            0,
+            match source {
+                PllUsb => CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_CLKSRC_PLL_USB,
+                PllSys => CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_CLKSRC_PLL_SYS,
+                Rosc => CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_ROSC_CLKSRC_PH,
+                Xosc => CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_XOSC_CLKSRC,
+                Gpin0(_) => CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_CLKSRC_GPIN0,
+                Gpin1(_) => CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_CLKSRC_GPIN1,
+                Ref | Sys => return None,
+            },
+        ),
+    })
+}
+
+/// The PLL `clock` runs from now, if any. Not clk_peri through clk_sys.
+fn current_pll(clock: Clock) -> Option<Source> {
+    let ctrl = unsafe { (*clocks_hw).clk[clock.index() as usize].ctrl.read() };
+    // CTRL.SRC from bit 0, CTRL.AUXSRC from bit 5
+    let src = ctrl & 0x3;
+    let auxsrc = (ctrl >> 5) & 0x7;
+    match clock {
+        Clock::Ref => match (src, auxsrc) {
+            (
+                CLOCKS_CLK_REF_CTRL_SRC_VALUE_CLKSRC_CLK_REF_AUX,
+                CLOCKS_CLK_REF_CTRL_AUXSRC_VALUE_CLKSRC_PLL_USB,
+            ) => Some(Source::PllUsb),
+            _ => None,
+        },
+        Clock::Sys => match (src & 0x1, auxsrc) {
+            (
+                CLOCKS_CLK_SYS_CTRL_SRC_VALUE_CLKSRC_CLK_SYS_AUX,
+                CLOCKS_CLK_SYS_CTRL_AUXSRC_VALUE_CLKSRC_PLL_SYS,
+            ) => Some(Source::PllSys),
+            (
+                CLOCKS_CLK_SYS_CTRL_SRC_VALUE_CLKSRC_CLK_SYS_AUX,
+                CLOCKS_CLK_SYS_CTRL_AUXSRC_VALUE_CLKSRC_PLL_USB,
+            ) => Some(Source::PllUsb),
+            _ => None,
+        },
+        Clock::Peri => match auxsrc {
+            CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_CLKSRC_PLL_SYS => Some(Source::PllSys),
+            CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_CLKSRC_PLL_USB => Some(Source::PllUsb),
+            _ => None,
+        },
+        Clock::Usb | Clock::Adc | Clock::Rtc => match auxsrc {
+            CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_CLKSRC_PLL_SYS => Some(Source::PllSys),
+            CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_CLKSRC_PLL_USB => Some(Source::PllUsb),
+            _ => None,
+        },
+    }
+}
+
+/// VCO limits, from the datasheet
+pub const VCO_MIN: Hertz = Hertz::mhz(400);
+pub const VCO_MAX: Hertz = Hertz::mhz(1600);
+
+/// A PLL setting, run from the crystal without a reference divider:
+/// `vco / (post_div1 * post_div2)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pll {
+    pub vco: Hertz,
+    /// 1..=7
+    pub post_div1: u8,
+    /// 1..=7, no more than `post_div1`
+    pub post_div2: u8,
+}
+
+impl Pll {
+    /// pico-sdk's default for pll_sys.
+    pub const SYS_125MHZ: Pll = Pll::new(Hertz::mhz(1500), 6, 2);
+    /// pico-sdk's default for pll_usb.
+    pub const USB_48MHZ: Pll = Pll::new(Hertz::mhz(480), 5, 2);
+
+    pub const fn new(vco: Hertz, post_div1: u8, post_div2: u8) -> Self {
+        Pll {
+            vco,
+            post_div1,
+            post_div2,
+        }
+    }
+
+    pub const fn freq(&self) -> Hertz {
+        Hertz(self.vco.0 / (self.post_div1 as u32 * self.post_div2 as u32))
+    }
+
+    /// Within the RP2040 limits, for a crystal of `xosc`.
+    pub fn validate(&self, xosc: Hertz) -> Result<(), Error> {
+        let fbdiv = self.vco.0 / xosc.0;
+        let ok = (VCO_MIN..=VCO_MAX).contains(&self.vco)
+            && self.vco.0.is_multiple_of(xosc.0)
+            && (16..=320).contains(&fbdiv)
+            && (1..=7).contains(&self.post_div1)
+            && (1..=self.post_div1).contains(&self.post_div2);
+        if ok {
+            Ok(())
+        } else {
+            Err(Error::Pll)
+        }
+    }
+
+    /// What `pll` runs at now, `None` if powered down.
+    fn current(pll: PLL, xosc: Hertz) -> Option<Hertz> {
+        unsafe {
+            let pll = &*pll;
+            // PWR.PD
+            if pll.pwr.read() & 0x1 != 0 {
+                return None;
+            }
+            let refdiv = pll.cs.read() & 0x3f;
+            let fbdiv = pll.fbdiv_int.read() & 0xfff;
+            let prim = pll.prim.read();
+            let post_div1 = (prim >> 16) & 0x7;
+            let post_div2 = (prim >> 12) & 0x7;
+            let vco = xosc.0 as u64 / refdiv as u64 * fbdiv as u64;
+            Some(Hertz((vco / (post_div1 * post_div2) as u64) as u32))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The PLL setting is out of the RP2040 limits.
+    Pll,
+    /// The clock can't run from that source.
+    Source(Clock),
+    /// The clock can't be divided down to that frequency, or its source isn't running.
+    Freq(Clock),
+    /// Not a GPOUT pin.
+    Pin(uint),
+    /// A GPOUT divider out of 1..2^24.
+    Div(u32),
+    /// A PLL changes, but this clock runs from it and isn't configured again.
+    Stale(Clock),
+    /// pico-sdk's `clock_configure()` refused the setting.
+    Configure(Clock),
+}
+
+/// A change to the clock tree, applied at once by `apply()`. Whatever isn't
+/// mentioned stays as it is.
+#[derive(Debug, Clone, Copy)]
+pub struct ClockConfig {
+    xosc: Hertz,
+    pll_sys: Option<Pll>,
+    pll_usb: Option<Pll>,
+    clocks: [Option<(Source, Hertz)>; 6],
+}
+
+impl ClockConfig {
+    pub const fn new() -> Self {
+        ClockConfig {
+            xosc: XOSC_FREQ,
+            pll_sys: None,
+            pll_usb: None,
+            clocks: [None; 6],
+        }
+    }
+
+    /// pico-sdk's defaults, as set up before `main`.
+    pub const fn pico_default() -> Self {
+        ClockConfig::new()
+            .pll_sys(Pll::SYS_125MHZ)
+            .pll_usb(Pll::USB_48MHZ)
+            .clock(Clock::Ref, Source::Xosc, XOSC_FREQ)
+            .clock(Clock::Sys, Source::PllSys, Hertz::mhz(125))
+            .clock(Clock::Usb, Source::PllUsb, Hertz::mhz(48))
+            .clock(Clock::Adc, Source::PllUsb, Hertz::mhz(48))
+            .clock(Clock::Rtc, Source::PllUsb, Hertz(46875))
+            .clock(Clock::Peri, Source::Sys, Hertz::mhz(125))
+    }
+
+    /// A crystal other than the Pico's 12 MHz one. pico-sdk's runtime still
+    /// assumes 12 MHz for the XOSC startup delay and the watchdog tick.
+    pub const fn xosc(mut self, freq: Hertz) -> Self {
+        self.xosc = freq;
+        self
+    }
+
+    pub const fn pll_sys(mut self, pll: Pll) -> Self {
+        self.pll_sys = Some(pll);
+        self
+    }
+
+    pub const fn pll_usb(mut self, pll: Pll) -> Self {
+        self.pll_usb = Some(pll);
+        self
+    }
+
+    /// Run `clock` from `source`, divided down to `freq`.
+    ///
+    /// - clk_ref: `Xosc`, `Rosc`, `PllUsb`, `Gpin0/1`, integer dividers
+    /// - clk_sys: `Ref`, `PllSys`, `PllUsb`, `Xosc`, `Rosc`, `Gpin0/1`
+    /// - clk_peri: `Sys`, `PllSys`, `PllUsb`, `Xosc`, `Rosc`, `Gpin0/1`, not divided
+    /// - clk_usb, clk_adc: `PllUsb`, `PllSys`, `Xosc`, `Rosc`, `Gpin0/1`, integer dividers
+    /// - clk_rtc: the same, fractional divider
+    pub const fn clock(mut self, clock: Clock, source: Source, freq: Hertz) -> Self {
+        self.clocks[clock as usize] = Some((source, freq));
+        self
+    }
+
+    /// Frequency of `source` once applied
+    fn source_freq(&self, source: Source) -> Option<Hertz> {
+        let current = |clock: Clock| match self.clocks[clock as usize] {
+            Some((_, freq)) => Some(freq),
+            None => Some(clock.freq()),
+        };
+        match source {
+            Source::Xosc => Some(self.xosc),
+            Source::Rosc => Some(Hertz::khz(unsafe {
+                frequency_count_khz(CLOCKS_FC0_SRC_VALUE_ROSC_CLKSRC)
+            })),
+            Source::PllSys => match self.pll_sys {
+                Some(pll) => Some(pll.freq()),
+                None => Pll::current(pll_sys, self.xosc),
+            },
+            Source::PllUsb => match self.pll_usb {
+                Some(pll) => Some(pll.freq()),
+                None => Pll::current(pll_usb, self.xosc),
+            },
+            Source::Gpin0(freq) | Source::Gpin1(freq) => Some(freq),
+            Source::Ref => current(Clock::Ref),
+            Source::Sys => current(Clock::Sys),
+        }
+    }
+
+    /// clk_peri following clk_sys, unless configured
+    fn with_peri(&self) -> Self {
+        let mut config = *self;
+        if let (Some((_, sys)), None) = (
+            config.clocks[Clock::Sys as usize],
+            config.clocks[Clock::Peri as usize],
+        ) {
+            config.clocks[Clock::Peri as usize] = Some((Source::Sys, sys));
+        }
+        config
+    }
+
+    /// The checks of `apply()`, against the clock tree as it is now, without
+    /// changing anything.
+    pub fn validate(&self) -> Result<(), Error> {
+        self.with_peri().plan().map(|_| ())
+    }
+
+    /// Everything checked, `(src, auxsrc, src_freq)` of each configured clock
+    #[allow(clippy::type_complexity)]
+    fn plan(&self) -> Result<[Option<(u32, u32, Hertz)>; 6], Error> {
+        if let Some(pll) = self.pll_sys {
+            pll.validate(self.xosc)?;
+        }
+        if let Some(pll) = self.pll_usb {
+            pll.validate(self.xosc)?;
+        }
+        let mut plan = [None; 6];
+        for clock in Clock::ALL {
+            let (source, freq) = match self.clocks[clock as usize] {
+                Some(c) => c,
+                None => {
+                    // clk_sys is moved to clk_ref while pll_sys changes, and left there
+                    let stale = (clock == Clock::Sys && self.pll_sys.is_some())
+                        || match current_pll(clock) {
+                            Some(Source::PllSys) => self.pll_sys.is_some(),
+                            Some(Source::PllUsb) => self.pll_usb.is_some(),
+                            _ => false,
+                        };
+                    if stale {
+                        return Err(Error::Stale(clock));
+                    }
+                    continue;
+                }
+            };
+            let (src, auxsrc) = mux(clock, source).ok_or(Error::Source(clock))?;
+            let src_freq = self.source_freq(source).ok_or(Error::Freq(clock))?;
+            // clk_peri has no divider
+            let ok = match clock {
+                Clock::Peri => freq == src_freq,
+                _ => freq.0 > 0 && freq <= src_freq,
+            };
+            if !ok {
+                return Err(Error::Freq(clock));
+            }
+            plan[clock as usize] = Some((src, auxsrc, src_freq));
+        }
+        Ok(plan)
+    }
+
+    /// Change the clock tree, once everything checked out: nothing is changed
+    /// on an error, but for `Error::Configure`, which stops halfway.
+    ///
+    /// clk_sys runs from clk_ref while pll_sys is set up. If clk_sys changes
+    /// and clk_peri isn't configured, clk_peri is assumed to follow it, as
+    /// pico-sdk's `set_sys_clock_pll()` does.
+    ///
+    /// A PLL change needs every clock running from it configured again,
+    /// clk_sys always for pll_sys: `Error::Stale` otherwise.
+    pub fn apply(&self) -> Result<ClockSet, Error> {
+        let config = self.with_peri();
+        let plan = config.plan()?;
+
+        unsafe {
+            if let Some(pll) = config.pll_sys {
+                let reference = clock_get_hz(clk_ref);
+                if !clock_configure(
+                    clk_sys,
+                    CLOCKS_CLK_SYS_CTRL_SRC_VALUE_CLK_REF,
+                    0,
+                    reference,
+                    reference,
+                ) {
+                    return Err(Error::Configure(Clock::Sys));
+                }
+                pll_init(
+                    pll_sys,
+                    1,
+                    pll.vco.0,
+                    pll.post_div1 as _,
+                    pll.post_div2 as _,
+                );
+            }
+            if let Some(pll) = config.pll_usb {
+                pll_init(
+                    pll_usb,
+                    1,
+                    pll.vco.0,
+                    pll.post_div1 as _,
+                    pll.post_div2 as _,
+                );
+            }
+            // sources before what runs from them: clk_sys after clk_ref, clk_peri after clk_sys
+            for clock in Clock::ALL {
+                if let (Some((src, auxsrc, src_freq)), Some((_, freq))) =
+                    (plan[clock as usize], config.clocks[clock as usize])
+                {
+                    if !clock_configure(clock.index(), src, auxsrc, src_freq.0, freq.0) {
+                        return Err(Error::Configure(clock));
+                    }
+                }
+            }
+        }
+        Ok(ClockSet::current())
+    }
+}
+
+impl Default for ClockConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What a GPOUT pin can output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpoutSource {
+    PllSys,
+    PllUsb,
+    Xosc,
+    Rosc,
+    /// The clock on GPIO20.
+    Gpin0,
+    /// The clock on GPIO22.
+    Gpin1,
+    /// Any `Clock` but clk_peri.
+    Clock(Clock),
+}
+
+/// Output `source` divided by `div` on GPIO21, 23, 24 or 25, GPOUT0 to 3.
+/// The pads manage about 50 MHz, divide down fast clocks.
+pub fn gpout(pin: uint, source: GpoutSource, div: u32) -> Result<(), Error> {
+    if !matches!(pin, 21 | 23 | 24 | 25) {
+        return Err(Error::Pin(pin));
+    }
+    let auxsrc = match source {
+        GpoutSource::PllSys => CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLKSRC_PLL_SYS,
+        GpoutSource::PllUsb => CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLKSRC_PLL_USB,
+        GpoutSource::Xosc => CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_XOSC_CLKSRC,
+        GpoutSource::Rosc => CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_ROSC_CLKSRC,
+        GpoutSource::Gpin0 => CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLKSRC_GPIN0,
+        GpoutSource::Gpin1 => CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLKSRC_GPIN1,
+        GpoutSource::Clock(Clock::Ref) => CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLK_REF,
+        GpoutSource::Clock(Clock::Sys) => CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLK_SYS,
+        GpoutSource::Clock(Clock::Usb) => CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLK_USB,
+        GpoutSource::Clock(Clock::Adc) => CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLK_ADC,
+        GpoutSource::Clock(Clock::Rtc) => CLOCKS_CLK_GPOUT0_CTRL_AUXSRC_VALUE_CLK_RTC,
+        GpoutSource::Clock(clock @ Clock::Peri) => return Err(Error::Source(clock)),
+    };
+    // 24 integer bits
+    if !(1..1 << 24).contains(&div) {
+        return Err(Error::Div(div));
+    }
+    unsafe {
+        clock_gpio_init(pin, auxsrc, div);
+    }
+    Ok(())
+}
+
+/// Stop the clock output on `pin`, see `gpout()`.
+pub fn gpout_stop(pin: uint) -> Result<(), Error> {
+    let clock = match pin {
+        21 => clk_gpout0,
+        23 => clk_gpout1,
+        24 => clk_gpout2,
+        25 => clk_gpout3,
+        _ => return Err(Error::Pin(pin)),
+    };
+    unsafe {
+        clock_stop(clock);
+    }
+    Ok(())
+}
@@ -0,0 +1,291 @@
+//! `defmt` global logger, enabled by one of the transport features:
+//!
+//! - `defmt-uart`: raw frames on `PICO_DEFAULT_UART`, call `init_uart()` first.
+//!   Don't share it with stdio, text output would break the framing.
+//! - `defmt-usb`: raw frames through the stdio_usb CDC driver, bypassing CR/LF
+//!   translation. Needs pico_stdio_usb linked in and `stdio_usb_init()`.
+//!   A frame is buffered and sent once interrupts are back on, USB needs
+//!   them; frames over `USB_FRAME_SIZE` are dropped.
+//! - `defmt-rtt`: a SEGGER RTT up channel in RAM, for a debug probe, e.g. OpenOCD
+//!   with `ft2232h-swd.cfg`.
+//!
+//! Logging works from both cores and from interrupt handlers: a frame is
+//! written with interrupts off and `DEFMT_SPINLOCK` held.
+//!
+//! Timestamps are `time_us_64()`. Decode with `pico-tool defmt <elf> [capture]`.
+//!
+//! The program must be linked with `-Tdefmt.x`.
+
+#[cfg(not(any(feature = "defmt-uart", feature = "defmt-usb", feature = "defmt-rtt")))]
+compile_error!("`defmt` needs a transport: enable one of `defmt-uart`, `defmt-usb` or `defmt-rtt`");
+
+#[cfg(any(
+    all(feature = "defmt-uart", feature = "defmt-usb"),
+    all(feature = "defmt-uart", feature = "defmt-rtt"),
+    all(feature = "defmt-usb", feature = "defmt-rtt")
+))]
+compile_error!("only one of `defmt-uart`, `defmt-usb` and `defmt-rtt` can be enabled");
+
+use core::ptr::{self, addr_of_mut};
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use rpi_pico_sdk_sys::*;
+
+#[cfg(feature = "defmt-rtt")]
+pub use self::rtt::BUF_SIZE as RTT_BUF_SIZE;
+#[cfg(feature = "defmt-usb")]
+pub use self::transport::FRAME_SIZE as USB_FRAME_SIZE;
+
+/// SIO spin lock held while a frame is written, one pico-sdk reserves but
+/// doesn't use.
+pub const DEFMT_SPINLOCK: u32 = 13;
+
+::defmt::timestamp!("{=u64:us}", unsafe { time_us_64() });
+
+#[::defmt::global_logger]
+struct Logger;
+
+/// Core holding the logger plus one, 0 if none. Written only by the holder.
+static OWNER: AtomicU32 = AtomicU32::new(0);
+// the rest only while holding DEFMT_SPINLOCK
+static mut INTERRUPTS: u32 = 0;
+static mut ENCODER: ::defmt::Encoder = ::defmt::Encoder::new();
+
+fn spin_lock() -> *mut u32 {
+    (SIO_BASE + SIO_SPINLOCK0_OFFSET + 4 * DEFMT_SPINLOCK) as *mut u32
+}
+
+unsafe impl ::defmt::Logger for Logger {
+    fn acquire() {
+        unsafe {
+            let interrupts = save_and_disable_interrupts();
+            let core = (*(SIO_BASE as *const sio_hw_t)).cpuid.read() + 1;
+            // the other core can't have stored this core's number
+            if OWNER.load(Ordering::Relaxed) == core {
+                panic!("defmt logger taken reentrantly");
+            }
+            // reading claims the spin lock, 0 while the other core holds it
+            while ptr::read_volatile(spin_lock()) == 0 {}
+            __dmb();
+            OWNER.store(core, Ordering::Relaxed);
+            INTERRUPTS = interrupts;
+            (*addr_of_mut!(ENCODER)).start_frame(transport::write);
+        }
+    }
+
+    unsafe fn flush() {
+        transport::flush();
+    }
+
+    unsafe fn release() {
+        (*addr_of_mut!(ENCODER)).end_frame(transport::write);
+        #[cfg(feature = "defmt-usb")]
+        let frame = transport::take_frame();
+        let interrupts = INTERRUPTS;
+        OWNER.store(0, Ordering::Relaxed);
+        __dmb();
+        ptr::write_volatile(spin_lock(), 0);
+        restore_interrupts(interrupts);
+        #[cfg(feature = "defmt-usb")]
+        transport::send(frame);
+    }
+
+    unsafe fn write(bytes: &[u8]) {
+        (*addr_of_mut!(ENCODER)).write(bytes, transport::write);
+    }
+}
+
+/// Set up `PICO_DEFAULT_UART` and its TX pin for `defmt-uart`.
+#[cfg(feature = "defmt-uart")]
+pub fn init_uart(baudrate: uint) {
+    unsafe {
+        uart_init(transport::UART, baudrate);
+        gpio_set_function(PICO_DEFAULT_UART_TX_PIN, GPIO_FUNC_UART);
+    }
+}
+
+#[cfg(feature = "defmt-uart")]
+mod transport {
+    use rpi_pico_sdk_sys::*;
+
+    pub const UART: *mut uart_inst_t = if PICO_DEFAULT_UART == 0 { uart0 } else { uart1 };
+
+    pub fn write(bytes: &[u8]) {
+        unsafe {
+            uart_write_blocking(UART, bytes.as_ptr(), bytes.len() as _);
+        }
+    }
+
+    pub fn flush() {
+        unsafe {
+            uart_tx_wait_blocking(UART);
+        }
+    }
+}
+
+/// Writes into a frame buffer, with interrupts off and the lock held; the
+/// frame goes to the CDC driver after `release()`.
+#[cfg(feature = "defmt-usb")]
+mod transport {
+    use core::ptr::{addr_of, addr_of_mut};
+
+    use rpi_pico_sdk_sys::*;
+
+    /// Largest frame sent, longer ones are dropped
+    pub const FRAME_SIZE: usize = 256;
+
+    static mut BUF: [u8; FRAME_SIZE] = [0; FRAME_SIZE];
+    /// `None` once the frame overflowed
+    static mut LEN: Option<usize> = Some(0);
+    static mut FLUSH: bool = false;
+
+    /// A copy of the frame, on the stack of the core that logged it
+    pub struct Frame {
+        buf: [u8; FRAME_SIZE],
+        len: usize,
+        flush: bool,
+    }
+
+    pub fn write(bytes: &[u8]) {
+        unsafe {
+            LEN = match LEN {
+                Some(len) if len + bytes.len() <= FRAME_SIZE => {
+                    (&mut *addr_of_mut!(BUF))[len..len + bytes.len()].copy_from_slice(bytes);
+                    Some(len + bytes.len())
+                }
+                _ => None,
+            };
+        }
+    }
+
+    pub fn flush() {
+        unsafe {
+            FLUSH = true;
+        }
+    }
+
+    pub fn take_frame() -> Frame {
+        unsafe {
+            let frame = Frame {
+                buf: *addr_of!(BUF),
+                len: LEN.unwrap_or(0),
+                flush: FLUSH,
+            };
+            LEN = Some(0);
+            FLUSH = false;
+            frame
+        }
+    }
+
+    pub fn send(frame: Frame) {
+        unsafe {
+            let driver = addr_of_mut!(stdio_usb);
+            if let (Some(out_chars), true) = ((*driver).out_chars, frame.len > 0) {
+                out_chars(frame.buf.as_ptr() as _, frame.len as _);
+            }
+            if let (Some(out_flush), true) = ((*driver).out_flush, frame.flush) {
+                out_flush();
+            }
+        }
+    }
+}
+
+#[cfg(feature = "defmt-rtt")]
+use self::rtt as transport;
+
+/// SEGGER RTT, a control block the debug probe finds by its ID, with one up channel.
+#[cfg(feature = "defmt-rtt")]
+mod rtt {
+    use core::ptr::{self, addr_of, addr_of_mut};
+
+    /// Up channel buffer size
+    pub const BUF_SIZE: usize = 1024;
+
+    const MODE_MASK: usize = 0b11;
+    /// Host asked to block instead of dropping data when the buffer is full
+    const MODE_BLOCK_IF_FULL: usize = 2;
+    /// Write what fits, drop the rest
+    const MODE_NON_BLOCKING_TRIM: usize = 1;
+
+    #[repr(C)]
+    struct Channel {
+        name: *const u8,
+        buffer: *mut u8,
+        size: usize,
+        write: usize,
+        read: usize,
+        flags: usize,
+    }
+
+    #[repr(C)]
+    pub struct ControlBlock {
+        id: [u8; 16],
+        max_up_channels: usize,
+        max_down_channels: usize,
+        up: Channel,
+    }
+
+    static mut BUFFER: [u8; BUF_SIZE] = [0; BUF_SIZE];
+
+    #[no_mangle]
+    static mut _SEGGER_RTT: ControlBlock = ControlBlock {
+        id: *b"SEGGER RTT\0\0\0\0\0\0",
+        max_up_channels: 1,
+        max_down_channels: 0,
+        up: Channel {
+            name: b"defmt\0".as_ptr(),
+            buffer: addr_of_mut!(BUFFER) as *mut u8,
+            size: BUF_SIZE,
+            write: 0,
+            read: 0,
+            flags: MODE_NON_BLOCKING_TRIM,
+        },
+    };
+
+    pub fn write(mut bytes: &[u8]) {
+        unsafe {
+            let up = addr_of_mut!(_SEGGER_RTT.up);
+            while !bytes.is_empty() {
+                let read = ptr::read_volatile(addr_of!((*up).read));
+                let write = ptr::read_volatile(addr_of!((*up).write));
+                // one byte is kept free, read == write means empty
+                let available = if read > write {
+                    read - write - 1
+                } else if read == 0 {
+                    BUF_SIZE - write - 1
+                } else {
+                    BUF_SIZE - write
+                };
+                if available == 0 {
+                    let flags = ptr::read_volatile(addr_of!((*up).flags));
+                    if flags & MODE_MASK == MODE_BLOCK_IF_FULL {
+                        continue;
+                    }
+                    return;
+                }
+
+                let n = available.min(bytes.len());
+                ptr::copy_nonoverlapping(
+                    bytes.as_ptr(),
+                    (addr_of_mut!(BUFFER) as *mut u8).add(write),
+                    n,
+                );
+                rpi_pico_sdk_sys::__dmb();
+                ptr::write_volatile(addr_of_mut!((*up).write), (write + n) % BUF_SIZE);
+                bytes = &bytes[n..];
+            }
+        }
+    }
+
+    pub fn flush() {
+        unsafe {
+            let up = addr_of!(_SEGGER_RTT.up);
+            if ptr::read_volatile(addr_of!((*up).flags)) & MODE_MASK != MODE_BLOCK_IF_FULL {
+                return;
+            }
+            while ptr::read_volatile(addr_of!((*up).read))
+                != ptr::read_volatile(addr_of!((*up).write))
+            {}
+        }
+    }
+}
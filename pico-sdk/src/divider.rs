@@ -0,0 +1,176 @@
+//! The SIO hardware divider: a 32 bit quotient and remainder in 8 cycles,
+//! where the software division on the Cortex-M0+ takes tens.
+//!
+//! ```ignore
+//! let (quotient, remainder) = divider::divmod_u32(1000, 7);
+//! let q = divider::quotient_s32(-1000, 7);
+//! ```
+//!
+//! Each core has its own divider. Code interrupted in the middle of a
+//! division gets its result anyway: the functions here save the divider's
+//! state when they find a division in flight (CSR `DIRTY`) and restore it
+//! when done, as pico-sdk's pico_divider does. `save_state()` and
+//! `restore_state()` do the same for code driving the registers directly.
+//!
+//! With the `divider-intrinsics` feature, the compiler-builtins routines
+//! behind `/` and `%` on 32 bit integers use the hardware divider too.
+
+use rpi_pico_sdk_sys::*;
+
+#[inline(always)]
+fn sio() -> &'static sio_hw_t {
+    unsafe { &*(SIO_BASE as *const sio_hw_t) }
+}
+
+/// A division in flight, or results not read yet
+#[inline(always)]
+fn is_dirty() -> bool {
+    sio().div_csr.read() & SIO_DIV_CSR_DIRTY_BITS != 0
+}
+
+#[inline(always)]
+fn wait_ready() {
+    while sio().div_csr.read() & SIO_DIV_CSR_READY_BITS == 0 {}
+}
+
+/// The divider's registers, to put back after using it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct State {
+    dividend: u32,
+    divisor: u32,
+    quotient: u32,
+    remainder: u32,
+}
+
+/// The divider's state, waiting for a division in flight to finish.
+#[inline]
+pub fn save_state() -> State {
+    let sio = sio();
+    wait_ready();
+    State {
+        dividend: sio.div_udividend.read(),
+        divisor: sio.div_udivisor.read(),
+        remainder: sio.div_remainder.read(),
+        // reading the quotient clears DIRTY, so last
+        quotient: sio.div_quotient.read(),
+    }
+}
+
+/// Put back a state from `save_state()`.
+#[inline]
+pub fn restore_state(state: &State) {
+    let sio = sio();
+    wait_ready();
+    sio.div_udividend.write(state.dividend);
+    sio.div_udivisor.write(state.divisor);
+    // writing the results stops the division just started, and sets DIRTY
+    // as it was for an unread result
+    sio.div_remainder.write(state.remainder);
+    sio.div_quotient.write(state.quotient);
+}
+
+/// Quotient and remainder, no check for 0: the hardware gives an all ones
+/// quotient (unsigned) or ±1 (signed) and the dividend as remainder.
+#[inline(always)]
+fn divmod_raw(signed: bool, dividend: u32, divisor: u32) -> (u32, u32) {
+    let run = || {
+        let sio = sio();
+        if signed {
+            sio.div_sdividend.write(dividend);
+            sio.div_sdivisor.write(divisor);
+        } else {
+            sio.div_udividend.write(dividend);
+            sio.div_udivisor.write(divisor);
+        }
+        wait_ready();
+        let remainder = sio.div_remainder.read();
+        (sio.div_quotient.read(), remainder)
+    };
+    if is_dirty() {
+        let state = save_state();
+        let result = run();
+        restore_state(&state);
+        result
+    } else {
+        run()
+    }
+}
+
+/// Unsigned quotient and remainder.
+///
+/// Panics if `divisor` is 0, like `/`.
+#[inline]
+pub fn divmod_u32(dividend: u32, divisor: u32) -> (u32, u32) {
+    assert!(divisor != 0, "attempt to divide by zero");
+    divmod_raw(false, dividend, divisor)
+}
+
+/// Signed quotient, rounded towards zero, and remainder with the sign of the
+/// dividend, as `/` and `%`. `i32::MIN / -1` wraps to `i32::MIN`.
+///
+/// Panics if `divisor` is 0, like `/`.
+#[inline]
+pub fn divmod_s32(dividend: i32, divisor: i32) -> (i32, i32) {
+    assert!(divisor != 0, "attempt to divide by zero");
+    if divisor == -1 {
+        return (dividend.wrapping_neg(), 0);
+    }
+    let (quotient, remainder) = divmod_raw(true, dividend as u32, divisor as u32);
+    (quotient as i32, remainder as i32)
+}
+
+/// `dividend / divisor` on the hardware divider.
+#[inline]
+pub fn quotient_u32(dividend: u32, divisor: u32) -> u32 {
+    divmod_u32(dividend, divisor).0
+}
+
+/// `dividend % divisor` on the hardware divider.
+#[inline]
+pub fn remainder_u32(dividend: u32, divisor: u32) -> u32 {
+    divmod_u32(dividend, divisor).1
+}
+
+/// `dividend / divisor` on the hardware divider.
+#[inline]
+pub fn quotient_s32(dividend: i32, divisor: i32) -> i32 {
+    divmod_s32(dividend, divisor).0
+}
+
+/// `dividend % divisor` on the hardware divider.
+#[inline]
+pub fn remainder_s32(dividend: i32, divisor: i32) -> i32 {
+    divmod_s32(dividend, divisor).1
+}
+
+/// The compiler-builtins routines LLVM calls for 32 bit `/` and `%` on
+/// thumbv6m, overridden like the ROM float ones. Rust checks for 0 before
+/// calling them. pico_divider in pico_stdlib defines the same symbols for C:
+/// with both linked, either one is the hardware divider.
+#[cfg(all(feature = "divider-intrinsics", target_arch = "arm"))]
+mod intrinsics {
+    use super::divmod_raw;
+
+    #[no_mangle]
+    pub extern "aapcs" fn __aeabi_uidiv(dividend: u32, divisor: u32) -> u32 {
+        divmod_raw(false, dividend, divisor).0
+    }
+
+    #[no_mangle]
+    pub extern "aapcs" fn __aeabi_idiv(dividend: i32, divisor: i32) -> i32 {
+        divmod_raw(true, dividend as u32, divisor as u32).0 as i32
+    }
+
+    /// Quotient in r0, remainder in r1: a `u64` returned by AAPCS
+    #[no_mangle]
+    pub extern "aapcs" fn __aeabi_uidivmod(dividend: u32, divisor: u32) -> u64 {
+        let (quotient, remainder) = divmod_raw(false, dividend, divisor);
+        quotient as u64 | (remainder as u64) << 32
+    }
+
+    #[no_mangle]
+    pub extern "aapcs" fn __aeabi_idivmod(dividend: i32, divisor: i32) -> u64 {
+        let (quotient, remainder) = divmod_raw(true, dividend as u32, divisor as u32);
+        quotient as u64 | (remainder as u64) << 32
+    }
+}
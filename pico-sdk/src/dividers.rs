@@ -0,0 +1,324 @@
+//! Divider solvers for the PLLs, PWM, UART and SPI, in plain `const fn`: no
+//! hardware access, so clock plans can be worked out at compile time or on
+//! the host.
+//!
+//! ```ignore
+//! const PLL: Pll = match dividers::pll(XOSC_FREQ, Hertz::mhz(133)) {
+//!     Some(pll) => pll,
+//!     None => panic!("no PLL setting for 133 MHz"),
+//! };
+//! const BAUD: UartDividers = dividers::uart(Hertz::mhz(125), Hertz(115_200));
+//! ```
+
+use crate::clocks::{Hertz, Pll, VCO_MAX, VCO_MIN};
+
+/// The PLL setting closest to `target` from a crystal of `xosc`, the one
+/// with the highest VCO frequency among equally close ones, for the least
+/// jitter. The same search as pico-sdk's check_sys_clock_khz() and the
+/// datasheet's vcocalc.py, but not limited to exact matches. `None` if
+/// `xosc` is out of range.
+pub const fn pll(xosc: Hertz, target: Hertz) -> Option<Pll> {
+    let mut best: Option<Pll> = None;
+    let mut best_err = u32::MAX;
+    let mut fbdiv: u64 = 320;
+    while fbdiv >= 16 {
+        let vco = fbdiv * xosc.0 as u64;
+        if vco >= VCO_MIN.0 as u64 && vco <= VCO_MAX.0 as u64 {
+            let mut post_div1: u8 = 7;
+            while post_div1 >= 1 {
+                let mut post_div2 = post_div1;
+                while post_div2 >= 1 {
+                    let out = (vco / (post_div1 as u64 * post_div2 as u64)) as u32;
+                    let err = out.abs_diff(target.0);
+                    if err < best_err {
+                        best_err = err;
+                        best = Some(Pll::new(Hertz(vco as u32), post_div1, post_div2));
+                    }
+                    post_div2 -= 1;
+                }
+                post_div1 -= 1;
+            }
+        }
+        fbdiv -= 1;
+    }
+    best
+}
+
+/// A PWM slice divider and wrap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PwmDividers {
+    /// 1..=255
+    pub int: u8,
+    /// In 1/16
+    pub frac: u8,
+    pub top: u16,
+}
+
+impl PwmDividers {
+    /// The output frequency from `freq_in`, rounded down.
+    pub const fn freq(&self, freq_in: Hertz) -> Hertz {
+        let div16 = self.int as u64 * 16 + self.frac as u64;
+        Hertz((freq_in.0 as u64 * 16 / (div16 * (self.top as u64 + 1))) as u32)
+    }
+}
+
+/// The PWM divider and wrap closest to `freq`, with the finest duty
+/// resolution: the smallest divider that lets `top` reach `freq` in 16 bits,
+/// then the `top` closest to it. Slowest or fastest possible if out of range.
+pub const fn pwm(freq_in: Hertz, freq: Hertz) -> PwmDividers {
+    let freq = if freq.0 == 0 { 1 } else { freq.0 as u64 };
+    let scaled = freq_in.0 as u64 * 16;
+    let div16 = clamp(scaled.div_ceil(freq * 65536), 16, 4095);
+    let wrap = clamp((scaled + div16 * freq / 2) / (div16 * freq), 1, 65536);
+    PwmDividers {
+        int: (div16 >> 4) as u8,
+        frac: (div16 & 0xf) as u8,
+        top: (wrap - 1) as u16,
+    }
+}
+
+/// The UART baud rate divisor, of 16 * the baud rate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UartDividers {
+    /// 1..=65535
+    pub ibrd: u16,
+    /// In 1/64
+    pub fbrd: u8,
+}
+
+impl UartDividers {
+    /// The baud rate from `freq_in`, rounded down.
+    pub const fn baudrate(&self, freq_in: Hertz) -> Hertz {
+        Hertz((4 * freq_in.0 as u64 / (64 * self.ibrd as u64 + self.fbrd as u64)) as u32)
+    }
+}
+
+/// The UART divisor for `baudrate`, rounded to the nearest 1/64 as
+/// pico-sdk's uart_set_baudrate() does.
+pub const fn uart(freq_in: Hertz, baudrate: Hertz) -> UartDividers {
+    let baudrate = if baudrate.0 == 0 {
+        1
+    } else {
+        baudrate.0 as u64
+    };
+    // in 1/128
+    let div = 8 * freq_in.0 as u64 / baudrate;
+    let ibrd = div >> 7;
+    if ibrd == 0 {
+        UartDividers { ibrd: 1, fbrd: 0 }
+    } else if ibrd >= 65535 {
+        UartDividers {
+            ibrd: 65535,
+            fbrd: 0,
+        }
+    } else {
+        UartDividers {
+            ibrd: ibrd as u16,
+            fbrd: (div & 0x7f).div_ceil(2) as u8,
+        }
+    }
+}
+
+/// The SPI clock prescaler and post divider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpiDividers {
+    /// Even, 2..=254
+    pub prescale: u8,
+    /// 1..=256, SCR + 1
+    pub postdiv: u16,
+}
+
+impl SpiDividers {
+    /// The baud rate from `freq_in`, rounded down.
+    pub const fn baudrate(&self, freq_in: Hertz) -> Hertz {
+        Hertz(freq_in.0 / (self.prescale as u32 * self.postdiv as u32))
+    }
+}
+
+/// The SPI dividers for the fastest baud rate not above `baudrate`, the same
+/// search as pico-sdk's spi_set_baudrate(): the smallest prescale that gets
+/// below 256 * `baudrate`, then the smallest post divider. The slowest
+/// possible if out of range.
+pub const fn spi(freq_in: Hertz, baudrate: Hertz) -> SpiDividers {
+    let freq_in = freq_in.0 as u64;
+    let baudrate = baudrate.0 as u64;
+    let mut prescale = 2;
+    while prescale < 254 && freq_in >= (prescale + 2) * 256 * baudrate {
+        prescale += 2;
+    }
+    let mut postdiv = 256;
+    while postdiv > 1 && freq_in / (prescale * (postdiv - 1)) <= baudrate {
+        postdiv -= 1;
+    }
+    SpiDividers {
+        prescale: prescale as u8,
+        postdiv: postdiv as u16,
+    }
+}
+
+const fn clamp(value: u64, min: u64, max: u64) -> u64 {
+    if value < min {
+        min
+    } else if value > max {
+        max
+    } else {
+        value
+    }
+}
+
+// Known good settings, checked at compile time
+const _: () = {
+    const fn same(a: Option<Pll>, vco: u32, post_div1: u8, post_div2: u8) -> bool {
+        match a {
+            Some(pll) => {
+                pll.vco.0 == vco && pll.post_div1 == post_div1 && pll.post_div2 == post_div2
+            }
+            None => false,
+        }
+    }
+    let xosc = Hertz::mhz(12);
+    assert!(same(pll(xosc, Hertz::mhz(125)), 1_500_000_000, 6, 2));
+    assert!(same(pll(xosc, Hertz::mhz(133)), 1_596_000_000, 6, 2));
+    assert!(same(pll(xosc, Hertz::mhz(48)), 1_440_000_000, 6, 5));
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLK_PERI: Hertz = Hertz::mhz(125);
+    const XOSC: Hertz = Hertz::mhz(12);
+
+    #[test]
+    fn pll_vcocalc() {
+        // vcocalc.py's highest-VCO answers, exact for all of these
+        let table = [
+            (48, 1440, 6, 5),
+            (100, 1500, 5, 3),
+            (120, 1440, 6, 2),
+            (125, 1500, 6, 2),
+            (133, 1596, 6, 2),
+            (150, 1500, 5, 2),
+            (250, 1500, 6, 1),
+        ];
+        for &(mhz, vco, post_div1, post_div2) in &table {
+            let pll = pll(XOSC, Hertz::mhz(mhz)).unwrap();
+            assert_eq!(
+                pll,
+                Pll::new(Hertz::mhz(vco), post_div1, post_div2),
+                "{} MHz",
+                mhz
+            );
+            assert_eq!(pll.freq(), Hertz::mhz(mhz));
+            assert_eq!(pll.validate(XOSC), Ok(()));
+        }
+    }
+
+    #[test]
+    fn pll_out_of_range() {
+        // closest reachable: slowest and fastest
+        let slow = pll(XOSC, Hertz(1)).unwrap();
+        assert_eq!(slow, Pll::new(Hertz::mhz(408), 7, 7));
+        let fast = pll(XOSC, Hertz::mhz(2000)).unwrap();
+        assert_eq!(fast, Pll::new(Hertz::mhz(1596), 1, 1));
+        // no fbdiv in 16..=320 puts the VCO in range
+        assert_eq!(pll(Hertz::mhz(200), Hertz::mhz(125)), None);
+    }
+
+    #[test]
+    fn uart_like_pico_sdk() {
+        // uart_set_baudrate(): 8 * clk_peri / baud in 1/128, fbrd rounded
+        let d = uart(CLK_PERI, Hertz(115_200));
+        assert_eq!(d, UartDividers { ibrd: 67, fbrd: 52 });
+        assert_eq!(d.baudrate(CLK_PERI), Hertz(115_207));
+
+        let d = uart(CLK_PERI, Hertz(9600));
+        assert_eq!(
+            d,
+            UartDividers {
+                ibrd: 813,
+                fbrd: 51
+            }
+        );
+        assert_eq!(d.baudrate(CLK_PERI), Hertz(9600));
+
+        // ibrd == 0: the fastest there is
+        let fastest = UartDividers { ibrd: 1, fbrd: 0 };
+        assert_eq!(uart(CLK_PERI, Hertz::mhz(10)), fastest);
+        // ibrd >= 65535: the slowest, baud rate 0 too
+        let slowest = UartDividers {
+            ibrd: 65535,
+            fbrd: 0,
+        };
+        assert_eq!(uart(CLK_PERI, Hertz(100)), slowest);
+        assert_eq!(uart(CLK_PERI, Hertz(0)), slowest);
+    }
+
+    #[test]
+    fn spi_like_pico_sdk() {
+        let cases = [
+            (1_000_000, 2, 63, 992_063),
+            (4_000_000, 2, 16, 3_906_250),
+            (62_500_000, 2, 1, 62_500_000),
+            (400_000, 2, 157, 398_089),
+        ];
+        for &(baud, prescale, postdiv, actual) in &cases {
+            let d = spi(CLK_PERI, Hertz(baud));
+            assert_eq!(d, SpiDividers { prescale, postdiv }, "{} Hz", baud);
+            assert_eq!(d.baudrate(CLK_PERI), Hertz(actual));
+        }
+        // too slow to reach, and 0: the slowest
+        let slowest = SpiDividers {
+            prescale: 254,
+            postdiv: 256,
+        };
+        assert_eq!(spi(CLK_PERI, Hertz(1000)), slowest);
+        assert_eq!(spi(CLK_PERI, Hertz(0)), slowest);
+    }
+
+    #[test]
+    fn pwm_freqs() {
+        let d = pwm(CLK_PERI, Hertz::khz(1));
+        assert_eq!(
+            d,
+            PwmDividers {
+                int: 1,
+                frac: 15,
+                top: 64515
+            }
+        );
+        assert_eq!(d.freq(CLK_PERI), Hertz::khz(1));
+
+        let d = pwm(CLK_PERI, Hertz(50));
+        assert_eq!(
+            d,
+            PwmDividers {
+                int: 38,
+                frac: 3,
+                top: 65465
+            }
+        );
+        assert_eq!(d.freq(CLK_PERI), Hertz(50));
+
+        // below the slowest, and 0: the largest divider and wrap
+        let slowest = PwmDividers {
+            int: 255,
+            frac: 15,
+            top: 65535,
+        };
+        assert_eq!(pwm(CLK_PERI, Hertz(1)), slowest);
+        assert_eq!(pwm(CLK_PERI, Hertz(0)), slowest);
+        assert_eq!(slowest.freq(CLK_PERI), Hertz(7));
+        // above the fastest: undivided, wrapping every cycle
+        let fastest = pwm(CLK_PERI, Hertz::mhz(200));
+        assert_eq!(
+            fastest,
+            PwmDividers {
+                int: 1,
+                frac: 0,
+                top: 0
+            }
+        );
+        assert_eq!(fastest.freq(CLK_PERI), CLK_PERI);
+    }
+}
@@ -0,0 +1,228 @@
+//! The onboard QSPI flash, past the program image: erase, program and read.
+//!
+//! ```ignore
+//! let mut flash = Flash::new();
+//! flash.erase(0, SECTOR_SIZE)?;
+//! flash.write(0, b"hello")?;
+//! let mut buf = [0; 5];
+//! flash.read(0, &mut buf)?;
+//! ```
+//!
+//! Offsets are relative to the start of the region, `Flash::new()` starts at
+//! the first sector past the image. Erasing and programming turn XIP off, so
+//! interrupts are disabled meanwhile, and the other core is paused with
+//! `multicore::lockout()` if it called `multicore::lockout_victim_init()`.
+//! Don't run code from flash on it otherwise.
+//!
+//! Add `hardware_flash` to the app's `target_link_libraries`. Implements
+//! embedded-storage's `ReadNorFlash`, `NorFlash` and `MultiwriteNorFlash`.
+
+use core::ptr;
+
+use embedded_storage::nor_flash::{
+    check_erase, check_read, check_write, ErrorType, MultiwriteNorFlash, NorFlash, NorFlashError,
+    NorFlashErrorKind, ReadNorFlash,
+};
+use rpi_pico_sdk_sys::*;
+
+use crate::multicore;
+
+/// Programmed at once, smaller writes are padded with 0xff.
+pub const PAGE_SIZE: u32 = FLASH_PAGE_SIZE;
+/// Smallest erase.
+pub const SECTOR_SIZE: u32 = FLASH_SECTOR_SIZE;
+/// The 2 MB of the Pico board.
+pub const FLASH_SIZE: u32 = PICO_FLASH_SIZE_BYTES;
+
+extern "C" {
+    /// From the linker script, pico-sdk's or rpi-pico-rt's
+    static __flash_binary_end: u8;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Not on a sector boundary, for a region or an erase.
+    NotAligned,
+    /// Past the end of the region, or of the flash.
+    OutOfBounds,
+    /// The region overlaps the program image.
+    Image,
+}
+
+impl NorFlashError for Error {
+    fn kind(&self) -> NorFlashErrorKind {
+        match self {
+            Error::NotAligned => NorFlashErrorKind::NotAligned,
+            Error::OutOfBounds => NorFlashErrorKind::OutOfBounds,
+            Error::Image => NorFlashErrorKind::Other,
+        }
+    }
+}
+
+impl From<NorFlashErrorKind> for Error {
+    fn from(kind: NorFlashErrorKind) -> Self {
+        match kind {
+            NorFlashErrorKind::NotAligned => Error::NotAligned,
+            _ => Error::OutOfBounds,
+        }
+    }
+}
+
+/// Offset in flash of the first sector past the program image.
+pub fn image_end() -> u32 {
+    let end = unsafe { &__flash_binary_end as *const u8 as u32 } - XIP_BASE;
+    (end + SECTOR_SIZE - 1) & !(SECTOR_SIZE - 1)
+}
+
+/// A region of the flash, sectors past the program image.
+pub struct Flash {
+    /// Offset in flash
+    start: u32,
+    len: u32,
+}
+
+impl Flash {
+    /// Everything past the program image.
+    pub fn new() -> Self {
+        let start = image_end();
+        Flash {
+            start,
+            len: FLASH_SIZE - start,
+        }
+    }
+
+    /// `len` bytes at `start`, both in bytes from the start of flash and on
+    /// sector boundaries. E.g. the last two sectors for settings, to keep
+    /// them across program updates.
+    pub fn region(start: u32, len: u32) -> Result<Self, Error> {
+        if !start.is_multiple_of(SECTOR_SIZE) || !len.is_multiple_of(SECTOR_SIZE) {
+            return Err(Error::NotAligned);
+        }
+        if start.checked_add(len).is_none_or(|end| end > FLASH_SIZE) {
+            return Err(Error::OutOfBounds);
+        }
+        if start < image_end() {
+            return Err(Error::Image);
+        }
+        Ok(Flash { start, len })
+    }
+
+    /// Offset in flash of the region.
+    pub fn start(&self) -> u32 {
+        self.start
+    }
+
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Read through XIP, no need to turn it off.
+    pub fn read(&self, offset: u32, bytes: &mut [u8]) -> Result<(), Error> {
+        check_read(self, offset, bytes.len())?;
+        unsafe {
+            let src = (XIP_BASE + self.start + offset) as *const u8;
+            ptr::copy_nonoverlapping(src, bytes.as_mut_ptr(), bytes.len());
+        }
+        Ok(())
+    }
+
+    /// Erase `from..to`, to 0xff. Both on sector boundaries.
+    pub fn erase(&mut self, from: u32, to: u32) -> Result<(), Error> {
+        check_erase(self, from, to)?;
+        if from < to {
+            let offset = self.start + from;
+            without_xip(|| unsafe { flash_range_erase(offset, (to - from) as usize) });
+        }
+        Ok(())
+    }
+
+    /// Program `bytes` at `offset`, anywhere. Programming only clears bits,
+    /// erase first unless writing over 0xff.
+    ///
+    /// Goes through a page buffer on the stack: the data may live in flash.
+    pub fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Error> {
+        check_write(self, offset, bytes.len())?;
+        let mut offset = self.start + offset;
+        let mut bytes = bytes;
+        while !bytes.is_empty() {
+            let page = offset & !(PAGE_SIZE - 1);
+            let at = (offset - page) as usize;
+            let n = bytes.len().min(PAGE_SIZE as usize - at);
+
+            // 0xff leaves the bits around the data as they are
+            let mut buf = [0xff; PAGE_SIZE as usize];
+            buf[at..at + n].copy_from_slice(&bytes[..n]);
+            without_xip(|| unsafe { flash_range_program(page, buf.as_ptr(), buf.len()) });
+
+            offset += n as u32;
+            bytes = &bytes[n..];
+        }
+        Ok(())
+    }
+}
+
+impl Default for Flash {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The flash chip's 64-bit unique ID, also a unique ID of the board.
+pub fn unique_id() -> [u8; FLASH_UNIQUE_ID_SIZE_BYTES] {
+    let mut id = [0; FLASH_UNIQUE_ID_SIZE_BYTES];
+    without_xip(|| unsafe { flash_get_unique_id(id.as_mut_ptr()) });
+    id
+}
+
+/// Run `f`, one of the hardware_flash functions, with nothing else touching
+/// flash: interrupts off, the other core locked out if it can be.
+fn without_xip<R>(f: impl FnOnce() -> R) -> R {
+    let other = multicore::core_id() ^ 1;
+    let _lockout = if multicore::is_lockout_victim(other) {
+        Some(multicore::lockout())
+    } else {
+        None
+    };
+    unsafe {
+        let status = save_and_disable_interrupts();
+        let r = f();
+        restore_interrupts(status);
+        r
+    }
+}
+
+impl ErrorType for Flash {
+    type Error = Error;
+}
+
+impl ReadNorFlash for Flash {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Error> {
+        Flash::read(self, offset, bytes)
+    }
+
+    fn capacity(&self) -> usize {
+        self.len as usize
+    }
+}
+
+impl NorFlash for Flash {
+    const WRITE_SIZE: usize = 1;
+    const ERASE_SIZE: usize = SECTOR_SIZE as usize;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), Error> {
+        Flash::erase(self, from, to)
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Error> {
+        Flash::write(self, offset, bytes)
+    }
+}
+
+// bits can be cleared again without an erase
+impl MultiwriteNorFlash for Flash {}
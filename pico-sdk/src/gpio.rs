@@ -174,3 +174,53 @@ impl<MODE> InputPin for Gpio<Input<MODE>> {
         Ok(unsafe { gpio_get(self.pin) == false })
     }
 }
+
+/// A push button to ground on a pulled up input, debounced: the state only
+/// changes once the pin reads the same for `samples` updates in a row.
+pub struct Button {
+    pin: Gpio<Input<PullUp>>,
+    pressed: bool,
+    count: u8,
+    samples: u8,
+}
+
+impl Button {
+    /// Debounced over 5 updates, 5 ms when updated every millisecond.
+    pub fn new(pin: Gpio<Input<PullUp>>) -> Self {
+        Self::with_samples(pin, 5)
+    }
+
+    pub fn with_samples(pin: Gpio<Input<PullUp>>, samples: u8) -> Self {
+        Button {
+            pin,
+            pressed: false,
+            count: 0,
+            samples: samples.max(1),
+        }
+    }
+
+    /// Sample the pin, at a steady pace. `Some(pressed)` when the debounced
+    /// state changes.
+    pub fn update(&mut self) -> Option<bool> {
+        let low = unsafe { !gpio_get(self.pin.pin) };
+        if low == self.pressed {
+            self.count = 0;
+            return None;
+        }
+        self.count += 1;
+        if self.count < self.samples {
+            return None;
+        }
+        self.count = 0;
+        self.pressed = low;
+        Some(low)
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    pub fn free(self) -> Gpio<Input<PullUp>> {
+        self.pin
+    }
+}
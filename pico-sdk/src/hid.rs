@@ -0,0 +1,797 @@
+//! USB HID: report descriptors built at compile time, keyboard, mouse and
+//! gamepad reports, and a `usb-device` class sending them.
+//!
+//! ```ignore
+//! let bus = UsbBusAllocator::new(UsbBus::new());
+//! let mut keyboard = HidClass::keyboard(&bus);
+//! let mut dev = UsbDeviceBuilder::new(&bus, UsbVidPid(0x16c0, 0x27db)).build();
+//!
+//! let mut report = KeyboardReport::default();
+//! report.press(keycode::A);
+//! loop {
+//!     if dev.poll(&mut [&mut keyboard]) { /* ... */ }
+//!     let _ = keyboard.write_report(&report.to_bytes());
+//! }
+//! ```
+//!
+//! Other devices bring their own descriptor:
+//!
+//! ```ignore
+//! static KNOB: ReportDescriptor<32> = ReportDescriptor::new()
+//!     .usage_page(usage_page::GENERIC_DESKTOP)
+//!     .usage(usage::DIAL)
+//!     .collection(Collection::Application)
+//!     .logical_minimum(-127)
+//!     .logical_maximum(127)
+//!     .report_size(8)
+//!     .report_count(1)
+//!     .input(VARIABLE | RELATIVE)
+//!     .end_collection();
+//!
+//! let mut knob = HidClass::new(&bus, KNOB.as_bytes(), 10);
+//! ```
+
+use usb_device::class_prelude::*;
+use usb_device::Result;
+
+/// Input, Output and Feature item flags, or-ed together. 0 is data, array, absolute.
+pub const CONSTANT: u8 = 0x01;
+pub const VARIABLE: u8 = 0x02;
+pub const RELATIVE: u8 = 0x04;
+
+/// Usage pages, from the HID Usage Tables
+pub mod usage_page {
+    pub const GENERIC_DESKTOP: u16 = 0x01;
+    pub const KEYBOARD: u16 = 0x07;
+    pub const LEDS: u16 = 0x08;
+    pub const BUTTON: u16 = 0x09;
+    pub const CONSUMER: u16 = 0x0c;
+}
+
+/// Usages of the Generic Desktop page
+pub mod usage {
+    pub const POINTER: u16 = 0x01;
+    pub const MOUSE: u16 = 0x02;
+    pub const JOYSTICK: u16 = 0x04;
+    pub const GAMEPAD: u16 = 0x05;
+    pub const KEYBOARD: u16 = 0x06;
+    pub const X: u16 = 0x30;
+    pub const Y: u16 = 0x31;
+    pub const Z: u16 = 0x32;
+    pub const RX: u16 = 0x33;
+    pub const RY: u16 = 0x34;
+    pub const RZ: u16 = 0x35;
+    pub const DIAL: u16 = 0x37;
+    pub const WHEEL: u16 = 0x38;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collection {
+    Physical = 0,
+    Application = 1,
+    Logical = 2,
+}
+
+/// A report descriptor of at most `N` bytes, items in short form with the
+/// fewest data bytes. Built in a `const` or `static`, overflowing `N` fails
+/// to compile.
+#[derive(Clone, Copy)]
+pub struct ReportDescriptor<const N: usize> {
+    buf: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> ReportDescriptor<N> {
+    pub const fn new() -> Self {
+        ReportDescriptor {
+            buf: [0; N],
+            len: 0,
+        }
+    }
+
+    /// `prefix` is the tag and type, the size is added
+    const fn item(mut self, prefix: u8, value: u32, size: usize) -> Self {
+        let size_code = match size {
+            0 => 0,
+            1 => 1,
+            2 => 2,
+            _ => 3,
+        };
+        self.buf[self.len] = prefix | size_code;
+        let mut i = 0;
+        while i < size {
+            self.buf[self.len + 1 + i] = (value >> (8 * i)) as u8;
+            i += 1;
+        }
+        self.len += 1 + size;
+        self
+    }
+
+    const fn unsigned(self, prefix: u8, value: u32) -> Self {
+        let size = if value <= 0xff {
+            1
+        } else if value <= 0xffff {
+            2
+        } else {
+            4
+        };
+        self.item(prefix, value, size)
+    }
+
+    const fn signed(self, prefix: u8, value: i32) -> Self {
+        let size = if value >= i8::MIN as i32 && value <= i8::MAX as i32 {
+            1
+        } else if value >= i16::MIN as i32 && value <= i16::MAX as i32 {
+            2
+        } else {
+            4
+        };
+        self.item(prefix, value as u32, size)
+    }
+
+    pub const fn usage_page(self, page: u16) -> Self {
+        self.unsigned(0x04, page as u32)
+    }
+
+    pub const fn usage(self, usage: u16) -> Self {
+        self.unsigned(0x08, usage as u32)
+    }
+
+    pub const fn usage_minimum(self, usage: u16) -> Self {
+        self.unsigned(0x18, usage as u32)
+    }
+
+    pub const fn usage_maximum(self, usage: u16) -> Self {
+        self.unsigned(0x28, usage as u32)
+    }
+
+    pub const fn logical_minimum(self, value: i32) -> Self {
+        self.signed(0x14, value)
+    }
+
+    pub const fn logical_maximum(self, value: i32) -> Self {
+        self.signed(0x24, value)
+    }
+
+    /// Bits per field.
+    pub const fn report_size(self, bits: u8) -> Self {
+        self.unsigned(0x74, bits as u32)
+    }
+
+    /// Fields in the next main item.
+    pub const fn report_count(self, count: u8) -> Self {
+        self.unsigned(0x94, count as u32)
+    }
+
+    /// Prefixes every report after it with `id`, needed when there's more
+    /// than one report of a kind.
+    pub const fn report_id(self, id: u8) -> Self {
+        self.unsigned(0x84, id as u32)
+    }
+
+    pub const fn collection(self, collection: Collection) -> Self {
+        self.unsigned(0xa0, collection as u32)
+    }
+
+    pub const fn end_collection(self) -> Self {
+        self.item(0xc0, 0, 0)
+    }
+
+    pub const fn input(self, flags: u8) -> Self {
+        self.unsigned(0x80, flags as u32)
+    }
+
+    pub const fn output(self, flags: u8) -> Self {
+        self.unsigned(0x90, flags as u32)
+    }
+
+    pub const fn feature(self, flags: u8) -> Self {
+        self.unsigned(0xb0, flags as u32)
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+impl<const N: usize> Default for ReportDescriptor<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The boot keyboard: modifiers, a reserved byte and 6 keys in, 5 LEDs out.
+pub static KEYBOARD_DESCRIPTOR: ReportDescriptor<64> = ReportDescriptor::new()
+    .usage_page(usage_page::GENERIC_DESKTOP)
+    .usage(usage::KEYBOARD)
+    .collection(Collection::Application)
+    // modifiers, a bit per key
+    .usage_page(usage_page::KEYBOARD)
+    .usage_minimum(keycode::LEFT_CTRL as u16)
+    .usage_maximum(keycode::RIGHT_GUI as u16)
+    .logical_minimum(0)
+    .logical_maximum(1)
+    .report_size(1)
+    .report_count(8)
+    .input(VARIABLE)
+    // reserved
+    .report_size(8)
+    .report_count(1)
+    .input(CONSTANT)
+    // LEDs
+    .usage_page(usage_page::LEDS)
+    .usage_minimum(1)
+    .usage_maximum(5)
+    .report_size(1)
+    .report_count(5)
+    .output(VARIABLE)
+    .report_size(3)
+    .report_count(1)
+    .output(CONSTANT)
+    // keys
+    .usage_page(usage_page::KEYBOARD)
+    .usage_minimum(0)
+    .usage_maximum(0xff)
+    .logical_maximum(0xff)
+    .report_size(8)
+    .report_count(6)
+    .input(0)
+    .end_collection();
+
+/// The boot mouse plus a wheel: 3 buttons and relative X, Y and wheel.
+pub static MOUSE_DESCRIPTOR: ReportDescriptor<64> = ReportDescriptor::new()
+    .usage_page(usage_page::GENERIC_DESKTOP)
+    .usage(usage::MOUSE)
+    .collection(Collection::Application)
+    .usage(usage::POINTER)
+    .collection(Collection::Physical)
+    .usage_page(usage_page::BUTTON)
+    .usage_minimum(1)
+    .usage_maximum(3)
+    .logical_minimum(0)
+    .logical_maximum(1)
+    .report_size(1)
+    .report_count(3)
+    .input(VARIABLE)
+    .report_size(5)
+    .report_count(1)
+    .input(CONSTANT)
+    .usage_page(usage_page::GENERIC_DESKTOP)
+    .usage(usage::X)
+    .usage(usage::Y)
+    .usage(usage::WHEEL)
+    .logical_minimum(-127)
+    .logical_maximum(127)
+    .report_size(8)
+    .report_count(3)
+    .input(VARIABLE | RELATIVE)
+    .end_collection()
+    .end_collection();
+
+/// 16 buttons and 4 axes, X and Y then RX and RY.
+pub static GAMEPAD_DESCRIPTOR: ReportDescriptor<64> = ReportDescriptor::new()
+    .usage_page(usage_page::GENERIC_DESKTOP)
+    .usage(usage::GAMEPAD)
+    .collection(Collection::Application)
+    .usage_page(usage_page::BUTTON)
+    .usage_minimum(1)
+    .usage_maximum(16)
+    .logical_minimum(0)
+    .logical_maximum(1)
+    .report_size(1)
+    .report_count(16)
+    .input(VARIABLE)
+    .usage_page(usage_page::GENERIC_DESKTOP)
+    .usage(usage::X)
+    .usage(usage::Y)
+    .usage(usage::RX)
+    .usage(usage::RY)
+    .logical_minimum(-127)
+    .logical_maximum(127)
+    .report_size(8)
+    .report_count(4)
+    .input(VARIABLE)
+    .end_collection();
+
+/// Usages of the Keyboard page, what `KeyboardReport` takes.
+pub mod keycode {
+    pub const A: u8 = 0x04;
+    pub const B: u8 = 0x05;
+    pub const C: u8 = 0x06;
+    pub const D: u8 = 0x07;
+    pub const E: u8 = 0x08;
+    pub const F: u8 = 0x09;
+    pub const G: u8 = 0x0a;
+    pub const H: u8 = 0x0b;
+    pub const I: u8 = 0x0c;
+    pub const J: u8 = 0x0d;
+    pub const K: u8 = 0x0e;
+    pub const L: u8 = 0x0f;
+    pub const M: u8 = 0x10;
+    pub const N: u8 = 0x11;
+    pub const O: u8 = 0x12;
+    pub const P: u8 = 0x13;
+    pub const Q: u8 = 0x14;
+    pub const R: u8 = 0x15;
+    pub const S: u8 = 0x16;
+    pub const T: u8 = 0x17;
+    pub const U: u8 = 0x18;
+    pub const V: u8 = 0x19;
+    pub const W: u8 = 0x1a;
+    pub const X: u8 = 0x1b;
+    pub const Y: u8 = 0x1c;
+    pub const Z: u8 = 0x1d;
+    /// 1 to 9, then 0
+    pub const N1: u8 = 0x1e;
+    pub const N0: u8 = 0x27;
+    pub const ENTER: u8 = 0x28;
+    pub const ESCAPE: u8 = 0x29;
+    pub const BACKSPACE: u8 = 0x2a;
+    pub const TAB: u8 = 0x2b;
+    pub const SPACE: u8 = 0x2c;
+    /// F1 to F12 follow
+    pub const F1: u8 = 0x3a;
+    pub const F12: u8 = 0x45;
+    pub const RIGHT: u8 = 0x4f;
+    pub const LEFT: u8 = 0x50;
+    pub const DOWN: u8 = 0x51;
+    pub const UP: u8 = 0x52;
+
+    /// Modifiers, a bit each in `KeyboardReport::modifiers`
+    pub const LEFT_CTRL: u8 = 0xe0;
+    pub const LEFT_SHIFT: u8 = 0xe1;
+    pub const LEFT_ALT: u8 = 0xe2;
+    pub const LEFT_GUI: u8 = 0xe3;
+    pub const RIGHT_CTRL: u8 = 0xe4;
+    pub const RIGHT_SHIFT: u8 = 0xe5;
+    pub const RIGHT_ALT: u8 = 0xe6;
+    pub const RIGHT_GUI: u8 = 0xe7;
+
+    /// In every slot when more than 6 keys are down
+    pub const ERROR_ROLL_OVER: u8 = 0x01;
+}
+
+/// What `KEYBOARD_DESCRIPTOR` sends, up to 6 keys down besides the modifiers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyboardReport {
+    pub modifiers: u8,
+    pub keys: [u8; 6],
+}
+
+impl KeyboardReport {
+    /// A key or a modifier down. A 7th key rolls over: every slot reads
+    /// `ERROR_ROLL_OVER` until one is released.
+    pub fn press(&mut self, key: u8) {
+        if let Some(bit) = Self::modifier_bit(key) {
+            self.modifiers |= bit;
+        } else if !self.keys.contains(&key) {
+            match self.keys.iter_mut().find(|k| **k == 0) {
+                Some(slot) => *slot = key,
+                None => self.keys = [keycode::ERROR_ROLL_OVER; 6],
+            }
+        }
+    }
+
+    pub fn release(&mut self, key: u8) {
+        if let Some(bit) = Self::modifier_bit(key) {
+            self.modifiers &= !bit;
+        } else if self.keys[0] == keycode::ERROR_ROLL_OVER {
+            // the keys held are lost, start over
+            self.keys = [0; 6];
+        } else if let Some(slot) = self.keys.iter_mut().find(|k| **k == key) {
+            *slot = 0;
+        }
+    }
+
+    fn modifier_bit(key: u8) -> Option<u8> {
+        match key {
+            keycode::LEFT_CTRL..=keycode::RIGHT_GUI => Some(1 << (key - keycode::LEFT_CTRL)),
+            _ => None,
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let k = self.keys;
+        [self.modifiers, 0, k[0], k[1], k[2], k[3], k[4], k[5]]
+    }
+}
+
+/// What `MOUSE_DESCRIPTOR` sends, movement since the last report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MouseReport {
+    pub buttons: u8,
+    pub x: i8,
+    pub y: i8,
+    pub wheel: i8,
+}
+
+impl MouseReport {
+    pub const LEFT: u8 = 0x01;
+    pub const RIGHT: u8 = 0x02;
+    pub const MIDDLE: u8 = 0x04;
+
+    pub fn to_bytes(&self) -> [u8; 4] {
+        [self.buttons, self.x as u8, self.y as u8, self.wheel as u8]
+    }
+}
+
+/// What `GAMEPAD_DESCRIPTOR` sends, button 1 in bit 0.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GamepadReport {
+    pub buttons: u16,
+    pub x: i8,
+    pub y: i8,
+    pub rx: i8,
+    pub ry: i8,
+}
+
+impl GamepadReport {
+    /// Button `n`, 1..=16, down or up. Other buttons are ignored.
+    pub fn set_button(&mut self, n: u8, pressed: bool) {
+        if !(1..=16).contains(&n) {
+            return;
+        }
+        let bit = 1 << (n - 1);
+        if pressed {
+            self.buttons |= bit;
+        } else {
+            self.buttons &= !bit;
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 6] {
+        let [b0, b1] = self.buttons.to_le_bytes();
+        [
+            b0,
+            b1,
+            self.x as u8,
+            self.y as u8,
+            self.rx as u8,
+            self.ry as u8,
+        ]
+    }
+}
+
+const USB_CLASS_HID: u8 = 0x03;
+const SUBCLASS_BOOT: u8 = 0x01;
+
+const DESC_HID: u8 = 0x21;
+const DESC_REPORT: u8 = 0x22;
+
+const GET_REPORT: u8 = 0x01;
+const GET_IDLE: u8 = 0x02;
+const GET_PROTOCOL: u8 = 0x03;
+const SET_REPORT: u8 = 0x09;
+const SET_IDLE: u8 = 0x0a;
+const SET_PROTOCOL: u8 = 0x0b;
+
+/// Biggest report kept for GET_REPORT and from SET_REPORT
+const MAX_REPORT: usize = 16;
+
+/// Boot protocol of the interface, for BIOSes and such. The reports must
+/// then start as the boot ones do, like `KeyboardReport` and `MouseReport`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boot {
+    None = 0,
+    Keyboard = 1,
+    Mouse = 2,
+}
+
+/// A HID interface with an interrupt IN endpoint. Output reports, like the
+/// keyboard LEDs, come through SET_REPORT.
+pub struct HidClass<'a, B: UsbBus> {
+    interface: InterfaceNumber,
+    ep_in: EndpointIn<'a, B>,
+    descriptor: &'static [u8],
+    boot: Boot,
+    idle: u8,
+    /// 0 boot, 1 report
+    protocol: u8,
+    report: [u8; MAX_REPORT],
+    report_len: usize,
+    output: [u8; MAX_REPORT],
+    output_len: usize,
+}
+
+impl<'a, B: UsbBus> HidClass<'a, B> {
+    /// Reports of up to 64 bytes, polled every `interval_ms` by the host.
+    pub fn new(alloc: &'a UsbBusAllocator<B>, descriptor: &'static [u8], interval_ms: u8) -> Self {
+        HidClass {
+            interface: alloc.interface(),
+            ep_in: alloc.interrupt(64, interval_ms),
+            descriptor,
+            boot: Boot::None,
+            idle: 0,
+            protocol: 1,
+            report: [0; MAX_REPORT],
+            report_len: 0,
+            output: [0; MAX_REPORT],
+            output_len: 0,
+        }
+    }
+
+    /// `KEYBOARD_DESCRIPTOR`, with the boot protocol, send `KeyboardReport`s.
+    pub fn keyboard(alloc: &'a UsbBusAllocator<B>) -> Self {
+        Self::new(alloc, KEYBOARD_DESCRIPTOR.as_bytes(), 10).boot(Boot::Keyboard)
+    }
+
+    /// `MOUSE_DESCRIPTOR`, with the boot protocol, send `MouseReport`s.
+    pub fn mouse(alloc: &'a UsbBusAllocator<B>) -> Self {
+        Self::new(alloc, MOUSE_DESCRIPTOR.as_bytes(), 10).boot(Boot::Mouse)
+    }
+
+    /// `GAMEPAD_DESCRIPTOR`, send `GamepadReport`s.
+    pub fn gamepad(alloc: &'a UsbBusAllocator<B>) -> Self {
+        Self::new(alloc, GAMEPAD_DESCRIPTOR.as_bytes(), 10)
+    }
+
+    pub fn boot(mut self, boot: Boot) -> Self {
+        self.boot = boot;
+        self
+    }
+
+    /// Queue an input report, `Err(UsbError::WouldBlock)` while the previous
+    /// one hasn't gone out yet.
+    pub fn write_report(&mut self, report: &[u8]) -> Result<usize> {
+        let n = self.ep_in.write(report)?;
+        self.report_len = n.min(MAX_REPORT);
+        self.report[..self.report_len].copy_from_slice(&report[..self.report_len]);
+        Ok(n)
+    }
+
+    /// The last output report from the host, e.g. the keyboard LEDs: num lock
+    /// in bit 0, caps lock in bit 1, scroll lock in bit 2.
+    pub fn output_report(&self) -> &[u8] {
+        &self.output[..self.output_len]
+    }
+
+    /// Whether the host switched to the boot protocol.
+    pub fn is_boot_protocol(&self) -> bool {
+        self.protocol == 0
+    }
+
+    fn hid_descriptor(&self) -> [u8; 7] {
+        let [lo, hi] = (self.descriptor.len() as u16).to_le_bytes();
+        // HID 1.11, no country, one report descriptor
+        [0x11, 0x01, 0x00, 0x01, DESC_REPORT, lo, hi]
+    }
+
+    fn is_ours(&self, req: &control::Request) -> bool {
+        req.recipient == control::Recipient::Interface
+            && req.index == u8::from(self.interface) as u16
+    }
+}
+
+impl<B: UsbBus> UsbClass<B> for HidClass<'_, B> {
+    fn get_configuration_descriptors(&self, writer: &mut DescriptorWriter) -> Result<()> {
+        let subclass = match self.boot {
+            Boot::None => 0,
+            _ => SUBCLASS_BOOT,
+        };
+        writer.interface(self.interface, USB_CLASS_HID, subclass, self.boot as u8)?;
+        writer.write(DESC_HID, &self.hid_descriptor())?;
+        writer.endpoint(&self.ep_in)
+    }
+
+    fn reset(&mut self) {
+        self.idle = 0;
+        self.protocol = 1;
+        self.output_len = 0;
+    }
+
+    fn control_in(&mut self, xfer: ControlIn<B>) {
+        let req = *xfer.request();
+        if !self.is_ours(&req) {
+            return;
+        }
+        match (req.request_type, req.request) {
+            (control::RequestType::Standard, control::Request::GET_DESCRIPTOR) => {
+                match (req.value >> 8) as u8 {
+                    DESC_REPORT => xfer.accept_with_static(self.descriptor).ok(),
+                    DESC_HID => xfer.accept_with(&self.hid_descriptor()).ok(),
+                    _ => xfer.reject().ok(),
+                };
+            }
+            (control::RequestType::Class, GET_REPORT) => {
+                xfer.accept_with(&self.report[..self.report_len]).ok();
+            }
+            (control::RequestType::Class, GET_IDLE) => {
+                xfer.accept_with(&[self.idle]).ok();
+            }
+            (control::RequestType::Class, GET_PROTOCOL) => {
+                xfer.accept_with(&[self.protocol]).ok();
+            }
+            _ => {}
+        }
+    }
+
+    fn control_out(&mut self, xfer: ControlOut<B>) {
+        let req = *xfer.request();
+        if !(self.is_ours(&req) && req.request_type == control::RequestType::Class) {
+            return;
+        }
+        match req.request {
+            SET_REPORT => {
+                let data = xfer.data();
+                self.output_len = data.len().min(MAX_REPORT);
+                self.output[..self.output_len].copy_from_slice(&data[..self.output_len]);
+                xfer.accept().ok();
+            }
+            SET_IDLE => {
+                // reports are only sent on change, the idle rate is just kept
+                self.idle = (req.value >> 8) as u8;
+                xfer.accept().ok();
+            }
+            SET_PROTOCOL => {
+                self.protocol = (req.value & 0xff) as u8;
+                xfer.accept().ok();
+            }
+            _ => {
+                xfer.reject().ok();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyboard_descriptor() {
+        #[rustfmt::skip]
+        let expected = [
+            0x05, 0x01, 0x09, 0x06, 0xa1, 0x01,
+            // modifiers
+            0x05, 0x07, 0x19, 0xe0, 0x29, 0xe7, 0x15, 0x00, 0x25, 0x01,
+            0x75, 0x01, 0x95, 0x08, 0x81, 0x02,
+            // reserved
+            0x75, 0x08, 0x95, 0x01, 0x81, 0x01,
+            // LEDs
+            0x05, 0x08, 0x19, 0x01, 0x29, 0x05, 0x75, 0x01, 0x95, 0x05, 0x91, 0x02,
+            0x75, 0x03, 0x95, 0x01, 0x91, 0x01,
+            // keys, 255 needs 2 bytes as a signed logical maximum
+            0x05, 0x07, 0x19, 0x00, 0x29, 0xff, 0x26, 0xff, 0x00,
+            0x75, 0x08, 0x95, 0x06, 0x81, 0x00,
+            0xc0,
+        ];
+        assert_eq!(KEYBOARD_DESCRIPTOR.as_bytes(), &expected[..]);
+    }
+
+    #[test]
+    fn mouse_descriptor() {
+        #[rustfmt::skip]
+        let expected = [
+            0x05, 0x01, 0x09, 0x02, 0xa1, 0x01, 0x09, 0x01, 0xa1, 0x00,
+            0x05, 0x09, 0x19, 0x01, 0x29, 0x03, 0x15, 0x00, 0x25, 0x01,
+            0x75, 0x01, 0x95, 0x03, 0x81, 0x02,
+            0x75, 0x05, 0x95, 0x01, 0x81, 0x01,
+            0x05, 0x01, 0x09, 0x30, 0x09, 0x31, 0x09, 0x38, 0x15, 0x81, 0x25, 0x7f,
+            0x75, 0x08, 0x95, 0x03, 0x81, 0x06,
+            0xc0, 0xc0,
+        ];
+        assert_eq!(MOUSE_DESCRIPTOR.as_bytes(), &expected[..]);
+    }
+
+    #[test]
+    fn gamepad_descriptor() {
+        #[rustfmt::skip]
+        let expected = [
+            0x05, 0x01, 0x09, 0x05, 0xa1, 0x01,
+            0x05, 0x09, 0x19, 0x01, 0x29, 0x10, 0x15, 0x00, 0x25, 0x01,
+            0x75, 0x01, 0x95, 0x10, 0x81, 0x02,
+            0x05, 0x01, 0x09, 0x30, 0x09, 0x31, 0x09, 0x33, 0x09, 0x34,
+            0x15, 0x81, 0x25, 0x7f, 0x75, 0x08, 0x95, 0x04, 0x81, 0x02,
+            0xc0,
+        ];
+        assert_eq!(GAMEPAD_DESCRIPTOR.as_bytes(), &expected[..]);
+    }
+
+    #[test]
+    fn item_sizes() {
+        const D: ReportDescriptor<32> = ReportDescriptor::new()
+            .usage_page(0xff00)
+            .logical_minimum(-128)
+            .logical_minimum(-129)
+            .logical_minimum(-32768)
+            .logical_maximum(32768)
+            .usage(0)
+            .feature(CONSTANT | VARIABLE)
+            .report_id(3);
+        #[rustfmt::skip]
+        let expected = [
+            0x06, 0x00, 0xff,
+            0x15, 0x80,
+            0x16, 0x7f, 0xff,
+            0x16, 0x00, 0x80,
+            0x27, 0x00, 0x80, 0x00, 0x00,
+            0x09, 0x00,
+            0xb1, 0x03,
+            0x85, 0x03,
+        ];
+        assert_eq!(D.as_bytes(), &expected[..]);
+    }
+
+    #[test]
+    fn modifiers() {
+        let mut report = KeyboardReport::default();
+        report.press(keycode::LEFT_SHIFT);
+        report.press(keycode::RIGHT_GUI);
+        report.press(keycode::A);
+        assert_eq!(report.modifiers, 0x82);
+        assert_eq!(report.to_bytes(), [0x82, 0, keycode::A, 0, 0, 0, 0, 0]);
+        report.release(keycode::LEFT_SHIFT);
+        report.release(keycode::LEFT_CTRL);
+        assert_eq!(report.to_bytes(), [0x80, 0, keycode::A, 0, 0, 0, 0, 0]);
+        report.release(keycode::RIGHT_GUI);
+        assert_eq!(report.modifiers, 0);
+    }
+
+    #[test]
+    fn six_key_rollover() {
+        let mut report = KeyboardReport::default();
+        for key in keycode::A..=keycode::F {
+            report.press(key);
+        }
+        // again, no duplicate slot
+        report.press(keycode::C);
+        assert_eq!(report.keys, [4, 5, 6, 7, 8, 9]);
+
+        report.press(keycode::LEFT_ALT);
+        report.press(keycode::G);
+        assert_eq!(report.keys, [keycode::ERROR_ROLL_OVER; 6]);
+        assert_eq!(report.modifiers, 0x04);
+        report.press(keycode::H);
+        assert_eq!(report.keys, [keycode::ERROR_ROLL_OVER; 6]);
+
+        report.release(keycode::A);
+        assert_eq!(report.keys, [0; 6]);
+        assert_eq!(report.modifiers, 0x04);
+    }
+
+    #[test]
+    fn release_unpressed() {
+        let mut report = KeyboardReport::default();
+        report.press(keycode::A);
+        report.press(keycode::B);
+        report.release(keycode::Z);
+        assert_eq!(report.keys, [keycode::A, keycode::B, 0, 0, 0, 0]);
+        report.release(keycode::A);
+        report.release(keycode::A);
+        assert_eq!(report.keys, [0, keycode::B, 0, 0, 0, 0]);
+        // the free slot is used again
+        report.press(keycode::C);
+        assert_eq!(report.keys, [keycode::C, keycode::B, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn gamepad_buttons() {
+        let mut report = GamepadReport::default();
+        report.set_button(0, true);
+        report.set_button(17, true);
+        report.set_button(255, true);
+        assert_eq!(report.buttons, 0);
+
+        report.set_button(1, true);
+        report.set_button(16, true);
+        assert_eq!(report.buttons, 0x8001);
+        report.set_button(1, false);
+        report.set_button(2, false);
+        assert_eq!(report.buttons, 0x8000);
+
+        report.x = -1;
+        report.ry = 127;
+        assert_eq!(report.to_bytes(), [0x00, 0x80, 0xff, 0, 0, 0x7f]);
+    }
+
+    #[test]
+    fn mouse_report() {
+        let report = MouseReport {
+            buttons: MouseReport::LEFT | MouseReport::MIDDLE,
+            x: -127,
+            y: 5,
+            wheel: -1,
+        };
+        assert_eq!(report.to_bytes(), [0x05, 0x81, 0x05, 0xff]);
+    }
+}
@@ -0,0 +1,799 @@
+//! The SIO interpolators: two per core, each with two lanes that shift, mask
+//! and add their accumulators in a single cycle.
+//!
+//! ```ignore
+//! let mut interp = Interp::interp0().unwrap();
+//! interp.set_lane(0, LaneConfig::new().shift(4).mask(0, 7));
+//! interp.set_base(0, 100);
+//! interp.set_accumulator(0, 0x1234);
+//! assert_eq!(interp.peek(0), 100 + 0x23);
+//! ```
+//!
+//! Each lane reads its accumulator (or the other lane's, `cross_input`),
+//! shifts it right, masks it and optionally sign-extends it, and adds its base:
+//! that's the lane result. The full result is `BASE2` plus both shifted and
+//! masked values. Reading a result with `pop` also writes the lane results back
+//! to the accumulators, which is how an interpolator walks through a table.
+//!
+//! INTERP0 lane 0 has blend mode, INTERP1 lane 0 clamp mode.
+//!
+//! The interpolators belong to the core: `Interp::interp0()` on core 1 is a
+//! different one than on core 0. An interrupt handler using one that the code
+//! it interrupted holds must `save()` and `restore()` it around its use.
+//!
+//! # Recipes
+//!
+//! [`lerp`] blends two values, `from + (to - from) * alpha / 256`, in blend
+//! mode, after [`init_lerp`].
+//!
+//! [`texture_walk`] steps a fixed point (u, v) coordinate through a texture of
+//! `1 << width_bits` by `1 << height_bits` texels, after [`init_texture`]:
+//! lane 0 masks the integer part of u, lane 1 that of v shifted into the row
+//! bits, and `pop_full()` returns their sum as the index while adding (du, dv).
+//!
+//! Both run on any [`Interpolator`], `Model` included, so they can be tried on
+//! the host.
+
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use rpi_pico_sdk_sys::*;
+
+/// Lane control register value, a const builder like pico-sdk's `interp_config`.
+///
+/// `new()` passes the accumulator through unchanged: no shift, full mask.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LaneConfig(u32);
+
+impl LaneConfig {
+    pub const fn new() -> Self {
+        LaneConfig(0).mask(0, 31)
+    }
+
+    /// From a raw CTRL_LANE value
+    pub const fn from_bits(bits: u32) -> Self {
+        LaneConfig(bits)
+    }
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    const fn field(self, lsb: u32, bits: u32, value: u32) -> Self {
+        LaneConfig((self.0 & !bits) | ((value << lsb) & bits))
+    }
+
+    const fn flag(self, lsb: u32, bits: u32, on: bool) -> Self {
+        self.field(lsb, bits, on as u32)
+    }
+
+    /// Logical right shift applied to the accumulator, 0 to 31
+    pub const fn shift(self, shift: u32) -> Self {
+        assert!(shift < 32, "shift out of range");
+        self.field(
+            SIO_INTERP0_CTRL_LANE0_SHIFT_LSB,
+            SIO_INTERP0_CTRL_LANE0_SHIFT_BITS,
+            shift,
+        )
+    }
+
+    /// Keep bits `lsb..=msb` of the shifted value
+    pub const fn mask(self, lsb: u32, msb: u32) -> Self {
+        assert!(lsb <= msb && msb < 32, "mask out of range");
+        self.field(
+            SIO_INTERP0_CTRL_LANE0_MASK_LSB_LSB,
+            SIO_INTERP0_CTRL_LANE0_MASK_LSB_BITS,
+            lsb,
+        )
+        .field(
+            SIO_INTERP0_CTRL_LANE0_MASK_MSB_LSB,
+            SIO_INTERP0_CTRL_LANE0_MASK_MSB_BITS,
+            msb,
+        )
+    }
+
+    /// Sign-extend the masked value from its MSB before adding the base. In
+    /// blend mode, lane 1's flag makes the blend signed, in clamp mode lane
+    /// 0's makes the bounds signed.
+    pub const fn signed(self, signed: bool) -> Self {
+        self.flag(
+            SIO_INTERP0_CTRL_LANE0_SIGNED_LSB,
+            SIO_INTERP0_CTRL_LANE0_SIGNED_BITS,
+            signed,
+        )
+    }
+
+    /// Take the other lane's accumulator as input
+    pub const fn cross_input(self, cross: bool) -> Self {
+        self.flag(
+            SIO_INTERP0_CTRL_LANE0_CROSS_INPUT_LSB,
+            SIO_INTERP0_CTRL_LANE0_CROSS_INPUT_BITS,
+            cross,
+        )
+    }
+
+    /// On pop, write the other lane's result to this lane's accumulator
+    pub const fn cross_result(self, cross: bool) -> Self {
+        self.flag(
+            SIO_INTERP0_CTRL_LANE0_CROSS_RESULT_LSB,
+            SIO_INTERP0_CTRL_LANE0_CROSS_RESULT_BITS,
+            cross,
+        )
+    }
+
+    /// Add the unshifted, unmasked input to the base for the lane result. The
+    /// full result still uses the shifted and masked value.
+    pub const fn add_raw(self, raw: bool) -> Self {
+        self.flag(
+            SIO_INTERP0_CTRL_LANE0_ADD_RAW_LSB,
+            SIO_INTERP0_CTRL_LANE0_ADD_RAW_BITS,
+            raw,
+        )
+    }
+
+    /// ORed into bits 29:28 of the lane result as read, e.g. to point into
+    /// SRAM (0b10) from a small offset. The accumulators don't see it.
+    pub const fn force_msb(self, bits: u32) -> Self {
+        assert!(bits < 4, "force_msb is 2 bits");
+        self.field(
+            SIO_INTERP0_CTRL_LANE0_FORCE_MSB_LSB,
+            SIO_INTERP0_CTRL_LANE0_FORCE_MSB_BITS,
+            bits,
+        )
+    }
+
+    /// Blend mode, INTERP0 lane 0 only: lane 1's result becomes a linear
+    /// interpolation from `BASE0` to `BASE1` by the low 8 bits of lane 1's
+    /// shifted and masked value; lane 0's result is just those 8 bits, and the
+    /// full result leaves out lane 1.
+    pub const fn blend(self, blend: bool) -> Self {
+        self.flag(
+            SIO_INTERP0_CTRL_LANE0_BLEND_LSB,
+            SIO_INTERP0_CTRL_LANE0_BLEND_BITS,
+            blend,
+        )
+    }
+
+    /// Clamp mode, INTERP1 lane 0 only: lane 0's result is its shifted and
+    /// masked value clamped to `BASE0..=BASE1`.
+    pub const fn clamp(self, clamp: bool) -> Self {
+        self.flag(
+            SIO_INTERP1_CTRL_LANE0_CLAMP_LSB,
+            SIO_INTERP1_CTRL_LANE0_CLAMP_BITS,
+            clamp,
+        )
+    }
+
+    const fn get(self, lsb: u32, bits: u32) -> u32 {
+        (self.0 & bits) >> lsb
+    }
+
+    fn is(self, bits: u32) -> bool {
+        self.0 & bits != 0
+    }
+
+    /// The input after shift and mask, sign-extended if `signed`
+    fn shift_mask(self, input: u32) -> u32 {
+        let shift = self.get(
+            SIO_INTERP0_CTRL_LANE0_SHIFT_LSB,
+            SIO_INTERP0_CTRL_LANE0_SHIFT_BITS,
+        );
+        let lsb = self.get(
+            SIO_INTERP0_CTRL_LANE0_MASK_LSB_LSB,
+            SIO_INTERP0_CTRL_LANE0_MASK_LSB_BITS,
+        );
+        let msb = self.get(
+            SIO_INTERP0_CTRL_LANE0_MASK_MSB_LSB,
+            SIO_INTERP0_CTRL_LANE0_MASK_MSB_BITS,
+        );
+        let upto_msb = u32::MAX >> (31 - msb);
+        let mut value = (input >> shift) & upto_msb & (u32::MAX << lsb);
+        if self.is(SIO_INTERP0_CTRL_LANE0_SIGNED_BITS) && value & (1 << msb) != 0 {
+            value |= !upto_msb;
+        }
+        value
+    }
+}
+
+impl Default for LaneConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Accumulators, bases and lane configuration of an interpolator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct State {
+    pub accum: [u32; 2],
+    pub base: [u32; 3],
+    pub ctrl: [LaneConfig; 2],
+}
+
+/// What an interpolator does, in hardware (`Interp`) or software (`Model`).
+///
+/// Lanes are 0 and 1, bases 0 to 2.
+pub trait Interpolator {
+    /// 0 or 1: blend mode is on 0, clamp mode on 1
+    fn num(&self) -> u8;
+
+    fn set_lane(&mut self, lane: usize, config: LaneConfig);
+    fn lane(&self, lane: usize) -> LaneConfig;
+
+    fn set_accumulator(&mut self, lane: usize, value: u32);
+    fn accumulator(&self, lane: usize) -> u32;
+
+    fn set_base(&mut self, index: usize, value: u32);
+    fn base(&self, index: usize) -> u32;
+
+    /// `BASE0` from the low and `BASE1` from the high 16 bits in one write,
+    /// each sign-extended if its lane is `signed`
+    fn set_base01(&mut self, value: u32);
+
+    /// Add to the accumulator
+    fn add_raw(&mut self, lane: usize, value: u32);
+    /// The lane's shifted and masked value, without the base
+    fn raw(&self, lane: usize) -> u32;
+
+    /// The lane result
+    fn peek(&self, lane: usize) -> u32;
+    /// The lane result, writing both lane results to the accumulators
+    fn pop(&mut self, lane: usize) -> u32;
+    /// The full result
+    fn peek_full(&self) -> u32;
+    /// The full result, writing both lane results to the accumulators
+    fn pop_full(&mut self) -> u32;
+
+    /// Load both accumulators, to start a run
+    fn push(&mut self, accum0: u32, accum1: u32) {
+        self.set_accumulator(0, accum0);
+        self.set_accumulator(1, accum1);
+    }
+
+    fn save(&self) -> State {
+        State {
+            accum: [self.accumulator(0), self.accumulator(1)],
+            base: [self.base(0), self.base(1), self.base(2)],
+            ctrl: [self.lane(0), self.lane(1)],
+        }
+    }
+
+    fn restore(&mut self, state: &State) {
+        self.set_lane(0, state.ctrl[0]);
+        self.set_lane(1, state.ctrl[1]);
+        self.push(state.accum[0], state.accum[1]);
+        for (index, &value) in state.base.iter().enumerate() {
+            self.set_base(index, value);
+        }
+    }
+}
+
+fn check_lane(num: u8, lane: usize, config: LaneConfig) {
+    assert!(lane < 2, "no such lane");
+    assert!(
+        !config.is(SIO_INTERP0_CTRL_LANE0_BLEND_BITS) || (num == 0 && lane == 0),
+        "blend mode is on INTERP0 lane 0 only"
+    );
+    assert!(
+        !config.is(SIO_INTERP1_CTRL_LANE0_CLAMP_BITS) || (num == 1 && lane == 0),
+        "clamp mode is on INTERP1 lane 0 only"
+    );
+}
+
+/// Interpolators claimed on each core, a bit per interpolator
+static CLAIMED: [AtomicU8; 2] = [AtomicU8::new(0), AtomicU8::new(0)];
+
+/// One of the calling core's interpolators. Stays on that core: not `Send`.
+pub struct Interp {
+    num: u8,
+    claimed: bool,
+    _core_local: PhantomData<*mut ()>,
+}
+
+impl Interp {
+    /// INTERP0 of the calling core, `None` if it's claimed. Unclaimed on drop.
+    pub fn interp0() -> Option<Interp> {
+        Self::claim(0)
+    }
+
+    /// INTERP1 of the calling core, `None` if it's claimed. Unclaimed on drop.
+    pub fn interp1() -> Option<Interp> {
+        Self::claim(1)
+    }
+
+    fn claim(num: u8) -> Option<Interp> {
+        unsafe {
+            // only this core touches its entry, masking interrupts is enough
+            let interrupts = save_and_disable_interrupts();
+            let claimed = &CLAIMED[get_core_num() as usize];
+            let free = claimed.load(Ordering::Relaxed) & (1 << num) == 0;
+            if free {
+                claimed.store(
+                    claimed.load(Ordering::Relaxed) | (1 << num),
+                    Ordering::Relaxed,
+                );
+            }
+            restore_interrupts(interrupts);
+            if !free {
+                return None;
+            }
+            Some(Interp {
+                num,
+                claimed: true,
+                _core_local: PhantomData,
+            })
+        }
+    }
+
+    /// Interpolator `num` of the calling core, without claiming it.
+    ///
+    /// # Safety
+    ///
+    /// Whoever else uses it must get its state back, e.g. an interrupt
+    /// handler does `save()` first and `restore()` when done.
+    pub unsafe fn steal(num: u8) -> Interp {
+        assert!((num as uint) < NUM_INTERPOLATORS, "no such interpolator");
+        Interp {
+            num,
+            claimed: false,
+            _core_local: PhantomData,
+        }
+    }
+
+    fn hw(&self) -> &interp_hw_t {
+        unsafe {
+            match self.num {
+                0 => &*interp0_hw,
+                _ => &*interp1_hw,
+            }
+        }
+    }
+}
+
+impl Drop for Interp {
+    fn drop(&mut self) {
+        if self.claimed {
+            unsafe {
+                let interrupts = save_and_disable_interrupts();
+                let claimed = &CLAIMED[get_core_num() as usize];
+                claimed.store(
+                    claimed.load(Ordering::Relaxed) & !(1 << self.num),
+                    Ordering::Relaxed,
+                );
+                restore_interrupts(interrupts);
+            }
+        }
+    }
+}
+
+impl Interpolator for Interp {
+    fn num(&self) -> u8 {
+        self.num
+    }
+
+    fn set_lane(&mut self, lane: usize, config: LaneConfig) {
+        check_lane(self.num, lane, config);
+        self.hw().ctrl[lane].write(config.bits());
+    }
+
+    fn lane(&self, lane: usize) -> LaneConfig {
+        // without the read-only overflow flags
+        let overflow = SIO_INTERP0_CTRL_LANE0_OVERF_BITS
+            | SIO_INTERP0_CTRL_LANE0_OVERF0_BITS
+            | SIO_INTERP0_CTRL_LANE0_OVERF1_BITS;
+        LaneConfig(self.hw().ctrl[lane].read() & !overflow)
+    }
+
+    fn set_accumulator(&mut self, lane: usize, value: u32) {
+        self.hw().accum[lane].write(value);
+    }
+
+    fn accumulator(&self, lane: usize) -> u32 {
+        self.hw().accum[lane].read()
+    }
+
+    fn set_base(&mut self, index: usize, value: u32) {
+        self.hw().base[index].write(value);
+    }
+
+    fn base(&self, index: usize) -> u32 {
+        self.hw().base[index].read()
+    }
+
+    fn set_base01(&mut self, value: u32) {
+        self.hw().base01.write(value);
+    }
+
+    fn add_raw(&mut self, lane: usize, value: u32) {
+        self.hw().add_raw[lane].write(value);
+    }
+
+    fn raw(&self, lane: usize) -> u32 {
+        self.hw().add_raw[lane].read()
+    }
+
+    fn peek(&self, lane: usize) -> u32 {
+        assert!(lane < 2, "no such lane");
+        self.hw().peek[lane].read()
+    }
+
+    fn pop(&mut self, lane: usize) -> u32 {
+        assert!(lane < 2, "no such lane");
+        self.hw().pop[lane].read()
+    }
+
+    fn peek_full(&self) -> u32 {
+        self.hw().peek[2].read()
+    }
+
+    fn pop_full(&mut self) -> u32 {
+        self.hw().pop[2].read()
+    }
+}
+
+/// An interpolator in software, following the datasheet: to work out a
+/// configuration on the host. The overflow flags aren't modelled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Model {
+    num: u8,
+    state: State,
+}
+
+/// Lane and full results, before `force_msb`
+struct Results {
+    lane: [u32; 2],
+    full: u32,
+}
+
+impl Model {
+    /// Modelling INTERP0 or INTERP1, reset state
+    pub fn new(num: u8) -> Self {
+        assert!((num as uint) < NUM_INTERPOLATORS, "no such interpolator");
+        Model {
+            num,
+            state: State {
+                accum: [0; 2],
+                base: [0; 3],
+                ctrl: [LaneConfig(0); 2],
+            },
+        }
+    }
+
+    fn input(&self, lane: usize) -> u32 {
+        let cross = self.state.ctrl[lane].is(SIO_INTERP0_CTRL_LANE0_CROSS_INPUT_BITS);
+        self.state.accum[lane ^ cross as usize]
+    }
+
+    fn results(&self) -> Results {
+        let [ctrl0, ctrl1] = self.state.ctrl;
+        let [base0, base1, base2] = self.state.base;
+        let masked = [0, 1].map(|lane| self.state.ctrl[lane].shift_mask(self.input(lane)));
+        let sum = [0, 1].map(|lane| {
+            let ctrl = self.state.ctrl[lane];
+            let value = if ctrl.is(SIO_INTERP0_CTRL_LANE0_ADD_RAW_BITS) {
+                self.input(lane)
+            } else {
+                masked[lane]
+            };
+            self.state.base[lane].wrapping_add(value)
+        });
+
+        if self.num == 0 && ctrl0.is(SIO_INTERP0_CTRL_LANE0_BLEND_BITS) {
+            let alpha = (masked[1] & 0xff) as i64;
+            let (from, to) = if ctrl1.is(SIO_INTERP0_CTRL_LANE0_SIGNED_BITS) {
+                (base0 as i32 as i64, base1 as i32 as i64)
+            } else {
+                (base0 as i64, base1 as i64)
+            };
+            Results {
+                lane: [alpha as u32, (from + (((to - from) * alpha) >> 8)) as u32],
+                full: base2.wrapping_add(masked[0]),
+            }
+        } else if self.num == 1 && ctrl0.is(SIO_INTERP1_CTRL_LANE0_CLAMP_BITS) {
+            let clamped = if ctrl0.is(SIO_INTERP0_CTRL_LANE0_SIGNED_BITS) {
+                (masked[0] as i32).max(base0 as i32).min(base1 as i32) as u32
+            } else {
+                masked[0].max(base0).min(base1)
+            };
+            Results {
+                lane: [clamped, sum[1]],
+                full: base2.wrapping_add(masked[0]).wrapping_add(masked[1]),
+            }
+        } else {
+            Results {
+                lane: sum,
+                full: base2.wrapping_add(masked[0]).wrapping_add(masked[1]),
+            }
+        }
+    }
+
+    fn read(&self, results: &Results, lane: usize) -> u32 {
+        let force = self.state.ctrl[lane].get(
+            SIO_INTERP0_CTRL_LANE0_FORCE_MSB_LSB,
+            SIO_INTERP0_CTRL_LANE0_FORCE_MSB_BITS,
+        );
+        results.lane[lane] | (force << 28)
+    }
+
+    fn write_back(&mut self, results: &Results) {
+        for lane in 0..2 {
+            let cross = self.state.ctrl[lane].is(SIO_INTERP0_CTRL_LANE0_CROSS_RESULT_BITS);
+            self.state.accum[lane] = results.lane[lane ^ cross as usize];
+        }
+    }
+}
+
+impl Interpolator for Model {
+    fn num(&self) -> u8 {
+        self.num
+    }
+
+    fn set_lane(&mut self, lane: usize, config: LaneConfig) {
+        check_lane(self.num, lane, config);
+        self.state.ctrl[lane] = config;
+    }
+
+    fn lane(&self, lane: usize) -> LaneConfig {
+        self.state.ctrl[lane]
+    }
+
+    fn set_accumulator(&mut self, lane: usize, value: u32) {
+        self.state.accum[lane] = value;
+    }
+
+    fn accumulator(&self, lane: usize) -> u32 {
+        self.state.accum[lane]
+    }
+
+    fn set_base(&mut self, index: usize, value: u32) {
+        self.state.base[index] = value;
+    }
+
+    fn base(&self, index: usize) -> u32 {
+        self.state.base[index]
+    }
+
+    fn set_base01(&mut self, value: u32) {
+        for lane in 0..2 {
+            let half = (value >> (16 * lane)) as u16;
+            let signed = self.state.ctrl[lane].is(SIO_INTERP0_CTRL_LANE0_SIGNED_BITS);
+            self.state.base[lane] = if signed {
+                half as i16 as u32
+            } else {
+                half as u32
+            };
+        }
+    }
+
+    fn add_raw(&mut self, lane: usize, value: u32) {
+        self.state.accum[lane] = self.state.accum[lane].wrapping_add(value);
+    }
+
+    fn raw(&self, lane: usize) -> u32 {
+        self.state.ctrl[lane].shift_mask(self.input(lane))
+    }
+
+    fn peek(&self, lane: usize) -> u32 {
+        assert!(lane < 2, "no such lane");
+        self.read(&self.results(), lane)
+    }
+
+    fn pop(&mut self, lane: usize) -> u32 {
+        assert!(lane < 2, "no such lane");
+        let results = self.results();
+        self.write_back(&results);
+        self.read(&results, lane)
+    }
+
+    fn peek_full(&self) -> u32 {
+        self.results().full
+    }
+
+    fn pop_full(&mut self) -> u32 {
+        let results = self.results();
+        self.write_back(&results);
+        results.full
+    }
+
+    fn save(&self) -> State {
+        self.state
+    }
+
+    fn restore(&mut self, state: &State) {
+        self.state = *state;
+    }
+}
+
+/// Blend mode set up for [`lerp`], on INTERP0. `signed` treats the end points
+/// as `i32`.
+pub fn init_lerp<I: Interpolator>(interp: &mut I, signed: bool) {
+    interp.set_lane(0, LaneConfig::new().blend(true));
+    interp.set_lane(1, LaneConfig::new().mask(0, 7).signed(signed));
+}
+
+/// `from + (to - from) * alpha / 256`, rounded down
+pub fn lerp<I: Interpolator>(interp: &mut I, from: u32, to: u32, alpha: u8) -> u32 {
+    interp.set_base(0, from);
+    interp.set_base(1, to);
+    interp.set_accumulator(1, alpha as u32);
+    interp.peek(1)
+}
+
+/// Lanes set up for [`texture_walk`]: u and v have `frac_bits` fractional
+/// bits, at least `width_bits`, and the texture is row-major.
+pub fn init_texture<I: Interpolator>(
+    interp: &mut I,
+    width_bits: u32,
+    height_bits: u32,
+    frac_bits: u32,
+) {
+    assert!(width_bits > 0 && height_bits > 0, "texture too small");
+    assert!(width_bits + height_bits <= 16, "texture too big");
+    assert!(
+        frac_bits >= width_bits && frac_bits < 32,
+        "not enough fractional bits"
+    );
+    let u = LaneConfig::new().shift(frac_bits).mask(0, width_bits - 1);
+    let v = LaneConfig::new()
+        .shift(frac_bits - width_bits)
+        .mask(width_bits, width_bits + height_bits - 1);
+    interp.set_lane(0, u.add_raw(true));
+    interp.set_lane(1, v.add_raw(true));
+    interp.set_base(2, 0);
+}
+
+/// Fill `out` with the texels at (u, v), (u + du, v + dv) and so on, wrapping
+/// around the texture's edges.
+pub fn texture_walk<I: Interpolator, T: Copy>(
+    interp: &mut I,
+    texture: &[T],
+    (u, v): (u32, u32),
+    (du, dv): (u32, u32),
+    out: &mut [T],
+) {
+    let (mask0, mask1) = (interp.lane(0), interp.lane(1));
+    let size = mask0.shift_mask(u32::MAX) | mask1.shift_mask(u32::MAX);
+    assert!(
+        texture.len() > size as usize,
+        "texture smaller than configured"
+    );
+    interp.push(u, v);
+    interp.set_base(0, du);
+    interp.set_base(1, dv);
+    for texel in out {
+        *texel = texture[interp.pop_full() as usize];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_example() {
+        let mut interp = Model::new(0);
+        interp.set_lane(0, LaneConfig::new().shift(4).mask(0, 7));
+        interp.set_base(0, 100);
+        interp.set_accumulator(0, 0x1234);
+        assert_eq!(interp.peek(0), 100 + 0x23);
+    }
+
+    #[test]
+    fn lerp_unsigned() {
+        let mut interp = Model::new(0);
+        init_lerp(&mut interp, false);
+        assert_eq!(lerp(&mut interp, 500, 1000, 128), 750);
+        for alpha in 0..=255u8 {
+            let expected = 100 + 512 * alpha as u32 / 256;
+            assert_eq!(lerp(&mut interp, 100, 612, alpha), expected);
+        }
+    }
+
+    #[test]
+    fn lerp_signed() {
+        let mut interp = Model::new(0);
+        init_lerp(&mut interp, true);
+        assert_eq!(lerp(&mut interp, -100i32 as u32, 100, 64) as i32, -50);
+        for alpha in 0..=255u8 {
+            let expected = 100 + ((-300 * alpha as i32) >> 8);
+            let result = lerp(&mut interp, 100, -200i32 as u32, alpha) as i32;
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn texture() {
+        let mut interp = Model::new(0);
+        init_texture(&mut interp, 3, 2, 16);
+        let texture: [u8; 32] = core::array::from_fn(|i| i as u8);
+        let (u, v, du, dv) = (0x1_8000u32, 0x3_0000u32, 0xc000u32, 0x4000u32);
+        let mut out = [0u8; 20];
+        texture_walk(&mut interp, &texture, (u, v), (du, dv), &mut out);
+        for (i, &texel) in out.iter().enumerate() {
+            let i = i as u32;
+            let x = (u + du * i) >> 16 & 7;
+            let y = (v + dv * i) >> 16 & 3;
+            assert_eq!(texel as u32, x + y * 8, "texel {}", i);
+        }
+    }
+
+    #[test]
+    fn clamp() {
+        let mut interp = Model::new(1);
+        interp.set_lane(0, LaneConfig::new().clamp(true).signed(true));
+        interp.set_base(0, -10i32 as u32);
+        interp.set_base(1, 10);
+        for &(input, clamped) in &[(-50, -10), (5, 5), (50, 10)] {
+            interp.set_accumulator(0, input as u32);
+            assert_eq!(interp.peek(0) as i32, clamped);
+        }
+
+        interp.set_lane(0, LaneConfig::new().clamp(true));
+        interp.set_base(0, 10);
+        interp.set_base(1, 20);
+        interp.set_accumulator(0, -1i32 as u32);
+        assert_eq!(interp.peek(0), 20);
+    }
+
+    #[test]
+    #[should_panic(expected = "clamp mode")]
+    fn clamp_on_interp0() {
+        Model::new(0).set_lane(0, LaneConfig::new().clamp(true));
+    }
+
+    #[test]
+    #[should_panic(expected = "blend mode")]
+    fn blend_on_interp1() {
+        Model::new(1).set_lane(0, LaneConfig::new().blend(true));
+    }
+
+    #[test]
+    fn signed_lane() {
+        let mut interp = Model::new(0);
+        interp.set_lane(1, LaneConfig::new().mask(0, 7).signed(true));
+        interp.set_accumulator(1, 0xff);
+        interp.set_base(1, 10);
+        assert_eq!(interp.peek(1), 9);
+
+        interp.set_lane(0, LaneConfig::new());
+        interp.set_base01(0xffff_fffe);
+        assert_eq!((interp.base(0), interp.base(1)), (0xfffe, u32::MAX));
+    }
+
+    #[test]
+    fn cross() {
+        let mut interp = Model::new(0);
+        interp.set_lane(0, LaneConfig::new().cross_input(true));
+        interp.push(1, 5);
+        assert_eq!(interp.peek(0), 5);
+        assert_eq!(interp.raw(0), 5);
+
+        interp.set_lane(0, LaneConfig::new().cross_result(true));
+        interp.set_lane(1, LaneConfig::new().cross_result(true));
+        interp.set_base(0, 100);
+        interp.set_base(1, 200);
+        assert_eq!(interp.pop(0), 101);
+        assert_eq!((interp.accumulator(0), interp.accumulator(1)), (205, 101));
+    }
+
+    #[test]
+    fn force_msb() {
+        let mut interp = Model::new(0);
+        interp.set_lane(0, LaneConfig::new().force_msb(0b10));
+        interp.set_base(0, 0x100);
+        assert_eq!(interp.peek(0), 0x2000_0100);
+        assert_eq!(interp.pop(0), 0x2000_0100);
+        assert_eq!(interp.accumulator(0), 0x100);
+        assert_eq!(interp.peek_full(), 0x100);
+    }
+
+    #[test]
+    fn save_restore() {
+        let mut interp = Model::new(0);
+        init_lerp(&mut interp, true);
+        interp.push(1, 2);
+        let state = interp.save();
+        init_texture(&mut interp, 3, 2, 16);
+        interp.restore(&state);
+        assert_eq!(interp.save(), state);
+    }
+}
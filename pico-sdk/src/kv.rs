@@ -0,0 +1,1019 @@
+//! A small key-value store on flash, for settings: byte keys, byte values.
+//!
+//! ```ignore
+//! // the last two sectors, kept across program updates
+//! let flash = Flash::region(FLASH_SIZE - 2 * SECTOR_SIZE, 2 * SECTOR_SIZE)?;
+//! let mut kv = Store::mount(flash)?;
+//! kv.set(b"brightness", &[80])?;
+//! let mut buf = [0; 1];
+//! if let Some(n) = kv.get(b"brightness", &mut buf)? { /* &buf[..n] */ }
+//! kv.remove(b"brightness")?;
+//! ```
+//!
+//! Works on any embedded-storage `NorFlash` of two or more sectors, such as
+//! `flash::Flash`, or `RamFlash` to try it out on the host.
+//!
+//! One sector is active at a time, a log of records appended one after the
+//! other, the last record of a key wins. When the active sector is full, the
+//! live records are copied to the next sector, which becomes active once its
+//! header is written, the last step. A record counts once its CRC checks out:
+//! power loss in the middle of a write or a compaction leaves the previous
+//! state.
+//!
+//! Layout, little endian:
+//!
+//! - sector header: magic `b"PKV1"`, sequence number `u32`, CRC-32 of both
+//! - record: key length `u8`, flags `u8`, value length `u16`, CRC-32 of the
+//!   rest, key, value, padded with 0xff to `WRITE_SIZE`
+
+use embedded_storage::nor_flash::{
+    ErrorType, MultiwriteNorFlash, NorFlash, NorFlashError, NorFlashErrorKind, ReadNorFlash,
+};
+
+const MAGIC: [u8; 4] = *b"PKV1";
+const SECTOR_HEADER_LEN: usize = 12;
+const RECORD_HEADER_LEN: usize = 8;
+/// Flags of a removed key, no value follows
+const FLAG_TOMBSTONE: u8 = 0x01;
+
+/// Longest key, a length of 0xff marks free space.
+pub const MAX_KEY_LEN: usize = 0xfe;
+/// Longest value.
+pub const MAX_VALUE_LEN: usize = 0xffff;
+
+/// Bytes copied at once, records go through it
+const CHUNK: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error<E> {
+    /// The flash failed.
+    Flash(E),
+    /// Less than two sectors, or a `WRITE_SIZE` not dividing 64.
+    Geometry,
+    /// An empty key, or longer than `MAX_KEY_LEN`.
+    Key,
+    /// Longer than `MAX_VALUE_LEN`.
+    Value,
+    /// The live records don't fit in a sector.
+    Full,
+    /// The value doesn't fit in the buffer given to `get()`, needs that many bytes.
+    Buffer(usize),
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(e: E) -> Self {
+        Error::Flash(e)
+    }
+}
+
+/// CRC-32 (IEEE), fed in pieces
+struct Crc(u32);
+
+impl Crc {
+    fn new() -> Self {
+        Crc(0xffff_ffff)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for &b in data {
+            self.0 ^= b as u32;
+            for _ in 0..8 {
+                self.0 = if self.0 & 1 != 0 {
+                    (self.0 >> 1) ^ 0xedb8_8320
+                } else {
+                    self.0 >> 1
+                };
+            }
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        !self.0
+    }
+}
+
+/// A record header, where it is in the sector
+#[derive(Debug, Clone, Copy)]
+struct Record {
+    /// Offset of the header, in the sector
+    at: u32,
+    key_len: u8,
+    flags: u8,
+    value_len: u16,
+    crc: u32,
+}
+
+impl Record {
+    fn parse(at: u32, raw: &[u8; RECORD_HEADER_LEN]) -> Self {
+        Record {
+            at,
+            key_len: raw[0],
+            flags: raw[1],
+            value_len: u16::from_le_bytes([raw[2], raw[3]]),
+            crc: u32::from_le_bytes([raw[4], raw[5], raw[6], raw[7]]),
+        }
+    }
+
+    fn header(&self) -> [u8; RECORD_HEADER_LEN] {
+        let len = self.value_len.to_le_bytes();
+        let crc = self.crc.to_le_bytes();
+        [
+            self.key_len,
+            self.flags,
+            len[0],
+            len[1],
+            crc[0],
+            crc[1],
+            crc[2],
+            crc[3],
+        ]
+    }
+
+    fn is_free(raw: &[u8; RECORD_HEADER_LEN]) -> bool {
+        raw.iter().all(|&b| b == 0xff)
+    }
+
+    fn is_tombstone(&self) -> bool {
+        self.flags & FLAG_TOMBSTONE != 0
+    }
+
+    fn key_at(&self) -> u32 {
+        self.at + RECORD_HEADER_LEN as u32
+    }
+
+    fn value_at(&self) -> u32 {
+        self.key_at() + self.key_len as u32
+    }
+
+    /// Bytes taken, padding included
+    fn len(&self, write_size: usize) -> u32 {
+        let len = RECORD_HEADER_LEN + self.key_len as usize + self.value_len as usize;
+        align_up(len, write_size) as u32
+    }
+}
+
+fn align_up(n: usize, align: usize) -> usize {
+    n.div_ceil(align) * align
+}
+
+/// Where a key's last record is
+enum Found {
+    Value(Record),
+    Removed,
+    Missing,
+}
+
+/// A key-value store on `F`, see the module docs.
+pub struct Store<F> {
+    flash: F,
+    sectors: u32,
+    /// Index of the active sector
+    active: u32,
+    seq: u32,
+    /// Offset of the free space in the active sector
+    free: u32,
+}
+
+impl<F: NorFlash> Store<F> {
+    const SECTOR: u32 = F::ERASE_SIZE as u32;
+
+    /// Open the store on `flash`, formatting it if there is none.
+    ///
+    /// A write cut short by power loss is dropped, then the store is
+    /// compacted to get past it.
+    pub fn mount(flash: F) -> Result<Self, Error<F::Error>> {
+        let sectors = flash.capacity() / F::ERASE_SIZE;
+        if sectors < 2
+            || !CHUNK.is_multiple_of(F::WRITE_SIZE)
+            || !CHUNK.is_multiple_of(F::READ_SIZE)
+        {
+            return Err(Error::Geometry);
+        }
+        let mut store = Store {
+            flash,
+            sectors: sectors as u32,
+            active: 0,
+            seq: 0,
+            free: 0,
+        };
+
+        let mut found = None;
+        for sector in 0..store.sectors {
+            if let Some(seq) = store.read_sector_header(sector)? {
+                // wrapping, a sequence number just past the other is newer
+                match found {
+                    Some((_, newest)) if (seq.wrapping_sub(newest) as i32) <= 0 => {}
+                    _ => found = Some((sector, seq)),
+                }
+            }
+        }
+        match found {
+            Some((sector, seq)) => {
+                store.active = sector;
+                store.seq = seq;
+                if !store.scan()? {
+                    store.compact(None)?;
+                }
+            }
+            None => store.format()?,
+        }
+        Ok(store)
+    }
+
+    /// Erase everything, and start over.
+    pub fn format(&mut self) -> Result<(), Error<F::Error>> {
+        let seq = self.seq.wrapping_add(1);
+        self.flash.erase(0, Self::SECTOR)?;
+        self.write_sector_header(0, seq)?;
+        // the others are stale now, but erase them so nothing old resurfaces
+        self.flash
+            .erase(Self::SECTOR, self.sectors * Self::SECTOR)?;
+        self.active = 0;
+        self.seq = seq;
+        self.free = self.records_start();
+        Ok(())
+    }
+
+    /// Copy the value of `key` into `buf`, returning its length, `None` if
+    /// there is no such key.
+    pub fn get(&mut self, key: &[u8], buf: &mut [u8]) -> Result<Option<usize>, Error<F::Error>> {
+        check_key(key)?;
+        match self.find(key)? {
+            Found::Value(record) => {
+                let len = record.value_len as usize;
+                if len > buf.len() {
+                    return Err(Error::Buffer(len));
+                }
+                self.read(self.offset(record.value_at()), &mut buf[..len])?;
+                Ok(Some(len))
+            }
+            Found::Removed | Found::Missing => Ok(None),
+        }
+    }
+
+    /// Whether there is a value for `key`.
+    pub fn contains(&mut self, key: &[u8]) -> Result<bool, Error<F::Error>> {
+        check_key(key)?;
+        Ok(matches!(self.find(key)?, Found::Value(_)))
+    }
+
+    /// Set `key` to `value`, on flash once it returns.
+    pub fn set(&mut self, key: &[u8], value: &[u8]) -> Result<(), Error<F::Error>> {
+        check_key(key)?;
+        if value.len() > MAX_VALUE_LEN {
+            return Err(Error::Value);
+        }
+        self.append(key, 0, value)
+    }
+
+    /// Remove `key`, nothing happens if there is no such key.
+    pub fn remove(&mut self, key: &[u8]) -> Result<(), Error<F::Error>> {
+        check_key(key)?;
+        match self.find(key)? {
+            Found::Value(_) => self.append(key, FLAG_TOMBSTONE, &[]),
+            Found::Removed | Found::Missing => Ok(()),
+        }
+    }
+
+    /// Bytes left in the active sector, before a compaction.
+    pub fn free_space(&self) -> usize {
+        (Self::SECTOR - self.free) as usize
+    }
+
+    /// Give the flash back.
+    pub fn release(self) -> F {
+        self.flash
+    }
+
+    fn records_start(&self) -> u32 {
+        align_up(SECTOR_HEADER_LEN, F::WRITE_SIZE) as u32
+    }
+
+    /// Offset in flash of `at` in the active sector
+    fn offset(&self, at: u32) -> u32 {
+        self.active * Self::SECTOR + at
+    }
+
+    /// Read anywhere, in `READ_SIZE` pieces through a buffer
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Error<F::Error>> {
+        let start = offset as usize / F::READ_SIZE * F::READ_SIZE;
+        let mut pos = start;
+        let end = offset as usize + bytes.len();
+        let mut buf = [0; CHUNK];
+        while pos < end {
+            let n = (end - pos).min(CHUNK);
+            let n_aligned = align_up(n, F::READ_SIZE);
+            self.flash.read(pos as u32, &mut buf[..n_aligned])?;
+            // the part of it in offset..end
+            let from = (offset as usize).max(pos);
+            let to = end.min(pos + n);
+            bytes[from - offset as usize..to - offset as usize]
+                .copy_from_slice(&buf[from - pos..to - pos]);
+            pos += n;
+        }
+        Ok(())
+    }
+
+    fn read_sector_header(&mut self, sector: u32) -> Result<Option<u32>, Error<F::Error>> {
+        let mut raw = [0; SECTOR_HEADER_LEN];
+        self.read(sector * Self::SECTOR, &mut raw)?;
+        let mut crc = Crc::new();
+        crc.update(&raw[..8]);
+        let ok = raw[..4] == MAGIC && raw[8..] == crc.finish().to_le_bytes();
+        Ok(if ok {
+            Some(u32::from_le_bytes([raw[4], raw[5], raw[6], raw[7]]))
+        } else {
+            None
+        })
+    }
+
+    fn write_sector_header(&mut self, sector: u32, seq: u32) -> Result<(), Error<F::Error>> {
+        let mut buf = [0xff; CHUNK];
+        buf[..4].copy_from_slice(&MAGIC);
+        buf[4..8].copy_from_slice(&seq.to_le_bytes());
+        let mut crc = Crc::new();
+        crc.update(&buf[..8]);
+        buf[8..12].copy_from_slice(&crc.finish().to_le_bytes());
+        let len = self.records_start() as usize;
+        self.flash.write(sector * Self::SECTOR, &buf[..len])?;
+        Ok(())
+    }
+
+    /// Record header at `at` in the active sector, `None` at the free space
+    fn record_at(&mut self, at: u32) -> Result<Option<Record>, Error<F::Error>> {
+        if at + RECORD_HEADER_LEN as u32 > Self::SECTOR {
+            return Ok(None);
+        }
+        let mut raw = [0; RECORD_HEADER_LEN];
+        self.read(self.offset(at), &mut raw)?;
+        if Record::is_free(&raw) {
+            return Ok(None);
+        }
+        Ok(Some(Record::parse(at, &raw)))
+    }
+
+    /// CRC of the record as stored
+    fn record_crc(&mut self, record: &Record) -> Result<u32, Error<F::Error>> {
+        let mut crc = Crc::new();
+        crc.update(&record.header()[..4]);
+        let len = record.key_len as u32 + record.value_len as u32;
+        let mut buf = [0; CHUNK];
+        let mut pos = 0;
+        while pos < len {
+            let n = (len - pos).min(CHUNK as u32);
+            self.read(self.offset(record.key_at() + pos), &mut buf[..n as usize])?;
+            crc.update(&buf[..n as usize]);
+            pos += n;
+        }
+        Ok(crc.finish())
+    }
+
+    /// Find the free space of the active sector. False if a record is cut
+    /// short, the space after it can't be trusted to be erased.
+    fn scan(&mut self) -> Result<bool, Error<F::Error>> {
+        let mut at = self.records_start();
+        self.free = at;
+        while let Some(record) = self.record_at(at)? {
+            let end = at + record.len(F::WRITE_SIZE);
+            if record.key_len == 0xff
+                || end > Self::SECTOR
+                || self.record_crc(&record)? != record.crc
+            {
+                return Ok(false);
+            }
+            at = end;
+            self.free = at;
+        }
+        Ok(true)
+    }
+
+    /// The record at `at`, `None` past the last one. Those before the free
+    /// space all passed `scan()`.
+    fn next_record(&mut self, at: u32) -> Result<Option<Record>, Error<F::Error>> {
+        if at >= self.free {
+            return Ok(None);
+        }
+        self.record_at(at)
+    }
+
+    fn key_matches(&mut self, record: &Record, key: &[u8]) -> Result<bool, Error<F::Error>> {
+        if record.key_len as usize != key.len() {
+            return Ok(false);
+        }
+        let mut buf = [0; MAX_KEY_LEN];
+        let stored = &mut buf[..key.len()];
+        self.read(self.offset(record.key_at()), stored)?;
+        Ok(stored == key)
+    }
+
+    fn find(&mut self, key: &[u8]) -> Result<Found, Error<F::Error>> {
+        let mut found = Found::Missing;
+        let mut at = self.records_start();
+        while let Some(record) = self.next_record(at)? {
+            if self.key_matches(&record, key)? {
+                found = if record.is_tombstone() {
+                    Found::Removed
+                } else {
+                    Found::Value(record)
+                };
+            }
+            at += record.len(F::WRITE_SIZE);
+        }
+        Ok(found)
+    }
+
+    fn append(&mut self, key: &[u8], flags: u8, value: &[u8]) -> Result<(), Error<F::Error>> {
+        let mut crc = Crc::new();
+        let mut record = Record {
+            at: 0,
+            key_len: key.len() as u8,
+            flags,
+            value_len: value.len() as u16,
+            crc: 0,
+        };
+        crc.update(&record.header()[..4]);
+        crc.update(key);
+        crc.update(value);
+        record.crc = crc.finish();
+
+        if self.free + record.len(F::WRITE_SIZE) > Self::SECTOR {
+            return self.compact(Some((record, key, value)));
+        }
+        record.at = self.free;
+        let offset = self.offset(record.at);
+        self.write_record(offset, &record, key, value)?;
+        self.free += record.len(F::WRITE_SIZE);
+        Ok(())
+    }
+
+    /// Write header, key and value at `offset` in flash, through a buffer
+    /// of whole `WRITE_SIZE`s
+    fn write_record(
+        &mut self,
+        offset: u32,
+        record: &Record,
+        key: &[u8],
+        value: &[u8],
+    ) -> Result<(), Error<F::Error>> {
+        let header = record.header();
+        let mut w = Writer {
+            offset,
+            buf: [0xff; CHUNK],
+            len: 0,
+        };
+        for part in [&header[..], key, value] {
+            w.put(&mut self.flash, part)?;
+        }
+        w.flush(&mut self.flash)?;
+        Ok(())
+    }
+
+    /// Copy the records still live to the next sector, with `extra` appended,
+    /// then switch to it
+    fn compact(&mut self, extra: Option<(Record, &[u8], &[u8])>) -> Result<(), Error<F::Error>> {
+        let to = (self.active + 1) % self.sectors;
+        self.flash
+            .erase(to * Self::SECTOR, (to + 1) * Self::SECTOR)?;
+
+        let mut free = self.records_start();
+        let mut at = self.records_start();
+        while let Some(record) = self.next_record(at)? {
+            at += record.len(F::WRITE_SIZE);
+            if record.is_tombstone() || self.is_superseded(&record, at, extra)? {
+                continue;
+            }
+            let len = record.len(F::WRITE_SIZE);
+            if free + len > Self::SECTOR {
+                return Err(Error::Full);
+            }
+            self.copy_record(&record, to * Self::SECTOR + free)?;
+            free += len;
+        }
+        if let Some((record, key, value)) = extra {
+            // a tombstone has nothing left to hide once compacted
+            if !record.is_tombstone() {
+                let len = record.len(F::WRITE_SIZE);
+                if free + len > Self::SECTOR {
+                    return Err(Error::Full);
+                }
+                self.write_record(to * Self::SECTOR + free, &record, key, value)?;
+                free += len;
+            }
+        }
+
+        // the commit: from here on `to` is the newest sector
+        let seq = self.seq.wrapping_add(1);
+        self.write_sector_header(to, seq)?;
+        self.active = to;
+        self.seq = seq;
+        self.free = free;
+        Ok(())
+    }
+
+    /// Whether a later record, from `after` on or `extra`, has the same key
+    fn is_superseded(
+        &mut self,
+        record: &Record,
+        after: u32,
+        extra: Option<(Record, &[u8], &[u8])>,
+    ) -> Result<bool, Error<F::Error>> {
+        let mut key = [0; MAX_KEY_LEN];
+        let key = &mut key[..record.key_len as usize];
+        self.read(self.offset(record.key_at()), key)?;
+        if let Some((_, extra_key, _)) = extra {
+            if extra_key == &key[..] {
+                return Ok(true);
+            }
+        }
+        let mut at = after;
+        while let Some(later) = self.next_record(at)? {
+            if self.key_matches(&later, key)? {
+                return Ok(true);
+            }
+            at += later.len(F::WRITE_SIZE);
+        }
+        Ok(false)
+    }
+
+    /// Copy `record` of the active sector to `offset` in flash, as it is
+    fn copy_record(&mut self, record: &Record, offset: u32) -> Result<(), Error<F::Error>> {
+        let len = record.len(F::WRITE_SIZE);
+        let mut buf = [0; CHUNK];
+        let mut pos = 0;
+        while pos < len {
+            let n = (len - pos).min(CHUNK as u32);
+            let chunk = &mut buf[..n as usize];
+            self.read(self.offset(record.at + pos), chunk)?;
+            self.flash.write(offset + pos, chunk)?;
+            pos += n;
+        }
+        Ok(())
+    }
+}
+
+fn check_key<E>(key: &[u8]) -> Result<(), Error<E>> {
+    if key.is_empty() || key.len() > MAX_KEY_LEN {
+        Err(Error::Key)
+    } else {
+        Ok(())
+    }
+}
+
+/// Gathers bytes into whole `WRITE_SIZE`s, see `Store::write_record()`
+struct Writer {
+    offset: u32,
+    buf: [u8; CHUNK],
+    len: usize,
+}
+
+impl Writer {
+    fn put<F: NorFlash>(&mut self, flash: &mut F, mut data: &[u8]) -> Result<(), F::Error> {
+        while !data.is_empty() {
+            let n = data.len().min(CHUNK - self.len);
+            self.buf[self.len..self.len + n].copy_from_slice(&data[..n]);
+            self.len += n;
+            data = &data[n..];
+            if self.len == CHUNK {
+                flash.write(self.offset, &self.buf)?;
+                self.offset += CHUNK as u32;
+                self.len = 0;
+            }
+        }
+        Ok(())
+    }
+
+    /// The rest, padded with 0xff
+    fn flush<F: NorFlash>(&mut self, flash: &mut F) -> Result<(), F::Error> {
+        if self.len > 0 {
+            let len = align_up(self.len, F::WRITE_SIZE);
+            self.buf[self.len..len].fill(0xff);
+            flash.write(self.offset, &self.buf[..len])?;
+        }
+        Ok(())
+    }
+}
+
+/// NOR flash in RAM, `N` bytes in sectors of `SECTOR`: erasing sets bits,
+/// writing only clears them. For trying out, fuzzing or testing the store on
+/// the host.
+pub struct RamFlash<const N: usize, const SECTOR: usize = 4096> {
+    pub mem: [u8; N],
+}
+
+impl<const N: usize, const SECTOR: usize> RamFlash<N, SECTOR> {
+    /// Erased, all 0xff.
+    pub const fn new() -> Self {
+        RamFlash { mem: [0xff; N] }
+    }
+}
+
+impl<const N: usize, const SECTOR: usize> Default for RamFlash<N, SECTOR> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RamFlashError(pub NorFlashErrorKind);
+
+impl NorFlashError for RamFlashError {
+    fn kind(&self) -> NorFlashErrorKind {
+        self.0
+    }
+}
+
+impl<const N: usize, const SECTOR: usize> ErrorType for RamFlash<N, SECTOR> {
+    type Error = RamFlashError;
+}
+
+impl<const N: usize, const SECTOR: usize> ReadNorFlash for RamFlash<N, SECTOR> {
+    const READ_SIZE: usize = 1;
+
+    fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), RamFlashError> {
+        embedded_storage::nor_flash::check_read(self, offset, bytes.len())
+            .map_err(RamFlashError)?;
+        let offset = offset as usize;
+        bytes.copy_from_slice(&self.mem[offset..offset + bytes.len()]);
+        Ok(())
+    }
+
+    fn capacity(&self) -> usize {
+        N
+    }
+}
+
+impl<const N: usize, const SECTOR: usize> NorFlash for RamFlash<N, SECTOR> {
+    const WRITE_SIZE: usize = 1;
+    const ERASE_SIZE: usize = SECTOR;
+
+    fn erase(&mut self, from: u32, to: u32) -> Result<(), RamFlashError> {
+        embedded_storage::nor_flash::check_erase(self, from, to).map_err(RamFlashError)?;
+        self.mem[from as usize..to as usize].fill(0xff);
+        Ok(())
+    }
+
+    fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), RamFlashError> {
+        embedded_storage::nor_flash::check_write(self, offset, bytes.len())
+            .map_err(RamFlashError)?;
+        let offset = offset as usize;
+        for (cell, &b) in self.mem[offset..offset + bytes.len()].iter_mut().zip(bytes) {
+            *cell &= b;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize, const SECTOR: usize> MultiwriteNorFlash for RamFlash<N, SECTOR> {}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::collections::BTreeMap;
+    use std::vec::Vec;
+
+    use super::*;
+
+    const SECTOR: usize = 256;
+
+    type Two = RamFlash<{ 2 * SECTOR }, SECTOR>;
+    type Three = RamFlash<{ 3 * SECTOR }, SECTOR>;
+    type Model = BTreeMap<Vec<u8>, Vec<u8>>;
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Set(&'static [u8], Vec<u8>),
+        Remove(&'static [u8]),
+    }
+
+    impl Op {
+        fn run<F: NorFlash>(&self, store: &mut Store<F>) -> Result<(), Error<F::Error>> {
+            match self {
+                Op::Set(key, value) => store.set(key, value),
+                Op::Remove(key) => store.remove(key),
+            }
+        }
+
+        fn model(&self, model: &mut Model) {
+            match self {
+                Op::Set(key, value) => {
+                    model.insert(key.to_vec(), value.clone());
+                }
+                Op::Remove(key) => {
+                    model.remove(*key);
+                }
+            }
+        }
+    }
+
+    const KEYS: [&[u8]; 5] = [b"a", b"bb", b"ccc", b"brightness", b"e"];
+
+    fn value(len: usize, fill: u8) -> Vec<u8> {
+        (0..len).map(|i| fill.wrapping_add(i as u8)).collect()
+    }
+
+    fn contents<F: NorFlash>(store: &mut Store<F>) -> Model
+    where
+        F::Error: core::fmt::Debug,
+    {
+        let mut model = Model::new();
+        let mut buf = [0; 256];
+        for key in KEYS {
+            if let Some(n) = store.get(key, &mut buf).unwrap() {
+                model.insert(key.to_vec(), buf[..n].to_vec());
+            }
+        }
+        model
+    }
+
+    fn copy<const N: usize>(flash: &RamFlash<N, SECTOR>) -> RamFlash<N, SECTOR> {
+        RamFlash { mem: flash.mem }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut store = Store::mount(Two::new()).unwrap();
+        let mut buf = [0; 8];
+        assert_eq!(store.get(b"k", &mut buf), Ok(None));
+        store.set(b"k", b"value").unwrap();
+        assert_eq!(store.get(b"k", &mut buf), Ok(Some(5)));
+        assert_eq!(&buf[..5], b"value");
+        assert_eq!(store.contains(b"k"), Ok(true));
+
+        store.set(b"k", b"").unwrap();
+        assert_eq!(store.get(b"k", &mut buf), Ok(Some(0)));
+        store.set(b"k", b"longer value").unwrap();
+        assert_eq!(store.get(b"k", &mut buf), Err(Error::Buffer(12)));
+
+        store.remove(b"k").unwrap();
+        assert_eq!(store.get(b"k", &mut buf), Ok(None));
+        assert_eq!(store.contains(b"k"), Ok(false));
+        // nothing written for a key that isn't there
+        let free = store.free_space();
+        store.remove(b"k").unwrap();
+        assert_eq!(store.free_space(), free);
+
+        // and all of it from flash again
+        store.set(b"x", b"1").unwrap();
+        let mut store = Store::mount(store.release()).unwrap();
+        assert_eq!(store.get(b"x", &mut buf), Ok(Some(1)));
+        assert_eq!(store.get(b"k", &mut buf), Ok(None));
+    }
+
+    #[test]
+    fn bad_arguments() {
+        let mut store = Store::mount(Two::new()).unwrap();
+        let mut buf = [0; 8];
+        assert_eq!(store.set(b"", b"v"), Err(Error::Key));
+        assert_eq!(
+            store.get(&[b'k'; MAX_KEY_LEN + 1], &mut buf),
+            Err(Error::Key)
+        );
+        let long = [0; MAX_VALUE_LEN + 1];
+        assert_eq!(store.set(b"k", &long), Err(Error::Value));
+        assert!(matches!(
+            Store::mount(RamFlash::<SECTOR, SECTOR>::new()),
+            Err(Error::Geometry)
+        ));
+    }
+
+    fn compaction<const N: usize>(flash: RamFlash<N, SECTOR>) {
+        let mut store = Store::mount(flash).unwrap();
+        let mut model = Model::new();
+        // enough rewrites to go around every sector a few times
+        for round in 0..40u8 {
+            for (i, key) in KEYS.iter().enumerate() {
+                let op = if (round as usize + i).is_multiple_of(7) {
+                    Op::Remove(key)
+                } else {
+                    Op::Set(key, value(10 + i * 3, round))
+                };
+                op.run(&mut store).unwrap();
+                op.model(&mut model);
+            }
+            assert_eq!(contents(&mut store), model);
+        }
+        let seq = store.seq;
+        assert!(seq > 10, "only {} compactions", seq);
+        let mut store = Store::mount(store.release()).unwrap();
+        assert_eq!(store.seq, seq);
+        assert_eq!(contents(&mut store), model);
+    }
+
+    #[test]
+    fn compaction_two_sectors() {
+        compaction(Two::new());
+    }
+
+    #[test]
+    fn compaction_three_sectors() {
+        compaction(Three::new());
+    }
+
+    #[test]
+    fn full() {
+        let mut store = Store::mount(Two::new()).unwrap();
+        store.set(b"a", &value(200, 0)).unwrap();
+        // 12 + 209 + 109 bytes don't fit a 256 byte sector
+        assert_eq!(store.set(b"b", &value(100, 0)), Err(Error::Full));
+        let mut buf = [0; 200];
+        assert_eq!(store.get(b"a", &mut buf), Ok(Some(200)));
+        assert_eq!(store.get(b"b", &mut buf), Ok(None));
+
+        // replacing it still works, the old record doesn't count
+        store.set(b"a", &value(200, 1)).unwrap();
+        let mut store = Store::mount(store.release()).unwrap();
+        assert_eq!(store.get(b"a", &mut buf), Ok(Some(200)));
+        assert_eq!(buf[..], value(200, 1)[..]);
+    }
+
+    #[test]
+    fn sequence_wraparound() {
+        let mut store = Store::mount(Three::new()).unwrap();
+        store.seq = u32::MAX - 3;
+        store.format().unwrap();
+        store.set(b"a", b"1").unwrap();
+        for expected in [u32::MAX - 1, u32::MAX, 0, 1, 2, 3] {
+            store.compact(None).unwrap();
+            assert_eq!(store.seq, expected);
+            let active = store.active;
+            store = Store::mount(store.release()).unwrap();
+            assert_eq!((store.active, store.seq), (active, expected));
+            let mut buf = [0; 1];
+            assert_eq!(store.get(b"a", &mut buf), Ok(Some(1)));
+        }
+    }
+
+    /// Loses power after `budget` bytes written or erased: the rest of that
+    /// write or erase doesn't happen, and every access after it fails.
+    struct Cutoff<const N: usize> {
+        flash: RamFlash<N, SECTOR>,
+        budget: usize,
+        cut: bool,
+    }
+
+    impl<const N: usize> Cutoff<N> {
+        /// How much of `len` bytes gets done
+        fn spend(&mut self, len: usize) -> Result<usize, RamFlashError> {
+            if self.cut {
+                return Err(RamFlashError(NorFlashErrorKind::Other));
+            }
+            let n = len.min(self.budget);
+            self.budget -= n;
+            self.cut = n < len;
+            Ok(n)
+        }
+
+        fn result(&self) -> Result<(), RamFlashError> {
+            if self.cut {
+                Err(RamFlashError(NorFlashErrorKind::Other))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl<const N: usize> ErrorType for Cutoff<N> {
+        type Error = RamFlashError;
+    }
+
+    impl<const N: usize> ReadNorFlash for Cutoff<N> {
+        const READ_SIZE: usize = 1;
+
+        fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), RamFlashError> {
+            self.result()?;
+            self.flash.read(offset, bytes)
+        }
+
+        fn capacity(&self) -> usize {
+            N
+        }
+    }
+
+    impl<const N: usize> NorFlash for Cutoff<N> {
+        const WRITE_SIZE: usize = 1;
+        const ERASE_SIZE: usize = SECTOR;
+
+        fn erase(&mut self, from: u32, to: u32) -> Result<(), RamFlashError> {
+            let n = self.spend((to - from) as usize)?;
+            self.flash.mem[from as usize..from as usize + n].fill(0xff);
+            self.result()
+        }
+
+        fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), RamFlashError> {
+            let n = self.spend(bytes.len())?;
+            self.flash.write(offset, &bytes[..n])?;
+            self.result()
+        }
+    }
+
+    /// `op` on the store `setup` leaves, cut short after every possible
+    /// number of bytes: mounted again, the store holds what it did before
+    /// `op`, or after it, and keeps working.
+    fn power_loss<const N: usize>(setup: &[Op], op: Op) {
+        let mut store = Store::mount(RamFlash::<N, SECTOR>::new()).unwrap();
+        let mut before = Model::new();
+        for op in setup {
+            op.run(&mut store).unwrap();
+            op.model(&mut before);
+        }
+        let base = store.release();
+        let mut after = before.clone();
+        op.model(&mut after);
+
+        for budget in 0.. {
+            let flash = Cutoff {
+                flash: copy(&base),
+                budget,
+                cut: false,
+            };
+            let mut store = Store::mount(flash).unwrap();
+            let done = op.run(&mut store).is_ok();
+            let flash = store.release();
+            assert_eq!(done, !flash.cut);
+
+            let mut store = Store::mount(flash.flash).unwrap();
+            let found = contents(&mut store);
+            if done {
+                assert_eq!(found, after, "{:?} done", op);
+                return;
+            }
+            assert!(
+                found == before || found == after,
+                "{:?} cut after {} bytes: {:?}",
+                op,
+                budget,
+                found
+            );
+            store.set(b"e", b"still works").unwrap();
+            let mut buf = [0; 16];
+            assert_eq!(store.get(b"e", &mut buf), Ok(Some(11)));
+        }
+    }
+
+    fn filled() -> Vec<Op> {
+        // 12 + 4 * 49 bytes of the 256, 48 left
+        KEYS[..4]
+            .iter()
+            .enumerate()
+            .map(|(i, key)| Op::Set(key, value(41 - key.len(), i as u8)))
+            .collect()
+    }
+
+    #[test]
+    fn power_loss_append() {
+        power_loss::<{ 2 * SECTOR }>(&filled()[..2], Op::Set(b"a", value(30, 9)));
+        power_loss::<{ 2 * SECTOR }>(&filled()[..2], Op::Remove(b"bb"));
+    }
+
+    #[test]
+    fn power_loss_compact() {
+        // doesn't fit in the 48 bytes left: compacted, with one record replaced
+        power_loss::<{ 2 * SECTOR }>(&filled(), Op::Set(b"ccc", value(60, 9)));
+        power_loss::<{ 3 * SECTOR }>(&filled(), Op::Set(b"ccc", value(60, 9)));
+        let mut setup = filled();
+        setup.push(Op::Set(b"e", value(30, 9)));
+        power_loss::<{ 2 * SECTOR }>(&setup, Op::Remove(b"a"));
+    }
+
+    /// xorshift32, enough to pick the operations
+    struct XorShift(u32);
+
+    impl XorShift {
+        fn next_u32(&mut self) -> u32 {
+            let mut x = self.0;
+            x ^= x << 13;
+            x ^= x >> 17;
+            x ^= x << 5;
+            self.0 = x;
+            x
+        }
+    }
+
+    #[test]
+    fn random_ops() {
+        for seed in 0..20 {
+            // never 0, xorshift would stay there
+            let mut rng = XorShift(0x9e37_79b9 ^ seed);
+            let mut store = Store::mount(Three::new()).unwrap();
+            let mut model = Model::new();
+            for step in 0..300 {
+                let key = KEYS[rng.next_u32() as usize % KEYS.len()];
+                let op = match rng.next_u32() % 8 {
+                    0 => Op::Remove(key),
+                    7 => {
+                        store = Store::mount(store.release()).unwrap();
+                        continue;
+                    }
+                    _ => {
+                        let len = rng.next_u32() as usize % 30;
+                        Op::Set(key, value(len, rng.next_u32() as u8))
+                    }
+                };
+                op.run(&mut store).unwrap();
+                op.model(&mut model);
+                assert_eq!(contents(&mut store), model, "seed {} step {}", seed, step);
+            }
+        }
+    }
+}
@@ -1,4 +1,28 @@
 #![no_std]
 
+pub mod binary_info;
+pub mod board;
+pub mod bootrom;
+pub mod bytes;
+pub mod clocks;
+#[cfg(feature = "defmt")]
+pub mod defmt_logger;
+pub mod divider;
+pub mod dividers;
+pub mod flash;
 pub mod gpio;
+pub mod hid;
+pub mod interp;
+pub mod kv;
+pub mod multicore;
+pub mod panic;
+pub mod power;
+pub mod pwm;
+pub mod rng;
+pub mod rtc;
 pub mod spi;
+pub mod stdio;
+pub mod sync;
+pub mod uart;
+pub mod usb;
+pub mod watchdog;
@@ -0,0 +1,716 @@
+//! Core 1: start it with a closure, talk to it through the SIO FIFOs, and pause
+//! it while core 0 writes to flash.
+//!
+//! Needs pico_multicore linked in, `target_link_libraries(app ... pico_multicore)`.
+//!
+//! ```ignore
+//! static CORE1_STACK: Stack<1024> = Stack::new();
+//!
+//! let to_core1 = Channel::<u32>::new();
+//! multicore::launch_core1(CORE1_STACK.take().unwrap(), move || {
+//!     let from_core0 = Channel::<u32>::new();
+//!     loop {
+//!         render(from_core0.recv());
+//!     }
+//! });
+//! to_core1.send(42);
+//! ```
+//!
+//! Each core has its own receive FIFO, 8 words deep: `send` goes to the other
+//! core, `recv` takes what the other core sent.
+//!
+//! `LOCKOUT_MAGIC_START` and `LOCKOUT_MAGIC_END` are reserved when lockout is
+//! used, don't send them as data.
+
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::mem;
+use core::ptr::{self, addr_of_mut};
+
+use rpi_pico_sdk_sys::*;
+
+/// Words the lockout handshake uses, same values as pico-sdk
+pub const LOCKOUT_MAGIC_START: u32 = 0x73a8_831e;
+pub const LOCKOUT_MAGIC_END: u32 = LOCKOUT_MAGIC_START ^ 1;
+
+/// Words buffered per core when the FIFO is read by the lockout handling
+pub const STASH_SIZE: usize = 16;
+
+/// Smallest stack left after the closure is moved onto it
+const MIN_STACK_BYTES: usize = 256;
+
+/// The core the caller runs on, 0 or 1.
+#[inline]
+pub fn core_id() -> u32 {
+    unsafe { get_core_num() }
+}
+
+/// Stack memory for core 1, to be kept in a `static`.
+pub struct Stack<const N: usize> {
+    mem: UnsafeCell<[u32; N]>,
+    taken: UnsafeCell<bool>,
+}
+
+// handed out once, by take()
+unsafe impl<const N: usize> Sync for Stack<N> {}
+
+impl<const N: usize> Stack<N> {
+    pub const fn new() -> Self {
+        Stack {
+            mem: UnsafeCell::new([0; N]),
+            taken: UnsafeCell::new(false),
+        }
+    }
+
+    /// The memory, `None` after the first call. Call it from core 0.
+    #[allow(clippy::mut_from_ref)] // guarded by `taken`
+    pub fn take(&'static self) -> Option<&'static mut [u32]> {
+        unsafe {
+            let status = save_and_disable_interrupts();
+            let taken = mem::replace(&mut *self.taken.get(), true);
+            restore_interrupts(status);
+            if taken {
+                None
+            } else {
+                Some(&mut *self.mem.get())
+            }
+        }
+    }
+}
+
+impl<const N: usize> Default for Stack<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where core 1 finds its closure, set right before launching it
+static mut CORE1_ENTRY: *mut () = ptr::null_mut();
+
+/// Run `entry` on core 1, with `stack` as its stack.
+///
+/// Core 1 must be waiting in the bootrom: right after boot, or after
+/// `reset_core1()`. If `entry` returns, core 1 sleeps.
+///
+/// The closure is moved to the top of `stack`, panics if less than 256 bytes
+/// are left below it.
+pub fn launch_core1<F>(stack: &'static mut [u32], entry: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    let bottom = stack.as_mut_ptr() as usize;
+    let top = bottom + mem::size_of_val(stack);
+    // keeps the initial SP 8-byte aligned, as the AAPCS wants
+    let align = mem::align_of::<F>().max(8);
+    let at = top.saturating_sub(mem::size_of::<F>()) & !(align - 1);
+    assert!(at >= bottom + MIN_STACK_BYTES, "core 1 stack too small");
+
+    unsafe {
+        ptr::write(at as *mut F, entry);
+        CORE1_ENTRY = at as *mut ();
+        __dmb();
+        multicore_launch_core1_with_stack(
+            Some(core1_trampoline::<F>),
+            bottom as *mut u32,
+            at - bottom,
+        );
+    }
+}
+
+unsafe extern "C" fn core1_trampoline<F: FnOnce()>() {
+    let entry = ptr::read(CORE1_ENTRY as *mut F);
+    entry();
+    loop {
+        __wfe();
+    }
+}
+
+/// Put core 1 back into the bootrom, ready for `launch_core1()`.
+pub fn reset_core1() {
+    unsafe {
+        multicore_reset_core1();
+        VICTIM[1] = false;
+        STASH[1] = Stash::new();
+    }
+}
+
+// ---- FIFO ----
+
+/// Words taken out of the FIFO before `pop()` asked for them
+struct Stash {
+    buf: [u32; STASH_SIZE],
+    head: usize,
+    len: usize,
+    /// The victim IRQ was turned off because the stash was full
+    irq_masked: bool,
+}
+
+impl Stash {
+    const fn new() -> Self {
+        Stash {
+            buf: [0; STASH_SIZE],
+            head: 0,
+            len: 0,
+            irq_masked: false,
+        }
+    }
+
+    #[inline(always)]
+    fn is_full(&self) -> bool {
+        self.len == STASH_SIZE
+    }
+
+    #[inline(always)]
+    fn push(&mut self, word: u32) -> bool {
+        if self.is_full() {
+            return false;
+        }
+        self.buf[(self.head + self.len) % STASH_SIZE] = word;
+        self.len += 1;
+        true
+    }
+
+    fn pop(&mut self) -> Option<u32> {
+        if self.len == 0 {
+            return None;
+        }
+        let word = self.buf[self.head];
+        self.head = (self.head + 1) % STASH_SIZE;
+        self.len -= 1;
+        Some(word)
+    }
+}
+
+/// One per core, only touched by its core with interrupts off
+static mut STASH: [Stash; 2] = [Stash::new(), Stash::new()];
+/// `lockout_victim_init()` was called on the core
+static mut VICTIM: [bool; 2] = [false, false];
+
+fn fifo_irq_num(core: u32) -> uint {
+    SIO_IRQ_PROC0 + core
+}
+
+/// Run `f` on this core's stash, interrupts off.
+fn with_stash<R>(f: impl FnOnce(&mut Stash) -> R) -> R {
+    unsafe {
+        let status = save_and_disable_interrupts();
+        let r = f(&mut *addr_of_mut!(STASH[core_id() as usize]));
+        restore_interrupts(status);
+        r
+    }
+}
+
+/// Send a word to the other core, waiting while its FIFO is full.
+/// Interrupts stay on while waiting.
+pub fn push(word: u32) {
+    while !try_push(word) {}
+}
+
+/// Send a word if there is room for it.
+pub fn try_push(word: u32) -> bool {
+    unsafe {
+        // interrupts off, so a handler can't take the free slot in between
+        let status = save_and_disable_interrupts();
+        let ready = multicore_fifo_wready();
+        if ready {
+            multicore_fifo_push_unchecked(word);
+        }
+        restore_interrupts(status);
+        ready
+    }
+}
+
+/// A word from the other core, if one is waiting.
+pub fn try_pop() -> Option<u32> {
+    with_stash(|stash| unsafe {
+        if let Some(word) = stash.pop() {
+            if stash.irq_masked {
+                stash.irq_masked = false;
+                irq_set_enabled(fifo_irq_num(core_id()), true);
+            }
+            return Some(word);
+        }
+        while multicore_fifo_rvalid() {
+            match multicore_fifo_pop_unchecked() {
+                LOCKOUT_MAGIC_START if VICTIM[core_id() as usize] => lockout_victim(stash),
+                // a late echo, after a lockout timed out
+                LOCKOUT_MAGIC_START | LOCKOUT_MAGIC_END => {}
+                word => return Some(word),
+            }
+        }
+        None
+    })
+}
+
+/// Wait for a word from the other core.
+pub fn pop() -> u32 {
+    loop {
+        if let Some(word) = try_pop() {
+            return word;
+        }
+        // woken by the other core's SEV after a push, or by the FIFO IRQ
+        __wfe();
+    }
+}
+
+/// Wait up to `timeout_us` for a word from the other core.
+pub fn pop_timeout_us(timeout_us: u64) -> Option<u32> {
+    let deadline = unsafe { time_us_64() } + timeout_us;
+    loop {
+        if let Some(word) = try_pop() {
+            return Some(word);
+        }
+        if unsafe { time_us_64() } >= deadline {
+            return None;
+        }
+    }
+}
+
+/// Throw away everything waiting to be received.
+pub fn drain() {
+    while try_pop().is_some() {}
+}
+
+/// Something is waiting to be received.
+pub fn has_data() -> bool {
+    with_stash(|stash| stash.len != 0) || unsafe { multicore_fifo_rvalid() }
+}
+
+// ---- typed channel ----
+
+/// A value sent as a fixed sequence of FIFO words.
+///
+/// Both cores must agree on the type, decoding never fails: a mismatch gives
+/// garbage values, not undefined behaviour.
+pub trait Message: Sized {
+    fn encode(self, push: &mut impl FnMut(u32));
+    fn decode(pop: &mut impl FnMut() -> u32) -> Self;
+}
+
+/// `$word` is `u32` or `i32`, for the sign extension of `isize` on a 64 bit
+/// host
+macro_rules! impl_message_word {
+    ($word:ty: $($ty:ty),*) => {
+        $(
+            impl Message for $ty {
+                fn encode(self, push: &mut impl FnMut(u32)) {
+                    push(self as u32);
+                }
+
+                fn decode(pop: &mut impl FnMut() -> u32) -> Self {
+                    pop() as $word as $ty
+                }
+            }
+        )*
+    };
+}
+
+impl_message_word!(u32: u8, u16, u32, usize);
+impl_message_word!(i32: i8, i16, i32, isize);
+
+impl Message for bool {
+    fn encode(self, push: &mut impl FnMut(u32)) {
+        push(self as u32);
+    }
+
+    fn decode(pop: &mut impl FnMut() -> u32) -> Self {
+        pop() != 0
+    }
+}
+
+impl Message for char {
+    fn encode(self, push: &mut impl FnMut(u32)) {
+        push(self as u32);
+    }
+
+    fn decode(pop: &mut impl FnMut() -> u32) -> Self {
+        core::char::from_u32(pop()).unwrap_or(core::char::REPLACEMENT_CHARACTER)
+    }
+}
+
+impl Message for f32 {
+    fn encode(self, push: &mut impl FnMut(u32)) {
+        push(self.to_bits());
+    }
+
+    fn decode(pop: &mut impl FnMut() -> u32) -> Self {
+        f32::from_bits(pop())
+    }
+}
+
+impl Message for u64 {
+    fn encode(self, push: &mut impl FnMut(u32)) {
+        push(self as u32);
+        push((self >> 32) as u32);
+    }
+
+    fn decode(pop: &mut impl FnMut() -> u32) -> Self {
+        let lo = pop() as u64;
+        let hi = pop() as u64;
+        hi << 32 | lo
+    }
+}
+
+impl Message for i64 {
+    fn encode(self, push: &mut impl FnMut(u32)) {
+        (self as u64).encode(push);
+    }
+
+    fn decode(pop: &mut impl FnMut() -> u32) -> Self {
+        u64::decode(pop) as i64
+    }
+}
+
+impl Message for f64 {
+    fn encode(self, push: &mut impl FnMut(u32)) {
+        self.to_bits().encode(push);
+    }
+
+    fn decode(pop: &mut impl FnMut() -> u32) -> Self {
+        f64::from_bits(u64::decode(pop))
+    }
+}
+
+impl Message for () {
+    fn encode(self, _push: &mut impl FnMut(u32)) {}
+
+    fn decode(_pop: &mut impl FnMut() -> u32) -> Self {}
+}
+
+impl<T: Message> Message for Option<T> {
+    fn encode(self, push: &mut impl FnMut(u32)) {
+        match self {
+            Some(value) => {
+                push(1);
+                value.encode(push);
+            }
+            None => push(0),
+        }
+    }
+
+    fn decode(pop: &mut impl FnMut() -> u32) -> Self {
+        if pop() != 0 {
+            Some(T::decode(pop))
+        } else {
+            None
+        }
+    }
+}
+
+macro_rules! impl_message_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: Message),+> Message for ($($name,)+) {
+            #[allow(non_snake_case)]
+            fn encode(self, push: &mut impl FnMut(u32)) {
+                let ($($name,)+) = self;
+                $($name.encode(push);)+
+            }
+
+            fn decode(pop: &mut impl FnMut() -> u32) -> Self {
+                ($($name::decode(pop),)+)
+            }
+        }
+    };
+}
+
+impl_message_tuple!(A);
+impl_message_tuple!(A, B);
+impl_message_tuple!(A, B, C);
+impl_message_tuple!(A, B, C, D);
+
+/// Typed messages to and from the other core, over the SIO FIFOs.
+///
+/// Only a handle: create one on each core, with the same `T`. Interrupts stay
+/// on while waiting for the FIFO, so multi-word messages sent from both an
+/// interrupt handler and the code it interrupts, on the same core, can
+/// interleave: send (and receive) from one of them only.
+pub struct Channel<T> {
+    _marker: PhantomData<fn(T) -> T>,
+}
+
+impl<T: Message> Channel<T> {
+    pub const fn new() -> Self {
+        Channel {
+            _marker: PhantomData,
+        }
+    }
+
+    /// Send, waiting while the other core's FIFO is full.
+    pub fn send(&self, msg: T) {
+        msg.encode(&mut push);
+    }
+
+    /// Wait for a message.
+    pub fn recv(&self) -> T {
+        T::decode(&mut pop)
+    }
+
+    /// A message, if one has started to arrive. Only the first word is not
+    /// waited for: once it's there, this blocks like `recv()` until the rest
+    /// of the message arrives.
+    pub fn try_recv(&self) -> Option<T> {
+        if has_data() {
+            Some(self.recv())
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Message> Default for Channel<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ---- lockout ----
+
+/// Let the other core pause this one with `lockout()`. Call it on the core to
+/// be paused, usually core 1.
+///
+/// Takes over the SIO FIFO interrupt of this core. Words arriving are kept
+/// for `pop()`/`recv()` (up to `STASH_SIZE`, then the FIFO fills up again).
+pub fn lockout_victim_init() {
+    let core = core_id();
+    unsafe {
+        VICTIM[core as usize] = true;
+        let irq = fifo_irq_num(core);
+        irq_set_exclusive_handler(irq, Some(fifo_irq));
+        irq_set_enabled(irq, true);
+    }
+}
+
+/// Whether `core` called `lockout_victim_init()`, so `lockout()` from the
+/// other core gets an answer.
+pub fn is_lockout_victim(core: u32) -> bool {
+    unsafe { ptr::read_volatile(addr_of_mut!(VICTIM[core as usize])) }
+}
+
+unsafe extern "C" fn fifo_irq() {
+    let core = core_id();
+    let stash = &mut *addr_of_mut!(STASH[core as usize]);
+    multicore_fifo_clear_irq();
+    while multicore_fifo_rvalid() {
+        if stash.is_full() {
+            // try_pop() turns it back on
+            stash.irq_masked = true;
+            irq_set_enabled(fifo_irq_num(core), false);
+            return;
+        }
+        match multicore_fifo_pop_unchecked() {
+            LOCKOUT_MAGIC_START => lockout_victim(stash),
+            LOCKOUT_MAGIC_END => {}
+            word => {
+                stash.push(word);
+            }
+        }
+    }
+}
+
+/// Ack the lockout and spin until it ends. In RAM, flash may be unusable
+/// meanwhile.
+///
+/// NOTE: the helpers it uses are only inlined with optimizations on, a debug
+/// build may still call into flash from here.
+#[inline(never)]
+#[link_section = ".time_critical.multicore_lockout_victim"]
+unsafe fn lockout_victim(stash: &mut Stash) {
+    let status = save_and_disable_interrupts();
+    let sio = SIO_BASE as *const sio_hw_t;
+    let fifo_st = (*sio).fifo_st.as_ptr();
+    let fifo_wr = (*sio).fifo_wr.as_ptr();
+    let fifo_rd = (*sio).fifo_rd.as_ptr();
+
+    while ptr::read_volatile(fifo_st) & SIO_FIFO_ST_RDY_BITS == 0 {}
+    ptr::write_volatile(fifo_wr, LOCKOUT_MAGIC_START);
+    __sev();
+    loop {
+        while ptr::read_volatile(fifo_st) & SIO_FIFO_ST_VLD_BITS == 0 {
+            __wfe();
+        }
+        match ptr::read_volatile(fifo_rd) {
+            LOCKOUT_MAGIC_END => break,
+            word => {
+                stash.push(word);
+            }
+        }
+    }
+    while ptr::read_volatile(fifo_st) & SIO_FIFO_ST_RDY_BITS == 0 {}
+    ptr::write_volatile(fifo_wr, LOCKOUT_MAGIC_END);
+    __sev();
+
+    restore_interrupts(status);
+}
+
+/// The other core is paused until this is dropped.
+pub struct Lockout {
+    // not Send, ends on the core that started it
+    _marker: PhantomData<*mut ()>,
+}
+
+impl Drop for Lockout {
+    fn drop(&mut self) {
+        handshake(LOCKOUT_MAGIC_END, None);
+    }
+}
+
+/// Pause the other core, which must have called `lockout_victim_init()`.
+///
+/// It spins in RAM with interrupts off until the `Lockout` is dropped, e.g.
+/// for erasing or programming flash. Blocks forever if the other core never
+/// answers, see `lockout_timeout_us()`.
+pub fn lockout() -> Lockout {
+    handshake(LOCKOUT_MAGIC_START, None);
+    Lockout {
+        _marker: PhantomData,
+    }
+}
+
+/// `lockout()`, giving up after `timeout_us`.
+pub fn lockout_timeout_us(timeout_us: u64) -> Option<Lockout> {
+    let deadline = unsafe { time_us_64() } + timeout_us;
+    match handshake(LOCKOUT_MAGIC_START, Some(deadline)) {
+        Handshake::Done => Some(Lockout {
+            _marker: PhantomData,
+        }),
+        Handshake::NotSent => None,
+        Handshake::NoEcho => {
+            // the other core may still get to it, let it go right away then
+            push(LOCKOUT_MAGIC_END);
+            None
+        }
+    }
+}
+
+enum Handshake {
+    Done,
+    NotSent,
+    NoEcho,
+}
+
+/// Send `magic`, wait for it to be echoed. Other words are kept for `pop()`,
+/// dropped if there is no room.
+fn handshake(magic: u32, deadline: Option<u64>) -> Handshake {
+    let expired = || match deadline {
+        Some(deadline) => (unsafe { time_us_64() }) >= deadline,
+        None => false,
+    };
+    let irq = fifo_irq_num(core_id());
+    unsafe {
+        // our own victim handler must not take the echo
+        let enabled = irq_is_enabled(irq);
+        irq_set_enabled(irq, false);
+
+        let mut result = Handshake::NoEcho;
+        while !try_push(magic) {
+            if expired() {
+                irq_set_enabled(irq, enabled);
+                return Handshake::NotSent;
+            }
+        }
+        while !expired() {
+            if !multicore_fifo_rvalid() {
+                continue;
+            }
+            match multicore_fifo_pop_unchecked() {
+                word if word == magic => {
+                    result = Handshake::Done;
+                    break;
+                }
+                LOCKOUT_MAGIC_START | LOCKOUT_MAGIC_END => {}
+                word => {
+                    with_stash(|stash| stash.push(word));
+                }
+            }
+        }
+
+        irq_set_enabled(irq, enabled);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use std::vec::Vec;
+
+    fn words<T: Message>(msg: T) -> Vec<u32> {
+        let mut out = Vec::new();
+        msg.encode(&mut |word| out.push(word));
+        out
+    }
+
+    /// Decodes all of `words`, nothing more
+    fn decode<T: Message>(words: &[u32]) -> T {
+        let mut words = words.iter();
+        let msg = T::decode(&mut || *words.next().expect("read past the message"));
+        assert_eq!(words.len(), 0, "message not fully read");
+        msg
+    }
+
+    fn round_trip<T: Message + Copy + PartialEq + core::fmt::Debug>(msg: T) {
+        assert_eq!(decode::<T>(&words(msg)), msg);
+    }
+
+    #[test]
+    fn single_words() {
+        assert_eq!(words(0x1234_5678u32), [0x1234_5678]);
+        assert_eq!(words(-1i8), [0xffff_ffff]);
+        assert_eq!(words(true), [1]);
+        assert_eq!(words('é'), [0xe9]);
+        assert_eq!(words(1.5f32), [0x3fc0_0000]);
+        assert_eq!(words(()), []);
+
+        round_trip(u8::MAX);
+        round_trip(i16::MIN);
+        round_trip(-7isize);
+        round_trip(u32::MAX as usize);
+        round_trip(false);
+        round_trip('🦀');
+        round_trip(-0.25f32);
+        assert!(decode::<f32>(&words(f32::NAN)).is_nan());
+    }
+
+    #[test]
+    fn decode_garbage() {
+        assert!(decode::<bool>(&[2]));
+        assert_eq!(decode::<u8>(&[0x1ff]), 0xff);
+        // surrogates aren't chars
+        assert_eq!(decode::<char>(&[0xd800]), core::char::REPLACEMENT_CHARACTER);
+        assert_eq!(
+            decode::<char>(&[0x11_0000]),
+            core::char::REPLACEMENT_CHARACTER
+        );
+    }
+
+    #[test]
+    fn two_words() {
+        // low word first
+        assert_eq!(words(0x0123_4567_89ab_cdefu64), [0x89ab_cdef, 0x0123_4567]);
+        assert_eq!(words(-2i64), [0xffff_fffe, 0xffff_ffff]);
+        assert_eq!(words(1.0f64), [0, 0x3ff0_0000]);
+
+        round_trip(u64::MAX);
+        round_trip(i64::MIN);
+        round_trip(core::f64::consts::PI);
+    }
+
+    #[test]
+    fn options_and_tuples() {
+        assert_eq!(words(None::<u64>), [0]);
+        assert_eq!(words(Some(7u64)), [1, 7, 0]);
+        assert_eq!(words(Some(None::<u8>)), [1, 0]);
+        assert_eq!(words((1u8, 2u64, true)), [1, 2, 0, 1]);
+        assert_eq!(
+            words((Some(3u32), (), -1i32, 'a')),
+            [1, 3, 0xffff_ffff, 0x61]
+        );
+
+        round_trip(Some(Some(5i16)));
+        round_trip(None::<(u32, f32)>);
+        round_trip((u64::MAX, None::<char>, Some(-1.5f64)));
+        round_trip(((1u8,), (2u16, 3u32), ()));
+    }
+}
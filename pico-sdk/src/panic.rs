@@ -0,0 +1,191 @@
+//! Panic handler, enabled by the `panic-handler` feature.
+//!
+//! What it does is picked by features, in this order:
+//!
+//! - `panic-print`: print the message through stdio (UART or USB, whatever
+//!   `stdio_init_all()` set up)
+//! - `panic-persist`: keep the message in RAM that survives a reboot (not a
+//!   power cycle), read it back with `last_panic()`
+//! - `panic-blink`: blink `PICO_DEFAULT_LED_PIN`, 3 short blinks then a pause
+//! - `panic-reboot`: reboot through the watchdog, after a few blink rounds if
+//!   `panic-blink` is on as well
+//!
+//! Without `panic-reboot` it hangs, like the handlers in the examples did.
+
+#[cfg(feature = "panic-persist")]
+use core::fmt;
+#[cfg(any(feature = "panic-print", feature = "panic-persist"))]
+use core::fmt::Write;
+#[cfg(feature = "panic-handler")]
+use core::panic::PanicInfo;
+#[cfg(feature = "panic-persist")]
+use core::ptr::{self, addr_of, addr_of_mut};
+
+#[cfg(feature = "panic-handler")]
+use rpi_pico_sdk_sys::*;
+
+/// Delay before the watchdog reboots, time for the stdio output to drain.
+pub const REBOOT_DELAY_MS: u32 = 100;
+
+/// Max message length kept by `panic-persist`, the rest is cut off.
+pub const PERSIST_SIZE: usize = 256;
+
+/// Blink rounds before rebooting, when both `panic-blink` and `panic-reboot` are on.
+pub const BLINK_ROUNDS_BEFORE_REBOOT: u32 = 5;
+
+#[cfg(feature = "panic-persist")]
+const PERSIST_MAGIC: u32 = 0x5041_4e43; // "PANC"
+
+#[cfg(feature = "panic-persist")]
+#[repr(C)]
+struct Persisted {
+    magic: u32,
+    len: u32,
+    /// RAM is random after power on, the magic alone is not enough
+    check: u32,
+    buf: [u8; PERSIST_SIZE],
+}
+
+/// Not zeroed by the startup code, see `.uninitialized_data` in the linker script.
+#[cfg(feature = "panic-persist")]
+#[link_section = ".uninitialized_data"]
+static mut PERSISTED: core::mem::MaybeUninit<Persisted> = core::mem::MaybeUninit::uninit();
+
+#[cfg(feature = "panic-persist")]
+fn check_value(len: u32, buf: &[u8]) -> u32 {
+    buf.iter()
+        .fold(PERSIST_MAGIC ^ len, |acc, &b| acc.rotate_left(5) ^ b as u32)
+}
+
+/// The panic message from before the last reboot, if there was one.
+#[cfg(feature = "panic-persist")]
+pub fn last_panic() -> Option<&'static str> {
+    unsafe {
+        let p = addr_of!(PERSISTED) as *const Persisted;
+        if ptr::read_volatile(addr_of!((*p).magic)) != PERSIST_MAGIC {
+            return None;
+        }
+        let len = ptr::read_volatile(addr_of!((*p).len));
+        if len as usize > PERSIST_SIZE {
+            return None;
+        }
+        let buf = &(&(*p).buf)[..len as usize];
+        if ptr::read_volatile(addr_of!((*p).check)) != check_value(len, buf) {
+            return None;
+        }
+        core::str::from_utf8(buf).ok()
+    }
+}
+
+/// Forget the persisted panic message, so it's reported only once.
+#[cfg(feature = "panic-persist")]
+pub fn clear_last_panic() {
+    unsafe {
+        let p = addr_of_mut!(PERSISTED) as *mut Persisted;
+        ptr::write_volatile(addr_of_mut!((*p).magic), 0);
+    }
+}
+
+/// Writes into the persisted buffer, cut off at a char boundary when full.
+#[cfg(feature = "panic-persist")]
+struct PersistWriter {
+    p: *mut Persisted,
+    len: usize,
+}
+
+#[cfg(feature = "panic-persist")]
+impl Write for PersistWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let buf = unsafe { &mut (*self.p).buf };
+        let mut n = s.len().min(PERSIST_SIZE - self.len);
+        while !s.is_char_boundary(n) {
+            n -= 1;
+        }
+        buf[self.len..self.len + n].copy_from_slice(&s.as_bytes()[..n]);
+        self.len += n;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "panic-persist")]
+fn persist(info: &PanicInfo) {
+    unsafe {
+        let p = addr_of_mut!(PERSISTED) as *mut Persisted;
+        ptr::write_volatile(addr_of_mut!((*p).magic), 0);
+        let mut w = PersistWriter { p, len: 0 };
+        let _ = write!(w, "{}", info);
+        let len = w.len as u32;
+        ptr::write_volatile(addr_of_mut!((*p).len), len);
+        ptr::write_volatile(
+            addr_of_mut!((*p).check),
+            check_value(len, &(&(*p).buf)[..w.len]),
+        );
+        ptr::write_volatile(addr_of_mut!((*p).magic), PERSIST_MAGIC);
+    }
+}
+
+#[cfg(feature = "panic-blink")]
+fn blink_round() {
+    const PIN_LED: uint = PICO_DEFAULT_LED_PIN;
+    unsafe {
+        gpio_init(PIN_LED);
+        gpio_set_dir(PIN_LED, GPIO_OUT);
+        for _ in 0..3 {
+            gpio_put(PIN_LED, true);
+            busy_wait_us_32(100_000);
+            gpio_put(PIN_LED, false);
+            busy_wait_us_32(100_000);
+        }
+        busy_wait_us_32(600_000);
+    }
+}
+
+/// SIO spin lock taken by the first panic and never released, pico-sdk's
+/// `PICO_SPINLOCK_ID_OS2`. `runtime_init()` releases it on the next boot.
+pub const PANIC_SPINLOCK: u32 = 15;
+
+/// Whether this is the first panic, on either core: a plain flag could be
+/// set by both, and thumbv6m has no atomic swap.
+#[cfg(feature = "panic-handler")]
+fn first_panic() -> bool {
+    // reading a spin lock claims it, 0 if it was taken already
+    let lock = (SIO_BASE + SIO_SPINLOCK0_OFFSET + 4 * PANIC_SPINLOCK) as *const u32;
+    unsafe { core::ptr::read_volatile(lock) != 0 }
+}
+
+#[cfg(feature = "panic-handler")]
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    // a panic while formatting the first one, or one on the other core
+    // meanwhile: skip straight to the end
+    let nested = !first_panic();
+
+    if !nested {
+        #[cfg(feature = "panic-persist")]
+        persist(info);
+
+        #[cfg(feature = "panic-print")]
+        {
+            let _ = writeln!(crate::stdio::Stdout, "\n{}", info);
+            crate::stdio::flush();
+        }
+    }
+    let _ = info;
+
+    #[cfg(all(feature = "panic-blink", feature = "panic-reboot"))]
+    for _ in 0..BLINK_ROUNDS_BEFORE_REBOOT {
+        blink_round();
+    }
+
+    #[cfg(feature = "panic-reboot")]
+    {
+        unsafe { busy_wait_us_32(REBOOT_DELAY_MS * 1000) };
+        crate::watchdog::reboot();
+    }
+
+    #[cfg(not(feature = "panic-reboot"))]
+    loop {
+        #[cfg(feature = "panic-blink")]
+        blink_round();
+    }
+}
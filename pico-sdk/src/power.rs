@@ -0,0 +1,318 @@
+//! Low-power modes: sleep until an RTC alarm, dormant until a GPIO edge.
+//!
+//! ```ignore
+//! let mut rtc = Rtc::new();
+//! rtc.set_datetime(&DateTime::new(2021, 3, 14, 15, 9, 26)?)?;
+//! loop {
+//!     redraw(&mut epd);
+//!     let next = (rtc.now()?.second + 1) % 60;
+//!     power::sleep_until_rtc_alarm(&mut rtc, Alarm::new().second(next))?;
+//! }
+//! ```
+//!
+//! Both run the chip from the crystal with the PLLs off while asleep, then
+//! put the clocks back as `LowPower::restore()` has them, pico-sdk's defaults
+//! unless set. Peripherals set up for other clocks need setting up again.
+//!
+//! Only core 0 is handled: core 1 should be idle, in `__wfe()` or `__wfi()`.
+//! Add `hardware_rtc` to the app's `target_link_libraries` for the RTC alarm.
+
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use rpi_pico_sdk_sys::*;
+
+use crate::clocks::{self, ClockConfig};
+use crate::rtc::{self, Alarm, Rtc};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Not a GPIO, 30 and up.
+    Pin(uint),
+    Rtc(rtc::Error),
+    /// `LowPower::restore()` can't be applied.
+    Clocks(clocks::Error),
+}
+
+impl From<rtc::Error> for Error {
+    fn from(err: rtc::Error) -> Self {
+        Error::Rtc(err)
+    }
+}
+
+impl From<clocks::Error> for Error {
+    fn from(err: clocks::Error) -> Self {
+        Error::Clocks(err)
+    }
+}
+
+/// What wakes the chip up from dormant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    Rising,
+    Falling,
+    Both,
+    /// Level triggered, wakes right away if the pin is already high.
+    High,
+    /// Level triggered, wakes right away if the pin is already low.
+    Low,
+}
+
+impl Edge {
+    fn events(self) -> u32 {
+        match self {
+            Edge::Rising => GPIO_IRQ_EDGE_RISE,
+            Edge::Falling => GPIO_IRQ_EDGE_FALL,
+            Edge::Both => GPIO_IRQ_EDGE_RISE | GPIO_IRQ_EDGE_FALL,
+            Edge::High => GPIO_IRQ_LEVEL_HIGH,
+            Edge::Low => GPIO_IRQ_LEVEL_LOW,
+        }
+    }
+}
+
+/// Peripherals that can keep their clocks while sleeping.
+///
+/// Not USB or the ADC: clk_usb and clk_adc are stopped while asleep, USB
+/// needs its 48 MHz, and the ADC would sample 4 times slower from the crystal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Peripheral {
+    Dma,
+    I2c0,
+    I2c1,
+    Pio0,
+    Pio1,
+    Pwm,
+    /// Always kept by `sleep_until_rtc_alarm()`.
+    Rtc,
+    Spi0,
+    Spi1,
+    /// The µs timer, for pico-sdk's alarms.
+    Timer,
+    Uart0,
+    Uart1,
+    Watchdog,
+}
+
+impl Peripheral {
+    /// `(SLEEP_EN0, SLEEP_EN1)` bits, both the clk_sys bus clock and the
+    /// peripheral clock
+    const fn bits(self) -> (u32, u32) {
+        match self {
+            Peripheral::Dma => (CLOCKS_SLEEP_EN0_CLK_SYS_DMA_BITS, 0),
+            Peripheral::I2c0 => (CLOCKS_SLEEP_EN0_CLK_SYS_I2C0_BITS, 0),
+            Peripheral::I2c1 => (CLOCKS_SLEEP_EN0_CLK_SYS_I2C1_BITS, 0),
+            Peripheral::Pio0 => (CLOCKS_SLEEP_EN0_CLK_SYS_PIO0_BITS, 0),
+            Peripheral::Pio1 => (CLOCKS_SLEEP_EN0_CLK_SYS_PIO1_BITS, 0),
+            Peripheral::Pwm => (CLOCKS_SLEEP_EN0_CLK_SYS_PWM_BITS, 0),
+            Peripheral::Rtc => (
+                CLOCKS_SLEEP_EN0_CLK_SYS_RTC_BITS | CLOCKS_SLEEP_EN0_CLK_RTC_RTC_BITS,
+                0,
+            ),
+            Peripheral::Spi0 => (
+                CLOCKS_SLEEP_EN0_CLK_SYS_SPI0_BITS | CLOCKS_SLEEP_EN0_CLK_PERI_SPI0_BITS,
+                0,
+            ),
+            Peripheral::Spi1 => (
+                CLOCKS_SLEEP_EN0_CLK_SYS_SPI1_BITS | CLOCKS_SLEEP_EN0_CLK_PERI_SPI1_BITS,
+                0,
+            ),
+            Peripheral::Timer => (0, CLOCKS_SLEEP_EN1_CLK_SYS_TIMER_BITS),
+            Peripheral::Uart0 => (
+                0,
+                CLOCKS_SLEEP_EN1_CLK_SYS_UART0_BITS | CLOCKS_SLEEP_EN1_CLK_PERI_UART0_BITS,
+            ),
+            Peripheral::Uart1 => (
+                0,
+                CLOCKS_SLEEP_EN1_CLK_SYS_UART1_BITS | CLOCKS_SLEEP_EN1_CLK_PERI_UART1_BITS,
+            ),
+            Peripheral::Watchdog => (0, CLOCKS_SLEEP_EN1_CLK_SYS_WATCHDOG_BITS),
+        }
+    }
+}
+
+/// Set by the RTC alarm, other interrupts wake the core up too
+static RTC_WOKE: AtomicBool = AtomicBool::new(false);
+
+fn on_rtc_alarm() {
+    RTC_WOKE.store(true, Ordering::Release);
+}
+
+/// How to sleep: the clocks kept while asleep and the clocks after waking up.
+#[derive(Debug, Clone, Copy)]
+pub struct LowPower {
+    sleep_en0: u32,
+    sleep_en1: u32,
+    restore: ClockConfig,
+}
+
+impl LowPower {
+    /// Every peripheral clock gated while asleep, pico-sdk's default clocks after.
+    pub const fn new() -> Self {
+        LowPower {
+            sleep_en0: 0,
+            sleep_en1: 0,
+            restore: ClockConfig::pico_default(),
+        }
+    }
+
+    /// Keep `peripheral` clocked while sleeping, at the 12 MHz of the crystal.
+    /// Doesn't matter for dormant, which stops every clock.
+    pub const fn keep(mut self, peripheral: Peripheral) -> Self {
+        let (en0, en1) = peripheral.bits();
+        self.sleep_en0 |= en0;
+        self.sleep_en1 |= en1;
+        self
+    }
+
+    /// The clocks to put back on wake up, what the app ran at before. Give
+    /// every clock and PLL, unlike `ClockConfig` in general: it's applied
+    /// with the PLLs off. Checked before going to sleep.
+    pub const fn restore(mut self, config: ClockConfig) -> Self {
+        self.restore = config;
+        self
+    }
+
+    /// Sleep until `alarm` matches, replacing any alarm handler of `rtc`.
+    ///
+    /// The RTC must be running. While asleep it runs from the crystal, 12 MHz / 256.
+    pub fn sleep_until_rtc_alarm(&self, rtc: &mut Rtc, alarm: Alarm) -> Result<(), Error> {
+        if !rtc.is_running() {
+            return Err(rtc::Error::NotRunning.into());
+        }
+        self.restore.validate()?;
+        RTC_WOKE.store(false, Ordering::Release);
+        rtc.set_alarm(alarm, on_rtc_alarm)?;
+
+        let (en0, en1) = Peripheral::Rtc.bits();
+        unsafe {
+            let saved = Saved::save();
+            run_from_xosc();
+            (*clocks_hw).sleep_en0.write(self.sleep_en0 | en0);
+            (*clocks_hw).sleep_en1.write(self.sleep_en1 | en1);
+            (*scb_hw).scr.set_bits(M0PLUS_SCR_SLEEPDEEP_BITS);
+
+            // WFI wakes up on a pending interrupt with them disabled, so the
+            // alarm can't slip in between the check and the WFI
+            loop {
+                let irq = save_and_disable_interrupts();
+                let woke = RTC_WOKE.load(Ordering::Acquire);
+                if !woke {
+                    __wfi();
+                }
+                restore_interrupts(irq);
+                if woke {
+                    break;
+                }
+            }
+
+            saved.restore();
+        }
+        self.restore_clocks()
+    }
+
+    /// Stop every clock until `pin` sees `edge`. The pin must be an input,
+    /// with a pull if nothing drives it. The RTC stops too.
+    pub fn dormant_until_gpio(&self, pin: uint, edge: Edge) -> Result<(), Error> {
+        if pin >= NUM_BANK0_GPIOS {
+            return Err(Error::Pin(pin));
+        }
+        self.restore.validate()?;
+        let events = edge.events();
+        unsafe {
+            let saved = Saved::save();
+            run_from_xosc();
+            gpio_set_dormant_irq_enabled(pin, events, true);
+            // blocks until woken up and the crystal is stable again
+            xosc_dormant();
+            gpio_acknowledge_irq(pin, events);
+            gpio_set_dormant_irq_enabled(pin, events, false);
+            saved.restore();
+        }
+        self.restore_clocks()
+    }
+
+    fn restore_clocks(&self) -> Result<(), Error> {
+        unsafe {
+            rosc_enable();
+        }
+        self.restore.apply()?;
+        Ok(())
+    }
+}
+
+impl Default for LowPower {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sleep until `alarm` matches, see `LowPower::sleep_until_rtc_alarm()`.
+pub fn sleep_until_rtc_alarm(rtc: &mut Rtc, alarm: Alarm) -> Result<(), Error> {
+    LowPower::new().sleep_until_rtc_alarm(rtc, alarm)
+}
+
+/// Dormant until `pin` sees `edge`, see `LowPower::dormant_until_gpio()`.
+pub fn dormant_until_gpio(pin: uint, edge: Edge) -> Result<(), Error> {
+    LowPower::new().dormant_until_gpio(pin, edge)
+}
+
+/// What sleeping changes besides the clock tree
+struct Saved {
+    scr: u32,
+    sleep_en0: u32,
+    sleep_en1: u32,
+}
+
+impl Saved {
+    unsafe fn save() -> Self {
+        Saved {
+            scr: (*scb_hw).scr.read(),
+            sleep_en0: (*clocks_hw).sleep_en0.read(),
+            sleep_en1: (*clocks_hw).sleep_en1.read(),
+        }
+    }
+
+    unsafe fn restore(self) {
+        (*scb_hw).scr.write(self.scr);
+        (*clocks_hw).sleep_en0.write(self.sleep_en0);
+        (*clocks_hw).sleep_en1.write(self.sleep_en1);
+    }
+}
+
+/// Everything from the crystal, PLLs and ring oscillator off, as pico-extras'
+/// `sleep_run_from_xosc()`
+unsafe fn run_from_xosc() {
+    let xosc = clocks::XOSC_FREQ.to_hz();
+    clock_configure(
+        clk_ref,
+        CLOCKS_CLK_REF_CTRL_SRC_VALUE_XOSC_CLKSRC,
+        0,
+        xosc,
+        xosc,
+    );
+    clock_configure(
+        clk_sys,
+        CLOCKS_CLK_SYS_CTRL_SRC_VALUE_CLK_REF,
+        0,
+        xosc,
+        xosc,
+    );
+    clock_stop(clk_usb);
+    clock_stop(clk_adc);
+    clock_configure(
+        clk_rtc,
+        0,
+        CLOCKS_CLK_USB_CTRL_AUXSRC_VALUE_XOSC_CLKSRC,
+        xosc,
+        46875,
+    );
+    clock_configure(
+        clk_peri,
+        0,
+        CLOCKS_CLK_PERI_CTRL_AUXSRC_VALUE_CLK_SYS,
+        xosc,
+        xosc,
+    );
+    pll_deinit(pll_sys);
+    pll_deinit(pll_usb);
+    rosc_disable();
+}
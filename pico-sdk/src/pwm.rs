@@ -0,0 +1,154 @@
+//! embedded-hal PWM over the eight PWM slices, the frequency worked out from
+//! clk_sys.
+//!
+//! ```ignore
+//! let mut pwm = Pwm::new(slice_for(25), &ClockSet::current()).with_output(25);
+//! pwm.set_period(Hertz::khz(1));
+//! pwm.set_duty(Channel::B, pwm.get_max_duty() / 4);
+//! pwm.enable(Channel::B);
+//! ```
+
+use embedded_hal as hal;
+use rpi_pico_sdk_sys::*;
+
+use crate::clocks::{ClockSet, Hertz};
+use crate::dividers::{self, PwmDividers};
+use crate::gpio::GpioFunction;
+
+/// The two outputs of a slice, a GPIO carries the one at `pin % 2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    A = 0,
+    B = 1,
+}
+
+/// The slice driving `pin`, GPIO0-15 and 16-29 map to slices 0-7 twice.
+pub const fn slice_for(pin: uint) -> uint {
+    (pin >> 1) & 7
+}
+
+pub const fn channel_for(pin: uint) -> Channel {
+    if pin & 1 == 0 {
+        Channel::A
+    } else {
+        Channel::B
+    }
+}
+
+/// A PWM slice, counting from 0 to `top` at clk_sys divided by 1 to 255 15/16.
+pub struct Pwm {
+    slice: uint,
+    clk_sys: Hertz,
+    div: PwmDividers,
+    duty: [u16; 2],
+    enabled: [bool; 2],
+}
+
+impl Pwm {
+    /// Slice `slice`, stopped, counting the full 16 bits at clk_sys.
+    pub fn new(slice: uint, clocks: &ClockSet) -> Self {
+        assert!(slice < NUM_PWM_SLICES, "no such PWM slice");
+        unsafe {
+            pwm_init(slice, &pwm_get_default_config(), false);
+        }
+        Pwm {
+            slice,
+            clk_sys: clocks.sys,
+            div: PwmDividers {
+                int: 1,
+                frac: 0,
+                top: u16::MAX,
+            },
+            duty: [0; 2],
+            enabled: [false; 2],
+        }
+    }
+
+    /// Route this slice's channel to `pin`.
+    pub fn with_output(self, pin: uint) -> Self {
+        debug_assert!(slice_for(pin) == self.slice, "not a pin of this slice");
+        unsafe {
+            gpio_set_function(pin, GpioFunction::PWM as _);
+        }
+        self
+    }
+
+    pub fn slice(&self) -> uint {
+        self.slice
+    }
+
+    /// Set the closest frequency to `freq` with the finest duty resolution,
+    /// and return it. `get_max_duty()` changes with it, set the duty again.
+    pub fn set_freq(&mut self, freq: Hertz) -> Hertz {
+        let div = dividers::pwm(self.clk_sys, freq);
+        self.set_top(div.top);
+        self.div = div;
+        unsafe {
+            pwm_set_clkdiv_int_frac(self.slice, div.int, div.frac);
+        }
+        self.freq()
+    }
+
+    pub fn freq(&self) -> Hertz {
+        self.div.freq(self.clk_sys)
+    }
+
+    /// Count from 0 to `top`, keeping the divider.
+    pub fn set_top(&mut self, top: u16) {
+        self.div.top = top;
+        unsafe {
+            pwm_set_wrap(self.slice, top);
+        }
+    }
+
+    pub fn top(&self) -> u16 {
+        self.div.top
+    }
+
+    fn update(&mut self) {
+        let level = |ch: usize| if self.enabled[ch] { self.duty[ch] } else { 0 };
+        unsafe {
+            pwm_set_both_levels(self.slice, level(0), level(1));
+            pwm_set_enabled(self.slice, self.enabled != [false; 2]);
+        }
+    }
+}
+
+impl hal::Pwm for Pwm {
+    type Channel = Channel;
+    /// The frequency, not the period
+    type Time = Hertz;
+    type Duty = u16;
+
+    fn disable(&mut self, channel: Channel) {
+        self.enabled[channel as usize] = false;
+        self.update();
+    }
+
+    fn enable(&mut self, channel: Channel) {
+        self.enabled[channel as usize] = true;
+        self.update();
+    }
+
+    fn get_period(&self) -> Hertz {
+        self.freq()
+    }
+
+    fn get_duty(&self, channel: Channel) -> u16 {
+        self.duty[channel as usize]
+    }
+
+    /// Always high at `top + 1`, which doesn't fit: `top` is as close as it gets.
+    fn get_max_duty(&self) -> u16 {
+        self.div.top
+    }
+
+    fn set_duty(&mut self, channel: Channel, duty: u16) {
+        self.duty[channel as usize] = duty;
+        self.update();
+    }
+
+    fn set_period<P: Into<Hertz>>(&mut self, period: P) {
+        self.set_freq(period.into());
+    }
+}
@@ -0,0 +1,299 @@
+//! Random numbers from the ring oscillator's RANDOMBIT, the RP2040's only
+//! entropy source.
+//!
+//! ```ignore
+//! let mut trng = Trng::rosc();
+//! let nonce = trng.next_u64();
+//! let mut rng = SmallRng::from_rng(&mut trng).unwrap();
+//! let jitter = rng.next_u32() % 100;
+//! ```
+//!
+//! RANDOMBIT samples the ROSC output: it's biased and not independent from
+//! one read to the next. `Trng` removes the bias with von Neumann's method,
+//! then conditions 64 of those bits per 32 bit word through a 64 bit mixing
+//! function, chained from word to word. A source that stops producing bits
+//! (ROSC off, or stuck) is an `Error::Stuck` rather than a hang.
+//!
+//! That's a few hundred register reads per word: for more than nonces and
+//! keys, seed a `SmallRng` (xoshiro128++) from it.
+//!
+//! `Trng` takes any `Iterator<Item = bool>` as its source, so recorded
+//! bitstreams, e.g. through `unpack()`, can be run through it on the host.
+
+use core::num::NonZeroU32;
+
+use rand_core::{impls, RngCore, SeedableRng};
+use rpi_pico_sdk_sys::*;
+
+/// Equal raw bit pairs in a row before giving up on the source. Once in
+/// 2^64 pairs from a fair source.
+const MAX_EQUAL_PAIRS: u32 = 64;
+
+/// Debiased bits conditioned into each word
+const BITS_PER_WORD: u32 = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The source kept giving the same bit.
+    Stuck,
+    /// The source ran out, for a recorded one.
+    Exhausted,
+}
+
+impl From<Error> for rand_core::Error {
+    fn from(err: Error) -> Self {
+        let code = rand_core::Error::CUSTOM_START + err as u32;
+        NonZeroU32::new(code).unwrap().into()
+    }
+}
+
+/// Raw bits from ROSC RANDOMBIT.
+#[derive(Debug, Clone, Copy)]
+pub struct RoscBits(());
+
+impl Iterator for RoscBits {
+    type Item = bool;
+
+    #[inline]
+    fn next(&mut self) -> Option<bool> {
+        Some(unsafe { (*rosc_hw).randombit.read() } & 1 != 0)
+    }
+}
+
+/// The bits of `bytes`, LSB first, e.g. a recorded RANDOMBIT stream.
+pub fn unpack(bytes: &[u8]) -> impl Iterator<Item = bool> + '_ {
+    bytes
+        .iter()
+        .flat_map(|&byte| (0..8).map(move |bit| byte & (1 << bit) != 0))
+}
+
+/// Murmur3's 64 bit finalizer: every input bit flips about half the output
+fn mix(mut x: u64) -> u64 {
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    x ^ (x >> 33)
+}
+
+/// Debiased, conditioned words from a raw bit source, ROSC by default.
+#[derive(Debug, Clone)]
+pub struct Trng<I = RoscBits> {
+    source: I,
+    pool: u64,
+}
+
+impl Trng<RoscBits> {
+    /// From ROSC RANDOMBIT. The ROSC runs from reset; while it's disabled,
+    /// as in `power`'s sleep on the crystal, every read is `Error::Stuck`.
+    pub fn rosc() -> Self {
+        Trng::new(RoscBits(()))
+    }
+}
+
+impl<I: Iterator<Item = bool>> Trng<I> {
+    pub fn new(source: I) -> Self {
+        Trng { source, pool: 0 }
+    }
+
+    pub fn free(self) -> I {
+        self.source
+    }
+
+    /// An unbiased bit: a raw pair 01 is 0, 10 is 1, 00 and 11 are dropped.
+    pub fn next_bit(&mut self) -> Result<bool, Error> {
+        for _ in 0..MAX_EQUAL_PAIRS {
+            let first = self.source.next().ok_or(Error::Exhausted)?;
+            let second = self.source.next().ok_or(Error::Exhausted)?;
+            if first != second {
+                return Ok(first);
+            }
+        }
+        Err(Error::Stuck)
+    }
+
+    /// A word conditioned from 64 unbiased bits.
+    pub fn try_next_u32(&mut self) -> Result<u32, Error> {
+        let mut bits = 0u64;
+        for _ in 0..BITS_PER_WORD {
+            bits = bits << 1 | self.next_bit()? as u64;
+        }
+        self.pool = mix(self.pool ^ bits);
+        Ok((self.pool >> 32) as u32)
+    }
+}
+
+impl<I: Iterator<Item = bool>> RngCore for Trng<I> {
+    /// Panics if the source is stuck or exhausted, see `try_next_u32()`.
+    fn next_u32(&mut self) -> u32 {
+        self.try_next_u32().expect("entropy source failed")
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        for chunk in dest.chunks_mut(4) {
+            let word = self.try_next_u32()?.to_le_bytes();
+            chunk.copy_from_slice(&word[..chunk.len()]);
+        }
+        Ok(())
+    }
+}
+
+/// xoshiro128++: fast, small and not cryptographic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmallRng {
+    s: [u32; 4],
+}
+
+impl SmallRng {
+    /// Seeded from ROSC RANDOMBIT, panics if it's stuck.
+    pub fn from_rosc() -> Self {
+        SmallRng::from_rng(Trng::rosc()).expect("ROSC entropy failed")
+    }
+}
+
+impl SeedableRng for SmallRng {
+    type Seed = [u8; 16];
+
+    /// An all zero seed would give only zeros, it's replaced with
+    /// `seed_from_u64(0)`.
+    fn from_seed(seed: [u8; 16]) -> Self {
+        if seed == [0; 16] {
+            return Self::seed_from_u64(0);
+        }
+        let mut s = [0; 4];
+        for (word, bytes) in s.iter_mut().zip(seed.chunks_exact(4)) {
+            *word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        SmallRng { s }
+    }
+}
+
+impl RngCore for SmallRng {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        let s = &mut self.s;
+        let result = s[0].wrapping_add(s[3]).rotate_left(7).wrapping_add(s[0]);
+        let t = s[1] << 9;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(11);
+        result
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_u32(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        impls::fill_bytes_via_next(self, dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Simulated RANDOMBIT, biased and correlated, not a capture from a
+    /// board. Written by tests/fixtures/randombit.py, which also works out
+    /// the words expected from it.
+    const RANDOMBIT: &[u8] = include_bytes!("../tests/fixtures/randombit.bin");
+
+    #[test]
+    fn von_neumann() {
+        // pairs 01, 10, 00, 11 (LSB first), then nothing
+        let mut trng = Trng::new(unpack(&[0b1100_0110]));
+        assert_eq!(trng.next_bit(), Ok(false));
+        assert_eq!(trng.next_bit(), Ok(true));
+        assert_eq!(trng.next_bit(), Err(Error::Exhausted));
+    }
+
+    #[test]
+    fn stuck() {
+        let mut trng = Trng::new(core::iter::repeat(true));
+        assert_eq!(trng.next_bit(), Err(Error::Stuck));
+        assert_eq!(trng.try_next_u32(), Err(Error::Stuck));
+
+        // one pair short of giving up
+        let equal = (0..MAX_EQUAL_PAIRS - 1).flat_map(|_| [false, false]);
+        let mut trng = Trng::new(equal.chain([true, false]));
+        assert_eq!(trng.next_bit(), Ok(true));
+    }
+
+    #[test]
+    fn exhausted() {
+        let mut trng = Trng::new(unpack(&[0b1010_1001; 8]));
+        assert_eq!(trng.try_next_u32(), Err(Error::Exhausted));
+        let mut trng = Trng::new(unpack(&[]));
+        let mut buf = [0; 4];
+        assert!(trng.try_fill_bytes(&mut buf).is_err());
+    }
+
+    #[test]
+    fn by_hand() {
+        // 0x66 is the pairs 01 10 01 10, 0x0f is 11 11 00 00 and dropped:
+        // 64 bits 0101...01, fmix64(0x5555_5555_5555_5555) >> 32
+        let bytes: [u8; 32] = core::array::from_fn(|i| [0x66, 0x0f][i % 2]);
+        let mut trng = Trng::new(unpack(&bytes));
+        assert_eq!(trng.try_next_u32(), Ok(0xbfa7_6d13));
+        assert_eq!(trng.try_next_u32(), Err(Error::Exhausted));
+    }
+
+    #[test]
+    fn simulated_randombit() {
+        let mut trng = Trng::new(unpack(RANDOMBIT));
+        let expected = [
+            0x99a1_0144,
+            0xc48c_f1be,
+            0x51aa_9e52,
+            0xb2f0_909c,
+            0x79c7_37f0,
+            0xc088_e518,
+            0xfafa_0245,
+            0x7f79_b5b1,
+            0xc98c_d02e,
+            0xda01_25b0,
+        ];
+        for &word in &expected {
+            assert_eq!(trng.try_next_u32(), Ok(word));
+        }
+        assert_eq!(trng.try_next_u32(), Err(Error::Exhausted));
+    }
+
+    #[test]
+    fn xoshiro128plusplus() {
+        // the reference implementation's output for s = [1, 2, 3, 4]
+        let mut seed = [0; 16];
+        for (i, bytes) in seed.chunks_exact_mut(4).enumerate() {
+            bytes.copy_from_slice(&(i as u32 + 1).to_le_bytes());
+        }
+        let mut rng = SmallRng::from_seed(seed);
+        let expected = [641, 1573767, 3222811527, 3517856514, 836907274, 4247214768];
+        for &word in &expected {
+            assert_eq!(rng.next_u32(), word);
+        }
+    }
+
+    #[test]
+    fn zero_seed() {
+        let mut rng = SmallRng::from_seed([0; 16]);
+        assert_eq!(rng, SmallRng::seed_from_u64(0));
+        assert_ne!(rng.s, [0; 4]);
+        assert!((0..4).any(|_| rng.next_u32() != 0));
+    }
+}
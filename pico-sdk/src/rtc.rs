@@ -0,0 +1,648 @@
+//! The real time clock: date and time, and alarms with wildcard fields.
+//!
+//! ```ignore
+//! let mut rtc = Rtc::new();
+//! rtc.set_datetime(&DateTime::new(2021, 3, 14, 15, 9, 26)?)?;
+//!
+//! // every minute, at :00
+//! rtc.set_alarm(Alarm::new().second(0), || println!("tick"))?;
+//! ```
+//!
+//! Add `hardware_rtc` to the app's `target_link_libraries`. With the `chrono`
+//! feature a `DateTime` converts to and from `chrono::NaiveDateTime`.
+
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use rpi_pico_sdk_sys::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Not in 0..=4095.
+    Year,
+    /// Not in 1..=12.
+    Month,
+    /// Not a day of that month.
+    Day,
+    /// Not in 0..=6, or not the weekday of the date.
+    DayOfWeek,
+    /// Not in 0..=23.
+    Hour,
+    /// Not in 0..=59.
+    Minute,
+    /// Not in 0..=59.
+    Second,
+    /// An alarm matching on nothing, it would fire all the time.
+    NoAlarmField,
+    /// The clock isn't set yet.
+    NotRunning,
+    /// pico-sdk's `rtc_set_datetime()` refused the date and time.
+    Rejected,
+}
+
+/// Date and time, as kept by the RTC. No time zone, no leap seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    /// 0..=4095
+    pub year: u16,
+    /// 1..=12, 1 is January
+    pub month: u8,
+    /// 1..=31, depending on the month
+    pub day: u8,
+    /// 0..=6, 0 is Sunday. The RTC doesn't check it, `new()` works it out.
+    pub day_of_week: u8,
+    /// 0..=23
+    pub hour: u8,
+    /// 0..=59
+    pub minute: u8,
+    /// 0..=59
+    pub second: u8,
+}
+
+impl DateTime {
+    /// Checked, with the day of the week filled in.
+    pub fn new(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+    ) -> Result<Self, Error> {
+        let mut t = DateTime {
+            year,
+            month,
+            day,
+            day_of_week: 0,
+            hour,
+            minute,
+            second,
+        };
+        t.check_date()?;
+        t.day_of_week = weekday(days_from_civil(year, month, day));
+        t.validate()?;
+        Ok(t)
+    }
+
+    /// Every field in range, and `day_of_week` matching the date.
+    pub fn validate(&self) -> Result<(), Error> {
+        self.check_date()?;
+        if self.day_of_week != weekday(days_from_civil(self.year, self.month, self.day)) {
+            return Err(Error::DayOfWeek);
+        }
+        check(self.hour, 23, Error::Hour)?;
+        check(self.minute, 59, Error::Minute)?;
+        check(self.second, 59, Error::Second)
+    }
+
+    fn check_date(&self) -> Result<(), Error> {
+        if self.year > 4095 {
+            return Err(Error::Year);
+        }
+        if !(1..=12).contains(&self.month) {
+            return Err(Error::Month);
+        }
+        if !(1..=days_in_month(self.year, self.month)).contains(&self.day) {
+            return Err(Error::Day);
+        }
+        Ok(())
+    }
+
+    /// From seconds since 1970-01-01 00:00:00 UTC.
+    pub fn from_unix_timestamp(secs: i64) -> Result<Self, Error> {
+        let days = secs.div_euclid(86400);
+        let secs = secs.rem_euclid(86400) as u32;
+        let (year, month, day) = civil_from_days(days);
+        if !(0..=4095).contains(&year) {
+            return Err(Error::Year);
+        }
+        Ok(DateTime {
+            year: year as u16,
+            month,
+            day,
+            day_of_week: weekday(days),
+            hour: (secs / 3600) as u8,
+            minute: (secs / 60 % 60) as u8,
+            second: (secs % 60) as u8,
+        })
+    }
+
+    /// Seconds since 1970-01-01 00:00:00 UTC, taking the date and time as UTC.
+    pub fn to_unix_timestamp(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) * 86400
+            + self.hour as i64 * 3600
+            + self.minute as i64 * 60
+            + self.second as i64
+    }
+
+    fn from_raw(t: &datetime_t) -> Self {
+        DateTime {
+            year: t.year as u16,
+            month: t.month as u8,
+            day: t.day as u8,
+            day_of_week: t.dotw as u8,
+            hour: t.hour as u8,
+            minute: t.min as u8,
+            second: t.sec as u8,
+        }
+    }
+
+    fn to_raw(self) -> datetime_t {
+        datetime_t {
+            year: self.year as i16,
+            month: self.month as i8,
+            day: self.day as i8,
+            dotw: self.day_of_week as i8,
+            hour: self.hour as i8,
+            min: self.minute as i8,
+            sec: self.second as i8,
+        }
+    }
+}
+
+fn check(value: u8, max: u8, err: Error) -> Result<(), Error> {
+    if value <= max {
+        Ok(())
+    } else {
+        Err(err)
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Howard Hinnant's days_from_civil and civil_from_days, proleptic Gregorian,
+// days since 1970-01-01
+
+fn days_from_civil(year: u16, month: u8, day: u8) -> i64 {
+    let y = year as i64 - (month <= 2) as i64;
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// 0 is Sunday, 1970-01-01 was a Thursday.
+fn weekday(days: i64) -> u8 {
+    (days + 4).rem_euclid(7) as u8
+}
+
+/// When an alarm fires: the fields to match, `None` matches anything.
+///
+/// With every field given it fires once, otherwise on every match, e.g.
+/// `Alarm::new().second(0)` every minute.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Alarm {
+    pub year: Option<u16>,
+    pub month: Option<u8>,
+    pub day: Option<u8>,
+    pub day_of_week: Option<u8>,
+    pub hour: Option<u8>,
+    pub minute: Option<u8>,
+    pub second: Option<u8>,
+}
+
+impl Alarm {
+    /// Matching on nothing yet, set at least one field.
+    pub const fn new() -> Self {
+        Alarm {
+            year: None,
+            month: None,
+            day: None,
+            day_of_week: None,
+            hour: None,
+            minute: None,
+            second: None,
+        }
+    }
+
+    /// Once, at `t`.
+    pub const fn at(t: &DateTime) -> Self {
+        Alarm {
+            year: Some(t.year),
+            month: Some(t.month),
+            day: Some(t.day),
+            day_of_week: Some(t.day_of_week),
+            hour: Some(t.hour),
+            minute: Some(t.minute),
+            second: Some(t.second),
+        }
+    }
+
+    pub const fn year(mut self, year: u16) -> Self {
+        self.year = Some(year);
+        self
+    }
+
+    pub const fn month(mut self, month: u8) -> Self {
+        self.month = Some(month);
+        self
+    }
+
+    pub const fn day(mut self, day: u8) -> Self {
+        self.day = Some(day);
+        self
+    }
+
+    /// 0 is Sunday.
+    pub const fn day_of_week(mut self, day_of_week: u8) -> Self {
+        self.day_of_week = Some(day_of_week);
+        self
+    }
+
+    pub const fn hour(mut self, hour: u8) -> Self {
+        self.hour = Some(hour);
+        self
+    }
+
+    pub const fn minute(mut self, minute: u8) -> Self {
+        self.minute = Some(minute);
+        self
+    }
+
+    pub const fn second(mut self, second: u8) -> Self {
+        self.second = Some(second);
+        self
+    }
+
+    /// Every given field in range, and at least one given.
+    pub fn validate(&self) -> Result<(), Error> {
+        if *self == Alarm::new() {
+            return Err(Error::NoAlarmField);
+        }
+        if self.year.is_some_and(|y| y > 4095) {
+            return Err(Error::Year);
+        }
+        if self.month.is_some_and(|m| !(1..=12).contains(&m)) {
+            return Err(Error::Month);
+        }
+        if self.day.is_some_and(|d| !(1..=31).contains(&d)) {
+            return Err(Error::Day);
+        }
+        check(self.day_of_week.unwrap_or(0), 6, Error::DayOfWeek)?;
+        check(self.hour.unwrap_or(0), 23, Error::Hour)?;
+        check(self.minute.unwrap_or(0), 59, Error::Minute)?;
+        check(self.second.unwrap_or(0), 59, Error::Second)
+    }
+
+    /// -1 for "don't match", as datetime_t has it
+    fn to_raw(self) -> datetime_t {
+        let field = |v: Option<u8>| v.map_or(-1, |v| v as i8);
+        datetime_t {
+            year: self.year.map_or(-1, |y| y as i16),
+            month: field(self.month),
+            day: field(self.day),
+            dotw: field(self.day_of_week),
+            hour: field(self.hour),
+            min: field(self.minute),
+            sec: field(self.second),
+        }
+    }
+}
+
+/// Handler of the alarm, a `fn()`
+static ALARM_HANDLER: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+unsafe extern "C" fn alarm_trampoline() {
+    let handler = ALARM_HANDLER.load(Ordering::Acquire);
+    if !handler.is_null() {
+        let handler: fn() = core::mem::transmute(handler);
+        handler();
+    }
+}
+
+/// The RTC, clocked from clk_rtc as set up by pico-sdk's runtime.
+pub struct Rtc {
+    _private: (),
+}
+
+impl Rtc {
+    /// Reset the RTC: it's stopped until `set_datetime()`.
+    pub fn new() -> Self {
+        unsafe {
+            rtc_init();
+        }
+        Rtc { _private: () }
+    }
+
+    /// Set the clock and start it. `now()` may still return the old time for a
+    /// few cycles of the 46.875 kHz clk_rtc, about 64 µs.
+    pub fn set_datetime(&mut self, t: &DateTime) -> Result<(), Error> {
+        t.validate()?;
+        let mut raw = t.to_raw();
+        // pico-sdk checks the same ranges, it shouldn't fail
+        if unsafe { rtc_set_datetime(&mut raw) } {
+            Ok(())
+        } else {
+            Err(Error::Rejected)
+        }
+    }
+
+    pub fn now(&self) -> Result<DateTime, Error> {
+        let mut raw = datetime_t {
+            year: 0,
+            month: 0,
+            day: 0,
+            dotw: 0,
+            hour: 0,
+            min: 0,
+            sec: 0,
+        };
+        if unsafe { rtc_get_datetime(&mut raw) } {
+            Ok(DateTime::from_raw(&raw))
+        } else {
+            Err(Error::NotRunning)
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        unsafe { rtc_running() }
+    }
+
+    /// Call `handler` from the RTC interrupt when `alarm` matches, replacing
+    /// any previous alarm. The handler runs in interrupt context, keep it short.
+    pub fn set_alarm(&mut self, alarm: Alarm, handler: fn()) -> Result<(), Error> {
+        alarm.validate()?;
+        ALARM_HANDLER.store(handler as *mut (), Ordering::Release);
+        let mut raw = alarm.to_raw();
+        unsafe {
+            rtc_set_alarm(&mut raw, Some(alarm_trampoline));
+        }
+        Ok(())
+    }
+
+    /// Re-arm the alarm, e.g. after a one-shot alarm fired.
+    pub fn enable_alarm(&mut self) {
+        unsafe {
+            rtc_enable_alarm();
+        }
+    }
+
+    pub fn disable_alarm(&mut self) {
+        unsafe {
+            rtc_disable_alarm();
+        }
+    }
+}
+
+impl Default for Rtc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_impl {
+    use super::{DateTime, Error};
+    use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+    use core::convert::TryFrom;
+
+    impl TryFrom<NaiveDateTime> for DateTime {
+        type Error = Error;
+
+        fn try_from(t: NaiveDateTime) -> Result<Self, Error> {
+            if !(0..=4095).contains(&t.year()) {
+                return Err(Error::Year);
+            }
+            Ok(DateTime {
+                year: t.year() as u16,
+                month: t.month() as u8,
+                day: t.day() as u8,
+                day_of_week: t.weekday().num_days_from_sunday() as u8,
+                hour: t.hour() as u8,
+                minute: t.minute() as u8,
+                second: t.second() as u8,
+            })
+        }
+    }
+
+    impl TryFrom<DateTime> for NaiveDateTime {
+        type Error = Error;
+
+        fn try_from(t: DateTime) -> Result<Self, Error> {
+            t.validate()?;
+            NaiveDate::from_ymd_opt(t.year as i32, t.month as u32, t.day as u32)
+                .and_then(|d| d.and_hms_opt(t.hour as u32, t.minute as u32, t.second as u32))
+                .ok_or(Error::Day)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dt(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> DateTime {
+        DateTime::new(year, month, day, hour, minute, second).unwrap()
+    }
+
+    // The timestamps and weekdays are from Python's datetime
+
+    #[test]
+    fn epoch() {
+        let t = dt(1970, 1, 1, 0, 0, 0);
+        assert_eq!(t.day_of_week, 4);
+        assert_eq!(t.to_unix_timestamp(), 0);
+        assert_eq!(DateTime::from_unix_timestamp(0), Ok(t));
+        assert_eq!(
+            DateTime::from_unix_timestamp(-1),
+            Ok(dt(1969, 12, 31, 23, 59, 59))
+        );
+        assert_eq!(
+            dt(2021, 3, 14, 15, 9, 26).to_unix_timestamp(),
+            1_615_734_566
+        );
+    }
+
+    #[test]
+    fn leap_days() {
+        let t = dt(2000, 2, 29, 0, 0, 0);
+        assert_eq!(t.day_of_week, 2);
+        assert_eq!(t.to_unix_timestamp(), 951_782_400);
+
+        // 2100 is not a leap year
+        assert_eq!(DateTime::new(2100, 2, 29, 0, 0, 0), Err(Error::Day));
+        let t = dt(2100, 2, 28, 23, 59, 59);
+        assert_eq!(t.day_of_week, 0);
+        assert_eq!(t.to_unix_timestamp(), 4_107_542_399);
+        let t = DateTime::from_unix_timestamp(4_107_542_400).unwrap();
+        assert_eq!(t, dt(2100, 3, 1, 0, 0, 0));
+        assert_eq!(t.day_of_week, 1);
+
+        assert!(DateTime::new(2024, 2, 29, 0, 0, 0).is_ok());
+        assert_eq!(DateTime::new(2023, 2, 29, 0, 0, 0), Err(Error::Day));
+    }
+
+    #[test]
+    fn year_range() {
+        // year 0 is a leap year in the proleptic Gregorian calendar
+        let t = dt(0, 1, 1, 0, 0, 0);
+        assert_eq!(t.day_of_week, 6);
+        assert_eq!(t.to_unix_timestamp(), -62_167_219_200);
+        assert_eq!(DateTime::from_unix_timestamp(-62_167_219_200), Ok(t));
+        assert!(DateTime::new(0, 2, 29, 0, 0, 0).is_ok());
+        assert_eq!(
+            DateTime::from_unix_timestamp(-62_167_219_201),
+            Err(Error::Year)
+        );
+
+        let t = dt(4095, 12, 31, 23, 59, 59);
+        assert_eq!(t.day_of_week, 6);
+        assert_eq!(t.to_unix_timestamp(), 67_090_118_399);
+        assert_eq!(DateTime::from_unix_timestamp(67_090_118_399), Ok(t));
+        assert_eq!(
+            DateTime::from_unix_timestamp(67_090_118_400),
+            Err(Error::Year)
+        );
+        assert_eq!(DateTime::new(4096, 1, 1, 0, 0, 0), Err(Error::Year));
+    }
+
+    #[test]
+    fn invalid_dates() {
+        assert_eq!(DateTime::new(2021, 0, 1, 0, 0, 0), Err(Error::Month));
+        assert_eq!(DateTime::new(2021, 13, 1, 0, 0, 0), Err(Error::Month));
+        assert_eq!(DateTime::new(2021, 1, 0, 0, 0, 0), Err(Error::Day));
+        assert_eq!(DateTime::new(2021, 1, 32, 0, 0, 0), Err(Error::Day));
+        assert_eq!(DateTime::new(2021, 4, 31, 0, 0, 0), Err(Error::Day));
+        assert_eq!(DateTime::new(2021, 1, 1, 24, 0, 0), Err(Error::Hour));
+        assert_eq!(DateTime::new(2021, 1, 1, 0, 60, 0), Err(Error::Minute));
+        // no leap seconds
+        assert_eq!(DateTime::new(2016, 12, 31, 23, 59, 60), Err(Error::Second));
+    }
+
+    #[test]
+    fn validate() {
+        let t = dt(2021, 3, 14, 15, 9, 26);
+        assert_eq!(t.validate(), Ok(()));
+        assert_eq!(
+            DateTime {
+                day_of_week: 1,
+                ..t
+            }
+            .validate(),
+            Err(Error::DayOfWeek)
+        );
+        assert_eq!(DateTime { day: 31, ..t }.validate(), Err(Error::DayOfWeek));
+        assert_eq!(DateTime { day: 32, ..t }.validate(), Err(Error::Day));
+        assert_eq!(DateTime { year: 5000, ..t }.validate(), Err(Error::Year));
+        assert_eq!(DateTime { month: 0, ..t }.validate(), Err(Error::Month));
+        assert_eq!(DateTime { hour: 24, ..t }.validate(), Err(Error::Hour));
+        assert_eq!(DateTime { minute: 60, ..t }.validate(), Err(Error::Minute));
+        assert_eq!(DateTime { second: 60, ..t }.validate(), Err(Error::Second));
+    }
+
+    #[test]
+    fn unix_round_trip() {
+        // a few days' worth of steps across the whole range, and every second
+        // around the epoch and a leap day
+        let first = -62_167_219_200i64;
+        let last = 67_090_118_399i64;
+        let steps = (first..=last)
+            .step_by(86400 * 3 + 3607)
+            .chain(-100_000..100_000)
+            .chain(951_782_400 - 86400..951_782_400 + 86400 * 2)
+            .chain(core::iter::once(last));
+        for secs in steps {
+            let t = DateTime::from_unix_timestamp(secs).unwrap();
+            assert_eq!(t.validate(), Ok(()), "{}", secs);
+            assert_eq!(t.to_unix_timestamp(), secs);
+        }
+    }
+
+    #[test]
+    fn alarm_validate() {
+        assert_eq!(Alarm::new().validate(), Err(Error::NoAlarmField));
+        assert_eq!(Alarm::new().second(0).validate(), Ok(()));
+        assert_eq!(Alarm::at(&dt(2021, 3, 14, 15, 9, 26)).validate(), Ok(()));
+        // any day 31 may match, some months
+        assert_eq!(Alarm::new().month(2).day(31).validate(), Ok(()));
+
+        assert_eq!(Alarm::new().year(4096).validate(), Err(Error::Year));
+        assert_eq!(Alarm::new().month(0).validate(), Err(Error::Month));
+        assert_eq!(Alarm::new().month(13).validate(), Err(Error::Month));
+        assert_eq!(Alarm::new().day(0).validate(), Err(Error::Day));
+        assert_eq!(Alarm::new().day(32).validate(), Err(Error::Day));
+        assert_eq!(
+            Alarm::new().day_of_week(7).validate(),
+            Err(Error::DayOfWeek)
+        );
+        assert_eq!(Alarm::new().hour(24).validate(), Err(Error::Hour));
+        assert_eq!(Alarm::new().minute(60).validate(), Err(Error::Minute));
+        assert_eq!(Alarm::new().second(60).validate(), Err(Error::Second));
+    }
+
+    #[test]
+    fn alarm_raw() {
+        let raw = Alarm::new().day_of_week(1).hour(7).to_raw();
+        assert_eq!((raw.year, raw.month, raw.day, raw.dotw), (-1, -1, -1, 1));
+        assert_eq!((raw.hour, raw.min, raw.sec), (7, -1, -1));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono() {
+        use chrono::{NaiveDate, NaiveDateTime};
+        use core::convert::TryFrom;
+
+        let t = dt(2000, 2, 29, 13, 14, 15);
+        let naive = NaiveDate::from_ymd_opt(2000, 2, 29)
+            .unwrap()
+            .and_hms_opt(13, 14, 15)
+            .unwrap();
+        assert_eq!(NaiveDateTime::try_from(t), Ok(naive));
+        assert_eq!(DateTime::try_from(naive), Ok(t));
+
+        for &(year, month, day) in &[(0, 1, 1), (1970, 1, 1), (2100, 3, 1), (4095, 12, 31)] {
+            let naive = NaiveDate::from_ymd_opt(year, month, day)
+                .unwrap()
+                .and_hms_opt(23, 59, 59)
+                .unwrap();
+            let t = DateTime::try_from(naive).unwrap();
+            assert_eq!(t.validate(), Ok(()));
+            assert_eq!(NaiveDateTime::try_from(t), Ok(naive));
+        }
+
+        let out_of_range = NaiveDate::from_ymd_opt(4096, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        assert_eq!(DateTime::try_from(out_of_range), Err(Error::Year));
+        let before_0 = NaiveDate::from_ymd_opt(-1, 12, 31)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        assert_eq!(DateTime::try_from(before_0), Err(Error::Year));
+        assert_eq!(
+            NaiveDateTime::try_from(DateTime {
+                day_of_week: 0,
+                ..t
+            }),
+            Err(Error::DayOfWeek)
+        );
+    }
+}
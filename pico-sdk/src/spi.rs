@@ -1,11 +1,28 @@
 //! Fake embedded-hal SPI via c-sdk.
 
-use rpi_pico_sdk_sys::*;
 use core::convert::Infallible;
 use embedded_hal::blocking::spi;
+use rpi_pico_sdk_sys::*;
+
+use crate::clocks::{ClockSet, Hertz};
+use crate::dividers;
+use crate::gpio::GpioFunction;
+
+/// SPI signals, a GPIO carries the one at `pin % 4`
+pub const RX: uint = 0;
+pub const CSN: uint = 1;
+pub const SCK: uint = 2;
+pub const TX: uint = 3;
+
+/// Whether `pin` can carry `signal` of SPI `instance`, GPIO0-7 and 16-23 are SPI0.
+pub const fn is_valid_pin(instance: uint, signal: uint, pin: uint) -> bool {
+    pin < 30 && pin % 4 == signal && (pin / 8) % 2 == instance
+}
 
 pub struct SPI {
     hw: *mut spi_inst_t,
+    clk_peri: Hertz,
+    baudrate: Hertz,
 }
 
 impl Drop for SPI {
@@ -17,28 +34,72 @@ impl Drop for SPI {
 }
 
 impl SPI {
+    /// Just the peripheral: route the pins with `with_pins()`, or use `spi0!`,
+    /// which also records them in the binary info.
     pub fn spi0(baudrate: uint) -> Self {
-        let mut s = SPI { hw: spi0 };
-        s.init(baudrate);
-        s
+        Self::spi0_with_clocks(&ClockSet::current(), Hertz(baudrate))
     }
 
+    /// See `spi0()` and `spi1!`.
     pub fn spi1(baudrate: uint) -> Self {
-        let mut s = SPI { hw: spi1 };
-        s.init(baudrate);
+        Self::spi1_with_clocks(&ClockSet::current(), Hertz(baudrate))
+    }
+
+    /// SPI0 at `baudrate`, or the closest below, from `clocks.peri`.
+    pub fn spi0_with_clocks(clocks: &ClockSet, baudrate: Hertz) -> Self {
+        Self::new(spi0, clocks, baudrate)
+    }
+
+    /// SPI1 at `baudrate`, or the closest below, from `clocks.peri`.
+    pub fn spi1_with_clocks(clocks: &ClockSet, baudrate: Hertz) -> Self {
+        Self::new(spi1, clocks, baudrate)
+    }
+
+    fn new(hw: *mut spi_inst_t, clocks: &ClockSet, baudrate: Hertz) -> Self {
+        let mut s = SPI {
+            hw,
+            clk_peri: clocks.peri,
+            baudrate: Hertz(0),
+        };
+        unsafe {
+            spi_init(s.hw, baudrate.0);
+        }
+        s.set_baudrate(baudrate.0);
         s
     }
 
-    fn init(&mut self, baudrate: uint) {
+    /// Route SCK and TX to the given GPIOs. See `spi0!`, which also checks
+    /// the pins and records them in the binary info.
+    pub fn with_pins(self, sck: uint, tx: uint) -> Self {
         unsafe {
-            spi_init(self.hw, baudrate);
+            gpio_set_function(sck, GpioFunction::SPI as _);
+            gpio_set_function(tx, GpioFunction::SPI as _);
         }
+        self
     }
 
-    pub fn set_baudrate(&mut self, baudrate: uint) {
+    pub fn with_rx(self, rx: uint) -> Self {
         unsafe {
-            spi_set_baudrate(self.hw, baudrate);
+            gpio_set_function(rx, GpioFunction::SPI as _);
         }
+        self
+    }
+
+    /// Set the closest baud rate at or below `baudrate`, and return it.
+    pub fn set_baudrate(&mut self, baudrate: uint) -> Hertz {
+        let div = dividers::spi(self.clk_peri, Hertz(baudrate));
+        unsafe {
+            let hw = &*spi_get_hw(self.hw);
+            hw.cpsr
+                .write_field(SPI_SSPCPSR::CPSDVSR, div.prescale as u32);
+            hw.cr0.write_field(SPI_SSPCR0::SCR, div.postdiv as u32 - 1);
+        }
+        self.baudrate = div.baudrate(self.clk_peri);
+        self.baudrate
+    }
+
+    pub fn baudrate(&self) -> Hertz {
+        self.baudrate
     }
 }
 
@@ -52,3 +113,39 @@ impl spi::Write<u8> for SPI {
         Ok(())
     }
 }
+
+/// `SPI::spi0(baudrate)` on the given pins, checked at compile time and
+/// recorded in the binary info:
+///
+/// ```ignore
+/// let spi = spi0!(4_000_000, sck: 18, tx: 19);
+/// ```
+#[macro_export]
+macro_rules! spi0 {
+    ($baudrate:expr, sck: $sck:expr, tx: $tx:expr $(, rx: $rx:expr)? $(,)?) => {
+        $crate::__spi!(0, spi0, $baudrate, $sck, $tx $(, $rx)?)
+    };
+}
+
+/// `SPI::spi1(baudrate)` on the given pins, see `spi0!`.
+#[macro_export]
+macro_rules! spi1 {
+    ($baudrate:expr, sck: $sck:expr, tx: $tx:expr $(, rx: $rx:expr)? $(,)?) => {
+        $crate::__spi!(1, spi1, $baudrate, $sck, $tx $(, $rx)?)
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __spi {
+    ($instance:expr, $ctor:ident, $baudrate:expr, $sck:expr, $tx:expr $(, $rx:expr)?) => {{
+        const _: () = {
+            use $crate::spi::{is_valid_pin, SCK, TX, RX};
+            assert!(is_valid_pin($instance, SCK, $sck), "not an SCK pin of this SPI");
+            assert!(is_valid_pin($instance, TX, $tx), "not a TX pin of this SPI");
+            $(assert!(is_valid_pin($instance, RX, $rx), "not an RX pin of this SPI");)?
+        };
+        $crate::bi_pins_with_func!($crate::gpio::GpioFunction::SPI, $sck, $tx $(, $rx)?);
+        $crate::spi::SPI::$ctor($baudrate).with_pins($sck, $tx)$(.with_rx($rx))?
+    }};
+}
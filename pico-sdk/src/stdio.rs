@@ -0,0 +1,207 @@
+//! Rust side of pico_stdio: `print!`/`println!` and line input over the SDK's
+//! stdio drivers (UART, USB).
+//!
+//! There is only one output stream, `eprint!`/`eprintln!` go to the same place
+//! as `print!`/`println!`, they exist so ported code reads the same.
+
+use core::fmt;
+use core::str;
+
+use rpi_pico_sdk_sys::*;
+
+/// Initialize all stdio drivers linked into the binary, `stdio_init_all()`.
+pub fn init_all() {
+    unsafe {
+        stdio_init_all();
+    }
+}
+
+/// Wait until all output has been sent.
+pub fn flush() {
+    unsafe {
+        stdio_flush();
+    }
+}
+
+/// A stdio driver, for per-driver settings.
+#[derive(Clone, Copy)]
+pub struct Driver(*mut stdio_driver_t);
+
+impl Driver {
+    /// pico_stdio_uart, needs it to be linked in.
+    #[inline]
+    pub fn uart() -> Self {
+        Driver(core::ptr::addr_of_mut!(stdio_uart))
+    }
+
+    /// pico_stdio_usb, needs it to be linked in.
+    #[inline]
+    pub fn usb() -> Self {
+        Driver(core::ptr::addr_of_mut!(stdio_usb))
+    }
+
+    /// Translate `\n` to `\r\n` on output, on by default.
+    pub fn set_translate_crlf(self, translate: bool) {
+        unsafe {
+            stdio_set_translate_crlf(self.0, translate);
+        }
+    }
+
+    /// Add or remove the driver from the active ones.
+    pub fn set_enabled(self, enabled: bool) {
+        unsafe {
+            stdio_set_driver_enabled(self.0, enabled);
+        }
+    }
+
+    /// Use only this driver for input and output.
+    pub fn select_only(self) {
+        unsafe {
+            stdio_filter_driver(self.0);
+        }
+    }
+
+    /// Undo `select_only()`, use all enabled drivers again.
+    pub fn select_all() {
+        unsafe {
+            stdio_filter_driver(core::ptr::null_mut());
+        }
+    }
+}
+
+/// Standard output, implements `core::fmt::Write`.
+pub struct Stdout;
+
+impl fmt::Write for Stdout {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for &b in s.as_bytes() {
+            unsafe {
+                putchar(b as _);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+pub fn _print(args: fmt::Arguments) {
+    let _ = fmt::Write::write_fmt(&mut Stdout, args);
+}
+
+#[macro_export]
+macro_rules! print {
+    ($($arg:tt)*) => {
+        $crate::stdio::_print(format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! println {
+    () => {
+        $crate::print!("\n")
+    };
+    ($($arg:tt)*) => {
+        $crate::stdio::_print(format_args!("{}\n", format_args!($($arg)*)))
+    };
+}
+
+/// Same as `print!`, stdio has no separate error stream.
+#[macro_export]
+macro_rules! eprint {
+    ($($arg:tt)*) => {
+        $crate::print!($($arg)*)
+    };
+}
+
+/// Same as `println!`, stdio has no separate error stream.
+#[macro_export]
+macro_rules! eprintln {
+    ($($arg:tt)*) => {
+        $crate::println!($($arg)*)
+    };
+}
+
+/// `len` is how much of the buffer holds what was read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadError {
+    /// No complete line within the timeout, just its first `len` bytes.
+    Timeout { len: usize },
+    /// The line does not fit into the buffer, `len` is the buffer's length.
+    /// The next `read_line()` returns the rest of it.
+    BufferFull { len: usize },
+    /// The line is not valid UTF-8.
+    InvalidUtf8 { len: usize },
+}
+
+/// Standard input, line based.
+#[derive(Default)]
+pub struct Stdin {
+    /// Last line ended with `\r`, a `\n` right after it belongs to it.
+    skip_lf: bool,
+    /// Read, but didn't fit into the last line's buffer.
+    pushback: Option<u8>,
+}
+
+impl Stdin {
+    pub const fn new() -> Self {
+        Stdin {
+            skip_lf: false,
+            pushback: None,
+        }
+    }
+
+    /// Read a byte, `None` on timeout.
+    pub fn read_byte(&mut self, timeout_us: u32) -> Option<u8> {
+        if let Some(b) = self.pushback.take() {
+            return Some(b);
+        }
+        match unsafe { getchar_timeout_us(timeout_us) } {
+            c if c >= 0 => Some(c as u8),
+            _ => None,
+        }
+    }
+
+    /// Read a line ending in `\n`, `\r` or `\r\n`, without the line ending.
+    ///
+    /// `timeout_us` is for the whole line, 0 waits forever.
+    pub fn read_line<'a>(
+        &mut self,
+        buf: &'a mut [u8],
+        timeout_us: u32,
+    ) -> Result<&'a str, ReadError> {
+        let deadline = unsafe { time_us_64() } + timeout_us as u64;
+        let mut len = 0;
+        loop {
+            let wait = if timeout_us == 0 {
+                u32::MAX
+            } else {
+                let now = unsafe { time_us_64() };
+                if now >= deadline {
+                    return Err(ReadError::Timeout { len });
+                }
+                (deadline - now).min(u32::MAX as u64) as u32
+            };
+            let b = match self.read_byte(wait) {
+                Some(b) => b,
+                None if timeout_us == 0 => continue,
+                None => return Err(ReadError::Timeout { len }),
+            };
+
+            let skip_lf = self.skip_lf;
+            self.skip_lf = b == b'\r';
+            match b {
+                b'\n' if skip_lf && len == 0 => continue,
+                b'\n' | b'\r' => break,
+                _ if len == buf.len() => {
+                    self.pushback = Some(b);
+                    return Err(ReadError::BufferFull { len });
+                }
+                _ => {
+                    buf[len] = b;
+                    len += 1;
+                }
+            }
+        }
+        str::from_utf8(&buf[..len]).map_err(|_| ReadError::InvalidUtf8 { len })
+    }
+}
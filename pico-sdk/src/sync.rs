@@ -0,0 +1,266 @@
+//! Hardware spin locks, and what's built on them: a `Mutex<T>` and, with the
+//! `critical-section-impl` feature, the `critical-section` implementation.
+//!
+//! All of them disable interrupts on the calling core and take a spin lock, so
+//! they exclude both interrupt handlers and the other core. Locking is
+//! reentrant per core: taking a lock the core already holds just goes on.
+//!
+//! Keep the locked sections short, the other core spins meanwhile.
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use rpi_pico_sdk_sys::*;
+
+/// The spin lock of `Mutex::new()` and of the critical section, one reserved
+/// by pico-sdk for an OS.
+pub const SHARED_SPINLOCK: u32 = PICO_SPINLOCK_ID_OS1;
+
+const NO_OWNER: u8 = u8::MAX;
+
+#[allow(clippy::declare_interior_mutable_const)]
+const UNOWNED: AtomicU8 = AtomicU8::new(NO_OWNER);
+
+/// Core holding each spin lock through `acquire()`, written only by that core
+/// while it holds the lock
+static OWNER: [AtomicU8; 32] = [UNOWNED; 32];
+
+/// What `release()` needs to undo `acquire()`
+#[derive(Clone, Copy)]
+struct Token {
+    interrupts: u32,
+    nested: bool,
+}
+
+fn acquire(num: u32) -> Token {
+    unsafe {
+        let interrupts = save_and_disable_interrupts();
+        let core = get_core_num() as u8;
+        if OWNER[num as usize].load(Ordering::Relaxed) == core {
+            return Token {
+                interrupts,
+                nested: true,
+            };
+        }
+        spin_lock_unsafe_blocking(spin_lock_instance(num));
+        OWNER[num as usize].store(core, Ordering::Relaxed);
+        Token {
+            interrupts,
+            nested: false,
+        }
+    }
+}
+
+fn try_acquire(num: u32) -> Option<Token> {
+    unsafe {
+        let interrupts = save_and_disable_interrupts();
+        let core = get_core_num() as u8;
+        if OWNER[num as usize].load(Ordering::Relaxed) == core {
+            return Some(Token {
+                interrupts,
+                nested: true,
+            });
+        }
+        // reading claims it, 0 means someone else has it
+        if (*spin_lock_instance(num)).read() == 0 {
+            restore_interrupts(interrupts);
+            return None;
+        }
+        __mem_fence_acquire();
+        OWNER[num as usize].store(core, Ordering::Relaxed);
+        Some(Token {
+            interrupts,
+            nested: false,
+        })
+    }
+}
+
+fn release(num: u32, token: Token) {
+    unsafe {
+        if !token.nested {
+            OWNER[num as usize].store(NO_OWNER, Ordering::Relaxed);
+            spin_unlock_unsafe(spin_lock_instance(num));
+        }
+        restore_interrupts(token.interrupts);
+    }
+}
+
+/// One of the 32 SIO spin locks.
+pub struct SpinLock {
+    num: u32,
+    claimed: bool,
+}
+
+impl SpinLock {
+    /// A spin lock nobody else claimed, `None` if all of the free ones
+    /// (24 to 31) are taken. Unclaimed on drop.
+    pub fn claim_unused() -> Option<SpinLock> {
+        match unsafe { spin_lock_claim_unused(false) } {
+            num if num >= 0 => Some(SpinLock {
+                num: num as u32,
+                claimed: true,
+            }),
+            _ => None,
+        }
+    }
+
+    /// One of the striped spin locks (16 to 23), shared round robin with other
+    /// users: expect some contention.
+    pub fn striped() -> SpinLock {
+        SpinLock {
+            num: unsafe { next_striped_spin_lock_num() },
+            claimed: false,
+        }
+    }
+
+    /// Spin lock `num`, without claiming it.
+    ///
+    /// # Safety
+    ///
+    /// Anything else using it must follow the same protocol, e.g. pico-sdk's
+    /// own spin locks are held with interrupts off and never nested.
+    pub unsafe fn new(num: u32) -> SpinLock {
+        assert!(num < NUM_SPIN_LOCKS, "no such spin lock");
+        SpinLock {
+            num,
+            claimed: false,
+        }
+    }
+
+    pub fn num(&self) -> u32 {
+        self.num
+    }
+
+    /// Run `f` with interrupts off and the lock held.
+    pub fn lock<R>(&self, f: impl FnOnce() -> R) -> R {
+        let token = acquire(self.num);
+        let r = f();
+        release(self.num, token);
+        r
+    }
+
+    /// Run `f` if the lock is free (or already held by this core), `None` otherwise.
+    pub fn try_lock<R>(&self, f: impl FnOnce() -> R) -> Option<R> {
+        let token = try_acquire(self.num)?;
+        let r = f();
+        release(self.num, token);
+        Some(r)
+    }
+
+    /// Held by either core.
+    pub fn is_locked(&self) -> bool {
+        unsafe { is_spin_locked(spin_lock_instance(self.num)) }
+    }
+}
+
+impl Drop for SpinLock {
+    fn drop(&mut self) {
+        if self.claimed {
+            unsafe {
+                spin_lock_unclaim(self.num);
+            }
+        }
+    }
+}
+
+/// Data shared between cores and interrupt handlers, guarded by a spin lock.
+///
+/// ```ignore
+/// static COUNT: Mutex<u32> = Mutex::new(0);
+///
+/// COUNT.lock(|count| *count += 1);
+/// ```
+///
+/// Locking the same `Mutex` again inside `lock()` panics.
+pub struct Mutex<T> {
+    spinlock: u32,
+    locked: UnsafeCell<bool>,
+    data: UnsafeCell<T>,
+}
+
+// the data is only reached through lock()
+unsafe impl<T: Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    /// Guarded by `SHARED_SPINLOCK`, like every other `Mutex::new()` and the
+    /// critical section.
+    pub const fn new(value: T) -> Self {
+        Self::with_spinlock(SHARED_SPINLOCK, value)
+    }
+
+    /// Guarded by spin lock `num`, to not contend with the others. Nothing else
+    /// may hold that spin lock in a different way, e.g. pico-sdk's own ones
+    /// (0 to 15) are out.
+    pub const fn with_spinlock(num: u32, value: T) -> Self {
+        assert!(num < NUM_SPIN_LOCKS, "no such spin lock");
+        Mutex {
+            spinlock: num,
+            locked: UnsafeCell::new(false),
+            data: UnsafeCell::new(value),
+        }
+    }
+
+    /// Run `f` on the data, with interrupts off and the spin lock held.
+    pub fn lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let token = acquire(self.spinlock);
+        let r = self.locked(f);
+        release(self.spinlock, token);
+        r
+    }
+
+    /// Run `f` if the spin lock is free, `None` otherwise.
+    pub fn try_lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> Option<R> {
+        let token = try_acquire(self.spinlock)?;
+        let r = self.locked(f);
+        release(self.spinlock, token);
+        Some(r)
+    }
+
+    /// The spin lock is held, only this core can be in here.
+    fn locked<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        unsafe {
+            if *self.locked.get() {
+                panic!("Mutex locked twice");
+            }
+            *self.locked.get() = true;
+            let r = f(&mut *self.data.get());
+            *self.locked.get() = false;
+            r
+        }
+    }
+
+    /// No locking needed, `&mut self` is exclusive already.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+#[cfg(feature = "critical-section-impl")]
+mod critical_section_impl {
+    use super::{acquire, release, Token, SHARED_SPINLOCK};
+
+    struct RpCriticalSection;
+    critical_section::set_impl!(RpCriticalSection);
+
+    // interrupt state in bit 0, nested in bit 1
+    const NESTED: u32 = 2;
+
+    unsafe impl critical_section::Impl for RpCriticalSection {
+        unsafe fn acquire() -> critical_section::RawRestoreState {
+            let token = acquire(SHARED_SPINLOCK);
+            token.interrupts | if token.nested { NESTED } else { 0 }
+        }
+
+        unsafe fn release(state: critical_section::RawRestoreState) {
+            let token = Token {
+                interrupts: state & !NESTED,
+                nested: state & NESTED != 0,
+            };
+            release(SHARED_SPINLOCK, token);
+        }
+    }
+}
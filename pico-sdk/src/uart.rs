@@ -0,0 +1,170 @@
+//! embedded-hal serial over the two UARTs, 8N1.
+//!
+//! ```ignore
+//! let mut uart = Uart::uart1(&ClockSet::current(), Hertz(115_200)).with_pins(4, 5);
+//! writeln!(uart, "hello")?;
+//! ```
+//!
+//! stdio may already use `PICO_DEFAULT_UART`, leave that one alone.
+
+use core::convert::Infallible;
+use core::fmt;
+
+use embedded_hal::{blocking, serial};
+use rpi_pico_sdk_sys::*;
+
+use crate::clocks::{ClockSet, Hertz};
+use crate::dividers;
+use crate::gpio::GpioFunction;
+
+/// UART signals, a GPIO carries the one at `pin % 4`
+pub const TX: uint = 0;
+pub const RX: uint = 1;
+pub const CTS: uint = 2;
+pub const RTS: uint = 3;
+
+/// Whether `pin` can carry `signal` of UART `instance`: GPIO0-3, 12-19 and
+/// 28-29 are UART0.
+pub const fn is_valid_pin(instance: uint, signal: uint, pin: uint) -> bool {
+    pin < 30 && pin % 4 == signal && ((pin + 4) / 8) % 2 == instance
+}
+
+/// A receive error, the byte is dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The RX FIFO was full, bytes got lost before this one.
+    Overrun,
+    /// RX held low for longer than a byte.
+    Break,
+    Parity,
+    /// No stop bit where expected, likely a baud rate mismatch.
+    Framing,
+}
+
+pub struct Uart {
+    hw: *mut uart_inst_t,
+    clk_peri: Hertz,
+    baudrate: Hertz,
+}
+
+impl Drop for Uart {
+    fn drop(&mut self) {
+        unsafe {
+            uart_deinit(self.hw);
+        }
+    }
+}
+
+impl Uart {
+    /// UART0 at `baudrate`, or the closest to it, from `clocks.peri`.
+    pub fn uart0(clocks: &ClockSet, baudrate: Hertz) -> Self {
+        Self::new(uart0, clocks, baudrate)
+    }
+
+    /// UART1 at `baudrate`, or the closest to it, from `clocks.peri`.
+    pub fn uart1(clocks: &ClockSet, baudrate: Hertz) -> Self {
+        Self::new(uart1, clocks, baudrate)
+    }
+
+    fn new(hw: *mut uart_inst_t, clocks: &ClockSet, baudrate: Hertz) -> Self {
+        let mut u = Uart {
+            hw,
+            clk_peri: clocks.peri,
+            baudrate: Hertz(0),
+        };
+        unsafe {
+            uart_init(u.hw, baudrate.0);
+        }
+        u.set_baudrate(baudrate);
+        u
+    }
+
+    /// Route TX and RX to the given GPIOs.
+    pub fn with_pins(self, tx: uint, rx: uint) -> Self {
+        let instance = unsafe { uart_get_index(self.hw) };
+        debug_assert!(is_valid_pin(instance, TX, tx), "not a TX pin of this UART");
+        debug_assert!(is_valid_pin(instance, RX, rx), "not an RX pin of this UART");
+        unsafe {
+            gpio_set_function(tx, GpioFunction::UART as _);
+            gpio_set_function(rx, GpioFunction::UART as _);
+        }
+        self
+    }
+
+    /// Set the closest baud rate to `baudrate`, and return it.
+    pub fn set_baudrate(&mut self, baudrate: Hertz) -> Hertz {
+        let div = dividers::uart(self.clk_peri, baudrate);
+        unsafe {
+            let hw = &*uart_get_hw(self.hw);
+            hw.ibrd.write(div.ibrd as u32);
+            hw.fbrd.write(div.fbrd as u32);
+            // the dividers only take effect on a write to LCR_H
+            hw.lcr_h.set_bits(0);
+        }
+        self.baudrate = div.baudrate(self.clk_peri);
+        self.baudrate
+    }
+
+    pub fn baudrate(&self) -> Hertz {
+        self.baudrate
+    }
+}
+
+impl serial::Read<u8> for Uart {
+    type Error = Error;
+
+    fn read(&mut self) -> nb::Result<u8, Error> {
+        unsafe {
+            if !uart_is_readable(self.hw) {
+                return Err(nb::Error::WouldBlock);
+            }
+            let dr = (*uart_get_hw(self.hw)).dr.read();
+            let err = if dr & UART_UARTDR_OE_BITS != 0 {
+                Error::Overrun
+            } else if dr & UART_UARTDR_BE_BITS != 0 {
+                Error::Break
+            } else if dr & UART_UARTDR_PE_BITS != 0 {
+                Error::Parity
+            } else if dr & UART_UARTDR_FE_BITS != 0 {
+                Error::Framing
+            } else {
+                return Ok(dr as u8);
+            };
+            Err(nb::Error::Other(err))
+        }
+    }
+}
+
+impl serial::Write<u8> for Uart {
+    type Error = Infallible;
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Infallible> {
+        unsafe {
+            if !uart_is_writable(self.hw) {
+                return Err(nb::Error::WouldBlock);
+            }
+            (*uart_get_hw(self.hw)).dr.write(word as u32);
+        }
+        Ok(())
+    }
+
+    /// Until the last byte is out on the wire.
+    fn flush(&mut self) -> nb::Result<(), Infallible> {
+        let fr = unsafe { (*uart_get_hw(self.hw)).fr.read() };
+        if fr & UART_UARTFR_BUSY_BITS != 0 {
+            return Err(nb::Error::WouldBlock);
+        }
+        Ok(())
+    }
+}
+
+impl blocking::serial::write::Default<u8> for Uart {}
+
+impl fmt::Write for Uart {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        unsafe {
+            uart_write_blocking(self.hw, s.as_ptr(), s.len() as _);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,615 @@
+//! USB device: the `usb-device` crate's `UsbBus` on the RP2040 USB controller,
+//! for usbd-serial, usbd-hid or any other class driver.
+//!
+//! ```ignore
+//! let bus = UsbBusAllocator::new(UsbBus::new());
+//! let mut serial = SerialPort::new(&bus);
+//! let mut dev = UsbDeviceBuilder::new(&bus, UsbVidPid(0x2e8a, 0x000a))
+//!     .strings(&[StringDescriptors::default().product("Pico")])?
+//!     .device_class(usbd_serial::USB_CLASS_CDC)
+//!     .build();
+//! loop {
+//!     if dev.poll(&mut [&mut serial]) {
+//!         let mut buf = [0; 64];
+//!         if let Ok(n) = serial.read(&mut buf) {
+//!             let _ = serial.write(&buf[..n]);
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Poll at least every 10 ms, from the main loop or from the USBCTRL_IRQ with
+//! `set_irq_handler()`. clk_usb must be 48 MHz, as pico-sdk's runtime sets it.
+//!
+//! This owns the controller: don't link pico_stdio_usb or TinyUSB alongside.
+//! Endpoint buffers are in the 4 KB DPRAM, 64-byte aligned, 3712 bytes in all
+//! past EP0.
+//!
+//! Add a `ResetInterface` to the class list to let the host reboot the board,
+//! into BOOTSEL mode or back into the program, as pico_stdio_usb does.
+
+use core::ptr::{self, NonNull};
+use core::sync::atomic::{compiler_fence, AtomicPtr, Ordering};
+
+use rpi_pico_sdk_sys::*;
+use usb_device::bus::PollResult;
+use usb_device::class_prelude::*;
+use usb_device::{UsbDirection, UsbError};
+
+use crate::bootrom;
+use crate::sync::Mutex;
+
+/// Offset in DPRAM of EP0's buffer, shared by IN and OUT
+const EP0_BUF: u16 = 0x100;
+/// Offset in DPRAM of the first buffer of the other endpoints
+const EPX_BUF: u16 = 0x180;
+
+#[derive(Clone, Copy)]
+struct Endpoint {
+    ty: EndpointType,
+    max_packet_size: u16,
+    /// Offset in DPRAM
+    buf: u16,
+    /// DATA1 next, toggled after every packet
+    data1: bool,
+}
+
+impl Endpoint {
+    fn ctrl(&self) -> u32 {
+        let ty = match self.ty {
+            EndpointType::Control => USB_EP_CTRL_ENDPOINT_TYPE_VALUE_CONTROL,
+            EndpointType::Isochronous { .. } => USB_EP_CTRL_ENDPOINT_TYPE_VALUE_ISOCHRONOUS,
+            EndpointType::Bulk => USB_EP_CTRL_ENDPOINT_TYPE_VALUE_BULK,
+            EndpointType::Interrupt => USB_EP_CTRL_ENDPOINT_TYPE_VALUE_INTERRUPT,
+        };
+        USB_EP_CTRL::ENABLE.bits()
+            | USB_EP_CTRL::INTERRUPT_PER_BUFF.bits()
+            | USB_EP_CTRL::ENDPOINT_TYPE.value(ty)
+            | USB_EP_CTRL::BUFFER_ADDRESS.value(self.buf as u32)
+    }
+
+    /// The PID bit for the next packet, and toggle it
+    fn next_pid(&mut self) -> u32 {
+        let pid = if self.data1 {
+            USB_BUF_CTRL::DATA_PID.bits()
+        } else {
+            0
+        };
+        // isochronous endpoints always send DATA0
+        if !matches!(self.ty, EndpointType::Isochronous { .. }) {
+            self.data1 = !self.data1;
+        }
+        pid
+    }
+
+    fn buf_ptr(&self) -> *mut u8 {
+        (USBCTRL_DPRAM_BASE + self.buf as u32) as *mut u8
+    }
+}
+
+struct Inner {
+    in_: [Option<Endpoint>; USB_NUM_ENDPOINTS],
+    out: [Option<Endpoint>; USB_NUM_ENDPOINTS],
+    /// Offset in DPRAM of the next free buffer
+    next_buf: u16,
+    /// OUT endpoints with a packet not read yet, a bit per endpoint
+    out_ready: u16,
+}
+
+/// The USB controller in device mode, hand it to `UsbBusAllocator::new()`.
+pub struct UsbBus {
+    inner: Mutex<Inner>,
+}
+
+impl UsbBus {
+    /// Take the controller, it's reset by `UsbDeviceBuilder::build()`.
+    pub fn new() -> Self {
+        UsbBus {
+            inner: Mutex::new(Inner {
+                in_: [None; USB_NUM_ENDPOINTS],
+                out: [None; USB_NUM_ENDPOINTS],
+                next_buf: EPX_BUF,
+                out_ready: 0,
+            }),
+        }
+    }
+}
+
+impl Default for UsbBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn buf_ctrl(index: usize, dir: UsbDirection) -> &'static RW<u32, USB_BUF_CTRL> {
+    unsafe {
+        let ctrl = &(*usb_dpram).ep_buf_ctrl[index];
+        match dir {
+            UsbDirection::In => &ctrl.in_,
+            UsbDirection::Out => &ctrl.out,
+        }
+    }
+}
+
+/// Hand a buffer to the controller. DPRAM has no set/clear aliases, every
+/// write is a whole word: the rest first, then AVAILABLE once the controller,
+/// on the slower clk_usb, has seen it
+fn arm(ctrl: &RW<u32, USB_BUF_CTRL>, value: u32) {
+    ctrl.write(value);
+    busy_wait_at_least_cycles(12);
+    ctrl.write(value | USB_BUF_CTRL::AVAILABLE.bits());
+}
+
+impl Inner {
+    fn endpoint(&mut self, ep_addr: EndpointAddress) -> Result<&mut Endpoint, UsbError> {
+        let eps = match ep_addr.direction() {
+            UsbDirection::In => &mut self.in_,
+            UsbDirection::Out => &mut self.out,
+        };
+        eps.get_mut(ep_addr.index())
+            .and_then(Option::as_mut)
+            .ok_or(UsbError::InvalidEndpoint)
+    }
+
+    /// Ready to receive a packet on OUT endpoint `index`
+    fn arm_out(&mut self, index: usize) {
+        if let Some(ep) = self.out[index].as_mut() {
+            let value = ep.next_pid() | USB_BUF_CTRL::LEN.value(ep.max_packet_size as u32);
+            arm(buf_ctrl(index, UsbDirection::Out), value);
+        }
+    }
+
+    /// Every endpoint back to DATA0, nothing to send, OUT endpoints armed
+    fn reset_endpoints(&mut self) {
+        for index in 0..USB_NUM_ENDPOINTS {
+            if let Some(ep) = self.in_[index].as_mut() {
+                ep.data1 = false;
+                buf_ctrl(index, UsbDirection::In).write(0);
+            }
+            if let Some(ep) = self.out[index].as_mut() {
+                ep.data1 = false;
+                buf_ctrl(index, UsbDirection::Out).write(0);
+                // EP0 OUT waits for a SETUP
+                if index != 0 {
+                    self.arm_out(index);
+                }
+            }
+        }
+        self.out_ready = 0;
+    }
+
+    /// After a SETUP: the data stage, either way, starts with DATA1
+    fn setup_received(&mut self) {
+        unsafe {
+            (*usb_hw).ep_stall_arm.write(0);
+        }
+        buf_ctrl(0, UsbDirection::In).write(0);
+        if let Some(ep) = self.in_[0].as_mut() {
+            ep.data1 = true;
+        }
+        if let Some(ep) = self.out[0].as_mut() {
+            ep.data1 = true;
+        }
+        self.out_ready &= !1;
+        self.arm_out(0);
+    }
+}
+
+impl usb_device::bus::UsbBus for UsbBus {
+    fn alloc_ep(
+        &mut self,
+        ep_dir: UsbDirection,
+        ep_addr: Option<EndpointAddress>,
+        ep_type: EndpointType,
+        max_packet_size: u16,
+        _interval: u8,
+    ) -> usb_device::Result<EndpointAddress> {
+        let inner = self.inner.get_mut();
+        let eps = match ep_dir {
+            UsbDirection::In => &mut inner.in_,
+            UsbDirection::Out => &mut inner.out,
+        };
+
+        let index = match ep_addr {
+            Some(addr) if addr.index() >= USB_NUM_ENDPOINTS => {
+                return Err(UsbError::InvalidEndpoint)
+            }
+            Some(addr) if eps[addr.index()].is_some() => return Err(UsbError::InvalidEndpoint),
+            Some(addr) => addr.index(),
+            None if ep_type == EndpointType::Control && eps[0].is_none() => 0,
+            None => (1..USB_NUM_ENDPOINTS)
+                .find(|&i| eps[i].is_none())
+                .ok_or(UsbError::EndpointOverflow)?,
+        };
+
+        let max = match ep_type {
+            EndpointType::Isochronous { .. } => 1023,
+            _ => 64,
+        };
+        if max_packet_size > max || (index == 0 && ep_type != EndpointType::Control) {
+            return Err(UsbError::Unsupported);
+        }
+
+        let buf = if index == 0 {
+            EP0_BUF
+        } else {
+            let buf = inner.next_buf;
+            let size = (max_packet_size + 63) & !63;
+            if (buf + size) as usize > USB_DPRAM_SIZE {
+                return Err(UsbError::EndpointMemoryOverflow);
+            }
+            inner.next_buf = buf + size;
+            buf
+        };
+
+        eps[index] = Some(Endpoint {
+            ty: ep_type,
+            max_packet_size,
+            buf,
+            data1: false,
+        });
+        Ok(EndpointAddress::from_parts(index, ep_dir))
+    }
+
+    fn enable(&mut self) {
+        let inner = self.inner.get_mut();
+        unsafe {
+            reset_block(RESETS_RESET_USBCTRL_BITS);
+            unreset_block_wait(RESETS_RESET_USBCTRL_BITS);
+            ptr::write_bytes(usb_dpram as *mut u8, 0, USB_DPRAM_SIZE);
+
+            let usb = &*usb_hw;
+            usb.muxing
+                .write(USB_USB_MUXING::TO_PHY.bits() | USB_USB_MUXING::SOFTCON.bits());
+            // no VBUS detect pin on the Pico, pretend it's always there
+            usb.pwr.write(
+                USB_USB_PWR::VBUS_DETECT.bits() | USB_USB_PWR::VBUS_DETECT_OVERRIDE_EN.bits(),
+            );
+            usb.main_ctrl.write(USB_MAIN_CTRL::CONTROLLER_EN.bits());
+            usb.sie_ctrl.write(USB_SIE_CTRL::EP0_INT_1BUF.bits());
+            usb.inte.write(
+                USB_INTR::BUFF_STATUS.bits()
+                    | USB_INTR::BUS_RESET.bits()
+                    | USB_INTR::SETUP_REQ.bits()
+                    | USB_INTR::DEV_SUSPEND.bits()
+                    | USB_INTR::DEV_RESUME_FROM_HOST.bits(),
+            );
+
+            // EP0 has a fixed buffer and no control register
+            for index in 1..USB_NUM_ENDPOINTS {
+                let ctrl = &(*usb_dpram).ep_ctrl[index - 1];
+                if let Some(ep) = &inner.in_[index] {
+                    ctrl.in_.write(ep.ctrl());
+                }
+                if let Some(ep) = &inner.out[index] {
+                    ctrl.out.write(ep.ctrl());
+                }
+            }
+            inner.reset_endpoints();
+
+            usb.sie_ctrl.set_bits(USB_SIE_CTRL::PULLUP_EN.bits());
+        }
+    }
+
+    fn reset(&self) {
+        self.inner.lock(|inner| {
+            unsafe {
+                (*usb_hw).dev_addr_ctrl.write(0);
+            }
+            inner.reset_endpoints();
+        });
+    }
+
+    fn set_device_address(&self, addr: u8) {
+        unsafe {
+            (*usb_hw).dev_addr_ctrl.write(addr as u32);
+        }
+    }
+
+    fn write(&self, ep_addr: EndpointAddress, buf: &[u8]) -> usb_device::Result<usize> {
+        self.inner.lock(|inner| {
+            let ep = inner.endpoint(ep_addr)?;
+            if buf.len() > ep.max_packet_size as usize {
+                return Err(UsbError::BufferOverflow);
+            }
+            let ctrl = buf_ctrl(ep_addr.index(), UsbDirection::In);
+            if ctrl.is_set(USB_BUF_CTRL::AVAILABLE) {
+                return Err(UsbError::WouldBlock);
+            }
+
+            unsafe {
+                ptr::copy_nonoverlapping(buf.as_ptr(), ep.buf_ptr(), buf.len());
+            }
+            compiler_fence(Ordering::Release);
+            let value = ep.next_pid()
+                | USB_BUF_CTRL::FULL.bits()
+                | USB_BUF_CTRL::LEN.value(buf.len() as u32);
+            arm(ctrl, value);
+            Ok(buf.len())
+        })
+    }
+
+    fn read(&self, ep_addr: EndpointAddress, buf: &mut [u8]) -> usb_device::Result<usize> {
+        self.inner.lock(|inner| {
+            let index = ep_addr.index();
+            inner.endpoint(ep_addr)?;
+            let usb = unsafe { &*usb_hw };
+
+            if index == 0 && usb.sie_status.is_set(USB_SIE_STATUS::SETUP_REC) {
+                let setup = unsafe { &(*usb_dpram).setup_packet };
+                if buf.len() < setup.len() {
+                    return Err(UsbError::BufferOverflow);
+                }
+                for (b, reg) in buf.iter_mut().zip(setup) {
+                    *b = reg.read();
+                }
+                // write 1 to clear
+                usb.sie_status.write(USB_SIE_STATUS::SETUP_REC.bits());
+                inner.setup_received();
+                return Ok(setup.len());
+            }
+
+            if inner.out_ready & (1 << index) == 0 {
+                return Err(UsbError::WouldBlock);
+            }
+            let len = buf_ctrl(index, UsbDirection::Out).read_field(USB_BUF_CTRL::LEN) as usize;
+            if len > buf.len() {
+                return Err(UsbError::BufferOverflow);
+            }
+            compiler_fence(Ordering::Acquire);
+            let ep = inner.endpoint(ep_addr)?;
+            unsafe {
+                ptr::copy_nonoverlapping(ep.buf_ptr(), buf.as_mut_ptr(), len);
+            }
+            inner.out_ready &= !(1 << index);
+            inner.arm_out(index);
+            Ok(len)
+        })
+    }
+
+    fn set_stalled(&self, ep_addr: EndpointAddress, stalled: bool) {
+        self.inner.lock(|inner| {
+            let index = ep_addr.index();
+            let dir = ep_addr.direction();
+            let ep = match inner.endpoint(ep_addr) {
+                Ok(ep) => ep,
+                Err(_) => return,
+            };
+            let ctrl = buf_ctrl(index, dir);
+
+            if stalled {
+                if index == 0 {
+                    // EP0 stalls only with both, the arm bit is cleared by the next SETUP
+                    let arm = match dir {
+                        UsbDirection::In => USB_EP_STALL_ARM::EP0_IN,
+                        UsbDirection::Out => USB_EP_STALL_ARM::EP0_OUT,
+                    };
+                    unsafe {
+                        (*usb_hw).ep_stall_arm.set_field(arm);
+                    }
+                }
+                ctrl.write(ctrl.read() | USB_BUF_CTRL::STALL.bits());
+            } else if ctrl.is_set(USB_BUF_CTRL::STALL) {
+                // a cleared halt starts again at DATA0, except within a control transfer
+                if index != 0 {
+                    ep.data1 = false;
+                }
+                ctrl.write(0);
+                if dir == UsbDirection::Out {
+                    inner.arm_out(index);
+                }
+            }
+        })
+    }
+
+    fn is_stalled(&self, ep_addr: EndpointAddress) -> bool {
+        buf_ctrl(ep_addr.index(), ep_addr.direction()).is_set(USB_BUF_CTRL::STALL)
+    }
+
+    fn suspend(&self) {}
+
+    fn resume(&self) {}
+
+    fn poll(&self) -> PollResult {
+        self.inner.lock(|inner| {
+            let usb = unsafe { &*usb_hw };
+            let ints = usb.ints.read();
+
+            if ints & USB_INTR::BUS_RESET.bits() != 0 {
+                usb.sie_status.write(USB_SIE_STATUS::BUS_RESET.bits());
+                return PollResult::Reset;
+            }
+
+            // IN at bit 2n, OUT at bit 2n + 1
+            let status = usb.buf_status.read();
+            usb.buf_status.write(status);
+            let mut ep_in_complete = 0;
+            for index in 0..USB_NUM_ENDPOINTS {
+                if status & (1 << (2 * index)) != 0 {
+                    ep_in_complete |= 1 << index;
+                }
+                if status & (1 << (2 * index + 1)) != 0 {
+                    inner.out_ready |= 1 << index;
+                }
+            }
+            let ep_setup = if usb.sie_status.is_set(USB_SIE_STATUS::SETUP_REC) {
+                1
+            } else {
+                0
+            };
+            let ep_out = inner.out_ready | ep_setup;
+            if ep_out | ep_in_complete | ep_setup != 0 {
+                return PollResult::Data {
+                    ep_out,
+                    ep_in_complete,
+                    ep_setup,
+                };
+            }
+
+            if ints & USB_INTR::DEV_SUSPEND.bits() != 0 {
+                usb.sie_status.write(USB_SIE_STATUS::SUSPENDED.bits());
+                return PollResult::Suspend;
+            }
+            if ints & USB_INTR::DEV_RESUME_FROM_HOST.bits() != 0 {
+                usb.sie_status.write(USB_SIE_STATUS::RESUME.bits());
+                return PollResult::Resume;
+            }
+            PollResult::None
+        })
+    }
+}
+
+/// Handler of the USBCTRL_IRQ, a `fn()`
+static IRQ_HANDLER: AtomicPtr<()> = AtomicPtr::new(ptr::null_mut());
+
+unsafe extern "C" fn irq_trampoline() {
+    if let Some(handler) = NonNull::new(IRQ_HANDLER.load(Ordering::Acquire)) {
+        let handler: fn() = core::mem::transmute(handler.as_ptr());
+        handler();
+    }
+}
+
+/// Call `handler` on USB activity, on this core. It must poll the
+/// `UsbDevice`, which clears the interrupt, e.g. from a `Mutex`:
+///
+/// ```ignore
+/// static USB: Mutex<Option<(UsbDevice<'static, UsbBus>, SerialPort<'static, UsbBus>)>> = Mutex::new(None);
+///
+/// usb::set_irq_handler(|| {
+///     USB.lock(|usb| {
+///         if let Some((dev, serial)) = usb {
+///             dev.poll(&mut [serial]);
+///         }
+///     })
+/// });
+/// ```
+pub fn set_irq_handler(handler: fn()) {
+    IRQ_HANDLER.store(handler as *mut (), Ordering::Release);
+    unsafe {
+        irq_set_exclusive_handler(USBCTRL_IRQ, Some(irq_trampoline));
+        irq_set_enabled(USBCTRL_IRQ, true);
+    }
+}
+
+/// Back to polling from the main loop.
+pub fn remove_irq_handler() {
+    unsafe {
+        irq_set_enabled(USBCTRL_IRQ, false);
+        irq_remove_handler(USBCTRL_IRQ, Some(irq_trampoline));
+    }
+    IRQ_HANDLER.store(ptr::null_mut(), Ordering::Release);
+}
+
+const RESET_INTERFACE_CLASS: u8 = 0xff;
+const RESET_INTERFACE_SUBCLASS: u8 = 0x00;
+const RESET_INTERFACE_PROTOCOL: u8 = 0x01;
+
+const RESET_REQUEST_BOOTSEL: u8 = 0x01;
+const RESET_REQUEST_FLASH: u8 = 0x02;
+
+/// Delay of the reboot into the program, time for the status stage to go out
+const RESET_TO_FLASH_DELAY_MS: u32 = 100;
+
+/// The vendor interface of pico_stdio_usb that `picotool reboot` uses, with
+/// `-u` for BOOTSEL mode. picotool only looks at Raspberry Pi's VID 0x2e8a.
+///
+/// The BOOTSEL request's wValue carries the interface mask in bits 0 to 6,
+/// and an activity LED pin in bits 9 and up when bit 8 is set.
+pub struct ResetInterface {
+    interface: InterfaceNumber,
+    name: StringIndex,
+    activity_led: Option<uint>,
+    /// BOOTSEL requested, after the status stage
+    bootsel: Option<(Option<uint>, u32)>,
+    acked: bool,
+}
+
+impl ResetInterface {
+    pub fn new<B: usb_device::bus::UsbBus>(alloc: &UsbBusAllocator<B>) -> Self {
+        ResetInterface {
+            interface: alloc.interface(),
+            name: alloc.string(),
+            activity_led: None,
+            bootsel: None,
+            acked: false,
+        }
+    }
+
+    /// The activity LED in BOOTSEL mode when the host doesn't give one.
+    pub fn activity_led(mut self, pin: uint) -> Self {
+        self.activity_led = Some(pin);
+        self
+    }
+}
+
+impl<B: usb_device::bus::UsbBus> UsbClass<B> for ResetInterface {
+    fn get_configuration_descriptors(
+        &self,
+        writer: &mut DescriptorWriter,
+    ) -> usb_device::Result<()> {
+        writer.interface_alt(
+            self.interface,
+            0,
+            RESET_INTERFACE_CLASS,
+            RESET_INTERFACE_SUBCLASS,
+            RESET_INTERFACE_PROTOCOL,
+            Some(self.name),
+        )
+    }
+
+    fn get_string(&self, index: StringIndex, _lang_id: LangID) -> Option<&str> {
+        if index == self.name {
+            Some("Reset")
+        } else {
+            None
+        }
+    }
+
+    fn reset(&mut self) {
+        self.bootsel = None;
+        self.acked = false;
+    }
+
+    fn control_out(&mut self, xfer: ControlOut<B>) {
+        let req = *xfer.request();
+        if req.request_type != control::RequestType::Vendor
+            || req.recipient != control::Recipient::Interface
+            || req.index != u8::from(self.interface) as u16
+        {
+            return;
+        }
+        match req.request {
+            RESET_REQUEST_BOOTSEL => {
+                let pin = (req.value >> 9) as uint;
+                // the host's pin, if it's one we have
+                let led = if req.value & 0x100 != 0 && pin < NUM_BANK0_GPIOS {
+                    Some(pin)
+                } else {
+                    self.activity_led
+                };
+                self.bootsel = Some((led, (req.value & 0x7f) as u32));
+                self.acked = false;
+                xfer.accept().ok();
+            }
+            RESET_REQUEST_FLASH => {
+                unsafe {
+                    watchdog_reboot(0, 0, RESET_TO_FLASH_DELAY_MS);
+                }
+                xfer.accept().ok();
+            }
+            _ => {
+                xfer.reject().ok();
+            }
+        }
+    }
+
+    fn poll(&mut self) {
+        // the first poll is the SETUP's, the next one has the status stage out
+        if let Some((led, mask)) = self.bootsel {
+            if self.acked {
+                bootrom::reset_to_usb_boot(led, mask);
+            }
+            self.acked = true;
+        }
+    }
+}
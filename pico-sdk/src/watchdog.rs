@@ -0,0 +1,152 @@
+//! The watchdog, its scratch registers and why the chip last reset.
+//!
+//! ```ignore
+//! let mut wd = Watchdog::new().pause_on_debug(true);
+//! let mut stage = 0;
+//! if reset_reason() == ResetReason::WatchdogTimeout {
+//!     // stuck last time, skip ahead
+//!     stage = wd.scratch(0) + 1;
+//! }
+//! wd.start(500u32);
+//! loop {
+//!     wd.set_scratch(0, stage);
+//!     stage = work(stage);
+//!     wd.feed();
+//! }
+//! ```
+
+use embedded_hal::watchdog;
+use rpi_pico_sdk_sys::*;
+
+/// Longest timeout in ms, longer ones are cut to it. The counter is 24 bits
+/// and counts down twice per µs tick.
+pub const MAX_TIMEOUT_MS: u32 = 0xff_ffff / 2000;
+
+/// Scratch registers, kept across a watchdog reset but not across a power-on
+/// or RUN pin reset.
+pub const NUM_SCRATCH: usize = 8;
+
+/// Why the chip last reset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetReason {
+    /// Power-on or brown-out.
+    PowerOn,
+    /// The RUN pin, e.g. a reset button.
+    RunPin,
+    /// The watchdog wasn't fed in time, or pico-sdk's `watchdog_reboot()`:
+    /// it lets the watchdog run out.
+    WatchdogTimeout,
+    /// A forced reboot, `reboot()`.
+    WatchdogForce,
+    /// The debugger, through the rescue debug port.
+    Debugger,
+}
+
+/// Why the chip last reset. The watchdog is checked first, its reason is
+/// cleared by any other reset.
+pub fn reset_reason() -> ResetReason {
+    unsafe {
+        let reason = &(*watchdog_hw).reason;
+        if reason.is_set(WATCHDOG_REASON::TIMER) {
+            return ResetReason::WatchdogTimeout;
+        }
+        if reason.is_set(WATCHDOG_REASON::FORCE) {
+            return ResetReason::WatchdogForce;
+        }
+        let chip_reset = &(*vreg_and_chip_reset_hw).chip_reset;
+        if chip_reset.is_set(VREG_AND_CHIP_RESET_CHIP_RESET::HAD_PSM_RESTART) {
+            ResetReason::Debugger
+        } else if chip_reset.is_set(VREG_AND_CHIP_RESET_CHIP_RESET::HAD_RUN) {
+            ResetReason::RunPin
+        } else {
+            ResetReason::PowerOn
+        }
+    }
+}
+
+/// Reboot right away, through the watchdog. Resets everything but the
+/// oscillators, as pico-sdk's `watchdog_reboot()` does, and boots normally.
+pub fn reboot() -> ! {
+    unsafe {
+        let wd = &*watchdog_hw;
+        wd.ctrl.clear_field(WATCHDOG_CTRL::ENABLE);
+        // no entry point for the bootrom to jump to
+        wd.scratch[4].write(0);
+        // the watchdog resets only the blocks selected here, none after a power-on
+        let all = PSM_WDSEL::PROC1.bits() | (PSM_WDSEL::PROC1.bits() - 1);
+        let oscillators = PSM_WDSEL::ROSC.bits() | PSM_WDSEL::XOSC.bits();
+        (*psm_hw).wdsel.set_bits(all & !oscillators);
+        wd.ctrl.set_field(WATCHDOG_CTRL::TRIGGER);
+    }
+    loop {
+        __wfe();
+    }
+}
+
+/// The watchdog, through pico-sdk: it expects the 1 µs tick pico-sdk's
+/// runtime starts from the 12 MHz XOSC.
+pub struct Watchdog {
+    pause_on_debug: bool,
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        Watchdog {
+            pause_on_debug: false,
+        }
+    }
+
+    /// Stop the countdown while a debugger halts either core, so stepping
+    /// doesn't reset the chip. Applied by the next `start()`.
+    pub fn pause_on_debug(mut self, pause: bool) -> Self {
+        self.pause_on_debug = pause;
+        self
+    }
+
+    /// Scratch register `index`, see `set_scratch()`.
+    pub fn scratch(&self, index: usize) -> u32 {
+        unsafe { (*watchdog_hw).scratch[index].read() }
+    }
+
+    /// Keep `value` across a watchdog reset. Registers 4 to 7 are for the
+    /// bootrom: pico-sdk's `watchdog_reboot()` overwrites them, and the bootrom
+    /// jumps to what they hold if it finds its magic in there. 0 to 3 are free.
+    pub fn set_scratch(&mut self, index: usize, value: u32) {
+        unsafe { (*watchdog_hw).scratch[index].write(value) }
+    }
+
+    /// Time left before the reset, in µs.
+    pub fn remaining_us(&self) -> u32 {
+        unsafe { watchdog_get_count() }
+    }
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl watchdog::Watchdog for Watchdog {
+    fn feed(&mut self) {
+        unsafe { watchdog_update() }
+    }
+}
+
+impl watchdog::WatchdogEnable for Watchdog {
+    /// Timeout in ms, up to `MAX_TIMEOUT_MS`. 0 resets right away.
+    type Time = u32;
+
+    fn start<T: Into<u32>>(&mut self, period: T) {
+        let ms = period.into().min(MAX_TIMEOUT_MS);
+        unsafe { watchdog_enable(ms, self.pause_on_debug) }
+    }
+}
+
+impl watchdog::WatchdogDisable for Watchdog {
+    fn disable(&mut self) {
+        unsafe {
+            (*watchdog_hw).ctrl.clear_field(WATCHDOG_CTRL::ENABLE);
+        }
+    }
+}
@@ -0,0 +1,78 @@
+#!/usr/bin/env python3
+"""Writes randombit.bin, the bitstream of rng's `simulated_randombit` test,
+and prints the words `Trng` must make of it.
+
+Not a capture from a board: the bits are simulated, biased towards 1 and
+correlated from one bit to the next, as ROSC RANDOMBIT is. The words are
+worked out here, apart from the Rust code: von Neumann on the raw pairs,
+64 bits per word MSB first, murmur3's fmix64 chained through the pool.
+"""
+
+import os
+import random
+
+RAW_BYTES = 512
+P_ONE = 0.7  # bias
+P_REPEAT = 0.2  # chance of repeating the previous bit instead of a fresh one
+MAX_EQUAL_PAIRS = 64
+MASK = (1 << 64) - 1
+
+
+def fmix64(x):
+    x ^= x >> 33
+    x = (x * 0xFF51AFD7ED558CCD) & MASK
+    x ^= x >> 33
+    x = (x * 0xC4CEB9FE1A85EC53) & MASK
+    return x ^ (x >> 33)
+
+
+def simulate(rng, n_bits):
+    bits = []
+    bit = 0
+    for _ in range(n_bits):
+        if not bits or rng.random() >= P_REPEAT:
+            bit = int(rng.random() < P_ONE)
+        bits.append(bit)
+    return bits
+
+
+def pack(bits):
+    """LSB first, as rng::unpack() reads them"""
+    return bytes(sum(b << i for i, b in enumerate(bits[n : n + 8])) for n in range(0, len(bits), 8))
+
+
+def words(raw):
+    unbiased = []
+    equal = 0
+    for first, second in zip(raw[::2], raw[1::2]):
+        if first != second:
+            unbiased.append(first)
+            equal = 0
+        else:
+            equal += 1
+            assert equal < MAX_EQUAL_PAIRS, "would be Error::Stuck"
+    pool = 0
+    out = []
+    for n in range(0, len(unbiased) - 63, 64):
+        value = 0
+        for b in unbiased[n : n + 64]:
+            value = value << 1 | b
+        pool = fmix64(pool ^ value)
+        out.append(pool >> 32)
+    return out
+
+
+def main():
+    raw = simulate(random.Random(2040), RAW_BYTES * 8)
+    out = os.path.join(os.path.dirname(os.path.abspath(__file__)), "randombit.bin")
+    with open(out, "wb") as f:
+        f.write(pack(raw))
+    print("ones: {:.2f}".format(sum(raw) / len(raw)))
+    print("words:", ", ".join("0x{:08x}".format(w) for w in words(raw)))
+    # the by-hand case of the test: 0x66 is the pairs 01 10 01 10, 0x0f two
+    # 11 and two 00, dropped, so 64 bits 0101...01
+    print("0x66/0x0f: 0x{:08x}".format(fmix64(0x5555555555555555) >> 32))
+
+
+if __name__ == "__main__":
+    main()
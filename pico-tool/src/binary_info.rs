@@ -0,0 +1,323 @@
+//! Binary info embedded by pico-sdk's `bi_decl()` (and rpi-pico-sdk), as read by picotool.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::image::{Image, Segment, FLASH_START, SRAM_START};
+
+pub const MARKER_START: u32 = 0x7188_ebf2;
+pub const MARKER_END: u32 = 0xe71a_a390;
+
+pub const TYPE_ID_AND_INT: u16 = 5;
+pub const TYPE_ID_AND_STRING: u16 = 6;
+pub const TYPE_PINS_WITH_FUNC: u16 = 8;
+pub const TYPE_PINS_WITH_NAME: u16 = 9;
+
+/// BINARY_INFO_MAKE_TAG('R', 'P')
+pub const TAG_RASPBERRY_PI: u16 = (b'P' as u16) << 8 | b'R' as u16;
+
+pub const ID_RP_PROGRAM_NAME: u32 = 0x0203_1c86;
+pub const ID_RP_PROGRAM_VERSION_STRING: u32 = 0x11a9_bc3a;
+pub const ID_RP_PROGRAM_BUILD_DATE_STRING: u32 = 0x9da2_2254;
+pub const ID_RP_BINARY_END: u32 = 0x68f4_65de;
+pub const ID_RP_PROGRAM_URL: u32 = 0x1856_239a;
+pub const ID_RP_PROGRAM_DESCRIPTION: u32 = 0xb6a0_7c19;
+pub const ID_RP_PROGRAM_FEATURE: u32 = 0xa1f4_b453;
+pub const ID_RP_PROGRAM_BUILD_ATTRIBUTE: u32 = 0x4275_f0d3;
+pub const ID_RP_SDK_VERSION: u32 = 0x5360_b3ab;
+pub const ID_RP_PICO_BOARD: u32 = 0xb63c_ffbb;
+pub const ID_RP_BOOT2_NAME: u32 = 0x7f88_82e1;
+
+pub const PINS_ENCODING_RANGE: u32 = 1;
+pub const PINS_ENCODING_MULTI: u32 = 2;
+
+/// GPIO function select names, same order as `gpio_function`
+const GPIO_FUNCTIONS: [&str; 10] = [
+    "XIP", "SPI", "UART", "I2C", "PWM", "SIO", "PIO0", "PIO1", "GPCK", "USB",
+];
+
+#[derive(Debug, Default)]
+pub struct BinaryInfo {
+    pub program_name: Option<String>,
+    pub program_version: Option<String>,
+    pub program_build_date: Option<String>,
+    pub program_url: Option<String>,
+    pub program_description: Option<String>,
+    pub program_features: Vec<String>,
+    pub build_attributes: Vec<String>,
+    pub sdk_version: Option<String>,
+    pub pico_board: Option<String>,
+    pub boot2_name: Option<String>,
+    pub binary_end: Option<u32>,
+    /// pin => what it is used for
+    pub pins: BTreeMap<u32, Vec<String>>,
+    /// Entries not understood, as (type, tag)
+    pub unknown: Vec<(u16, u16)>,
+}
+
+/// Find the header in the first 256 bytes of the image (after boot2 for flash binaries).
+fn find_header(image: &Image) -> Option<(u32, u32, u32)> {
+    let base = if image.is_flash() {
+        FLASH_START + 0x100
+    } else {
+        SRAM_START
+    };
+    let words: Vec<u32> = (0..64)
+        .filter_map(|i| image.read_u32(base + i * 4))
+        .collect();
+    words.windows(5).find_map(|w| {
+        if w[0] == MARKER_START && w[4] == MARKER_END {
+            Some((w[1], w[2], w[3]))
+        } else {
+            None
+        }
+    })
+}
+
+/// Apply the data copy table, so pointers into `.data` resolve for UF2 images too.
+fn apply_mapping_table(image: &mut Image, mut table: u32) {
+    loop {
+        let word = |off: u32| table.checked_add(off).and_then(|addr| image.read_u32(addr));
+        let (source, dest_start, dest_end) = match (word(0), word(4), word(8)) {
+            (Some(s), Some(ds), Some(de)) if s != 0 && de >= ds => (s, ds, de),
+            _ => break,
+        };
+        if image.runtime.iter().any(|s| s.contains(dest_start)) {
+            // already known, e.g. from an ELF's p_vaddr
+        } else if let Some(data) = image.read_load(source, (dest_end - dest_start) as usize) {
+            let data = data.to_vec();
+            image.runtime.extend(Segment::new(dest_start, data));
+        }
+        table = match table.checked_add(12) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+}
+
+fn decode_pins(encoding: u32) -> Vec<u32> {
+    match encoding & 7 {
+        PINS_ENCODING_RANGE => {
+            let lo = (encoding >> 7) & 0x1f;
+            let hi = (encoding >> 12) & 0x1f;
+            (lo..=hi).collect()
+        }
+        PINS_ENCODING_MULTI => {
+            let mut pins = Vec::new();
+            let mut last = None;
+            for shift in (7..32).step_by(5) {
+                let pin = (encoding >> shift) & 0x1f;
+                if Some(pin) == last {
+                    break;
+                }
+                pins.push(pin);
+                last = Some(pin);
+            }
+            pins
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Read all binary info from an image. Returns `None` if there is no header.
+pub fn read(image: &Image) -> Option<BinaryInfo> {
+    let (start, end, mapping_table) = find_header(image)?;
+
+    let mut image = image.clone();
+    apply_mapping_table(&mut image, mapping_table);
+
+    // the entry pointers are one array, in one segment: a bogus `end` stops there
+    let segment_end = image
+        .runtime
+        .iter()
+        .chain(image.load.iter())
+        .find(|s| s.contains(start))?
+        .end();
+    let end = u64::from(end).min(segment_end);
+
+    let mut info = BinaryInfo::default();
+    let mut ptr = start;
+    while u64::from(ptr) < end {
+        if let Some(entry) = image.read_u32(ptr) {
+            read_entry(&image, entry, &mut info);
+        }
+        ptr = match ptr.checked_add(4) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    Some(info)
+}
+
+fn read_entry(image: &Image, entry: u32, info: &mut BinaryInfo) {
+    let u16_at = |off: u32| entry.checked_add(off).and_then(|addr| image.read_u16(addr));
+    let u32_at = |off: u32| entry.checked_add(off).and_then(|addr| image.read_u32(addr));
+    let (ty, tag) = match (u16_at(0), u16_at(2)) {
+        (Some(ty), Some(tag)) => (ty, tag),
+        _ => return,
+    };
+    if tag != TAG_RASPBERRY_PI {
+        info.unknown.push((ty, tag));
+        return;
+    }
+
+    match ty {
+        TYPE_ID_AND_INT => {
+            let id = u32_at(4);
+            let value = u32_at(8);
+            match (id, value) {
+                (Some(ID_RP_BINARY_END), Some(value)) => info.binary_end = Some(value),
+                _ => info.unknown.push((ty, tag)),
+            }
+        }
+        TYPE_ID_AND_STRING => {
+            let id = u32_at(4).unwrap_or(0);
+            let value = match u32_at(8).and_then(|p| image.read_cstr(p)) {
+                Some(value) => value,
+                None => return,
+            };
+            match id {
+                ID_RP_PROGRAM_NAME => info.program_name = Some(value),
+                ID_RP_PROGRAM_VERSION_STRING => info.program_version = Some(value),
+                ID_RP_PROGRAM_BUILD_DATE_STRING => info.program_build_date = Some(value),
+                ID_RP_PROGRAM_URL => info.program_url = Some(value),
+                ID_RP_PROGRAM_DESCRIPTION => info.program_description = Some(value),
+                ID_RP_PROGRAM_FEATURE => info.program_features.push(value),
+                ID_RP_PROGRAM_BUILD_ATTRIBUTE => info.build_attributes.push(value),
+                ID_RP_SDK_VERSION => info.sdk_version = Some(value),
+                ID_RP_PICO_BOARD => info.pico_board = Some(value),
+                ID_RP_BOOT2_NAME => info.boot2_name = Some(value),
+                _ => info.unknown.push((ty, tag)),
+            }
+        }
+        TYPE_PINS_WITH_FUNC => {
+            if let Some(encoding) = u32_at(4) {
+                let func = ((encoding >> 3) & 0xf) as usize;
+                let name = GPIO_FUNCTIONS.get(func).copied().unwrap_or("?");
+                for pin in decode_pins(encoding) {
+                    info.pins.entry(pin).or_default().push(name.to_string());
+                }
+            }
+        }
+        TYPE_PINS_WITH_NAME => {
+            let mask = u32_at(4).unwrap_or(0);
+            let label = u32_at(8)
+                .and_then(|p| image.read_cstr(p))
+                .unwrap_or_default();
+            // one label per set bit, separated by '|', the last one repeats
+            let labels: Vec<&str> = label.split('|').collect();
+            let mut n = 0;
+            for pin in 0..32 {
+                if mask & (1 << pin) != 0 {
+                    let label = labels.get(n).or_else(|| labels.last()).unwrap_or(&"");
+                    info.pins.entry(pin).or_default().push(label.to_string());
+                    n += 1;
+                }
+            }
+        }
+        _ => info.unknown.push((ty, tag)),
+    }
+}
+
+impl fmt::Display for BinaryInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn line(f: &mut fmt::Formatter<'_>, key: &str, value: &Option<String>) -> fmt::Result {
+            match value {
+                Some(value) => writeln!(f, " {:<20} {}", key, value),
+                None => Ok(()),
+            }
+        }
+
+        writeln!(f, "Program Information")?;
+        line(f, "name:", &self.program_name)?;
+        line(f, "version:", &self.program_version)?;
+        line(f, "web site:", &self.program_url)?;
+        line(f, "description:", &self.program_description)?;
+        for feature in &self.program_features {
+            writeln!(f, " {:<20} {}", "feature:", feature)?;
+        }
+        if let Some(end) = self.binary_end {
+            writeln!(f, " {:<20} {:#010x}", "binary end:", end)?;
+        }
+
+        if !self.pins.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "Fixed Pin Information")?;
+            for (pin, uses) in &self.pins {
+                writeln!(f, " {:<20} {}", pin, uses.join(", "))?;
+            }
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Build Information")?;
+        line(f, "sdk version:", &self.sdk_version)?;
+        line(f, "pico_board:", &self.pico_board)?;
+        line(f, "boot2_name:", &self.boot2_name)?;
+        line(f, "build date:", &self.program_build_date)?;
+        for attr in &self.build_attributes {
+            writeln!(f, " {:<20} {}", "build attribute:", attr)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::uf2;
+
+    /// Written by tests/fixtures/sample_elf.py
+    const SAMPLE_ELF: &[u8] = include_bytes!("../tests/fixtures/sample.elf");
+    /// File offset of the header's `end` pointer in the sample
+    const HEADER_END_OFFSET: usize = 396;
+
+    fn check_sample(info: &BinaryInfo) {
+        assert_eq!(info.program_name.as_deref(), Some("blink"));
+        assert_eq!(info.program_version.as_deref(), Some("0.1.0"));
+        assert_eq!(info.binary_end, Some(0x1000_0408));
+        assert_eq!(info.pins.get(&25), Some(&vec!["LED".to_string()]));
+        assert_eq!(info.pins.len(), 1);
+        assert!(info.unknown.is_empty());
+    }
+
+    #[test]
+    fn from_elf() {
+        let image = Image::from_elf(SAMPLE_ELF).unwrap();
+        check_sample(&read(&image).unwrap());
+    }
+
+    #[test]
+    fn from_uf2() {
+        let elf = Image::from_elf(SAMPLE_ELF).unwrap();
+        let image =
+            Image::from_uf2(&uf2::from_image(&elf, uf2::RP2040_FAMILY_ID).unwrap()).unwrap();
+        // the name is in .data, only found through the mapping table
+        assert_eq!(image.read_cstr(SRAM_START), None);
+        check_sample(&read(&image).unwrap());
+    }
+
+    #[test]
+    fn bogus_end() {
+        let mut elf = SAMPLE_ELF.to_vec();
+        elf[HEADER_END_OFFSET..HEADER_END_OFFSET + 4]
+            .copy_from_slice(&0xffff_fff0u32.to_le_bytes());
+        let image = Image::from_elf(&elf).unwrap();
+        // walks to the end of the segment, the entries are still there
+        let info = read(&image).unwrap();
+        assert_eq!(info.program_name.as_deref(), Some("blink"));
+    }
+
+    #[test]
+    fn decode_pin_encodings() {
+        // range encoding: pins 2..=5
+        assert_eq!(
+            decode_pins(PINS_ENCODING_RANGE | 2 << 7 | 5 << 12),
+            vec![2, 3, 4, 5]
+        );
+        // multi encoding: pins 0 and 1, the repeat ends the list
+        assert_eq!(
+            decode_pins(PINS_ENCODING_MULTI | 1 << 12 | 1 << 17),
+            vec![0, 1]
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! Stage-2 bootloader checks.
+//!
+//! The bootrom loads the first 256 bytes of flash and only runs them if the
+//! CRC32 of the first 252 bytes matches the last 4 bytes.
+
+use crate::image::{Image, FLASH_START};
+
+pub const BOOT2_SIZE: usize = 256;
+
+/// CRC-32/MPEG-2: poly 0x04c11db7, init 0xffffffff, not reflected, no final xor.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &b in data {
+        crc ^= (b as u32) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04c1_1db7
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Boot2Status {
+    /// RAM-only binary, no boot2 needed
+    NotApplicable,
+    Missing,
+    Valid {
+        crc: u32,
+    },
+    Invalid {
+        expected: u32,
+        found: u32,
+    },
+}
+
+pub fn check(image: &Image) -> Boot2Status {
+    if !image.is_flash() {
+        return Boot2Status::NotApplicable;
+    }
+    let boot2 = match image.read_load(FLASH_START, BOOT2_SIZE) {
+        Some(boot2) => boot2,
+        None => return Boot2Status::Missing,
+    };
+    let expected = crc32(&boot2[..BOOT2_SIZE - 4]);
+    let found = u32::from_le_bytes([boot2[252], boot2[253], boot2[254], boot2[255]]);
+    if expected == found {
+        Boot2Status::Valid { crc: found }
+    } else {
+        Boot2Status::Invalid { expected, found }
+    }
+}
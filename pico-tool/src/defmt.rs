@@ -0,0 +1,722 @@
+//! defmt log decoder, for rpi-pico-sdk's `defmt-uart`, `defmt-usb` and `defmt-rtt`.
+//!
+//! Format strings come from the `.defmt` symbols of the ELF: the symbol name is
+//! a JSON object with the `tag` (log level, or kind of string) and the string
+//! itself, the symbol value is its index. Frames are rzCOBS encoded and end with
+//! a 0x00 byte, the decoded frame is the message index, the timestamp and the
+//! arguments, all little-endian.
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::fmt::Write;
+use std::io::BufRead;
+
+use defmt_parser::{DisplayHint, Fragment, ParserMode, TimePrecision, Type};
+use goblin::elf::Elf;
+
+use crate::Result;
+
+/// One interned string
+#[derive(Debug, Clone)]
+pub struct Entry {
+    /// `defmt_info`, `defmt_fmt`, `defmt_derived`, ...
+    pub tag: String,
+    pub format: String,
+}
+
+impl Entry {
+    /// Log level, `None` for `println!` and for strings that are not messages.
+    pub fn level(&self) -> Option<&'static str> {
+        match self.tag.as_str() {
+            "defmt_trace" => Some("TRACE"),
+            "defmt_debug" => Some("DEBUG"),
+            "defmt_info" => Some("INFO"),
+            "defmt_warn" => Some("WARN"),
+            "defmt_error" => Some("ERROR"),
+            _ => None,
+        }
+    }
+}
+
+/// A decoded log message
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub timestamp: Option<String>,
+    pub level: Option<&'static str>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(timestamp) = &self.timestamp {
+            write!(f, "{} ", timestamp)?;
+        }
+        if let Some(level) = self.level {
+            write!(f, "{:<5} ", level)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// The interned strings of a program
+#[derive(Debug, Default)]
+pub struct Table {
+    entries: BTreeMap<u16, Entry>,
+    timestamp: Option<String>,
+}
+
+impl Table {
+    pub fn from_elf(bytes: &[u8]) -> Result<Table> {
+        let elf = Elf::parse(bytes)?;
+        let section = elf
+            .section_headers
+            .iter()
+            .position(|sh| elf.shdr_strtab.get_at(sh.sh_name) == Some(".defmt"))
+            .ok_or("no .defmt section, is the program linked with -Tdefmt.x?")?;
+
+        let mut table = Table::default();
+        for sym in elf.syms.iter() {
+            if sym.st_shndx != section {
+                continue;
+            }
+            let name = match elf.strtab.get_at(sym.st_name) {
+                Some(name) if name.starts_with('{') => name,
+                // linker markers, e.g. __DEFMT_MARKER_INFO_START
+                _ => continue,
+            };
+            let json: serde_json::Value = serde_json::from_str(name)?;
+            let tag = json["tag"].as_str().unwrap_or_default().to_string();
+            let format = json["data"].as_str().unwrap_or_default().to_string();
+            if tag == "defmt_timestamp" {
+                table.timestamp = Some(format);
+            } else {
+                table
+                    .entries
+                    .insert(sym.st_value as u16, Entry { tag, format });
+            }
+        }
+        if table.entries.is_empty() {
+            return Err("no defmt strings found".into());
+        }
+        Ok(table)
+    }
+
+    pub fn get(&self, index: u16) -> Option<&Entry> {
+        self.entries.get(&index)
+    }
+
+    /// Decode one frame, already rzCOBS decoded.
+    pub fn decode(&self, frame: &[u8]) -> Result<Frame> {
+        let mut r = Reader(frame);
+        let index = r.u16()?;
+        let entry = self
+            .get(index)
+            .ok_or_else(|| format!("unknown message index {}", index))?;
+        let timestamp = match &self.timestamp {
+            Some(format) => Some(self.format(&mut r, format)?),
+            None => None,
+        };
+        let message = self.format(&mut r, &entry.format)?;
+        Ok(Frame {
+            timestamp,
+            level: entry.level(),
+            message,
+        })
+    }
+
+    /// Decode the arguments of `format` and render it.
+    fn format(&self, r: &mut Reader, format: &str) -> Result<String> {
+        let fragments = defmt_parser::parse(format, ParserMode::ForwardsCompatible)
+            .map_err(|e| format!("bad format string {:?}: {}", format, e))?;
+
+        // arguments are sent once each, in index order; all bitfields of one
+        // argument share a single value covering their bit ranges
+        let mut types: BTreeMap<usize, Type> = BTreeMap::new();
+        for fragment in &fragments {
+            if let Fragment::Parameter(param) = fragment {
+                let ty = types.entry(param.index).or_insert_with(|| param.ty.clone());
+                if let (Type::BitField(all), Type::BitField(range)) = (ty, &param.ty) {
+                    all.start = all.start.min(range.start);
+                    all.end = all.end.max(range.end);
+                }
+            }
+        }
+        let mut args = BTreeMap::new();
+        for (index, ty) in types {
+            args.insert(index, self.read_arg(r, &ty)?);
+        }
+
+        let mut out = String::new();
+        for fragment in &fragments {
+            match fragment {
+                Fragment::Literal(s) => out.push_str(s),
+                Fragment::Parameter(param) => render(
+                    &mut out,
+                    &args[&param.index],
+                    &param.ty,
+                    param.hint.as_ref(),
+                ),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Data of a `Format` value, whose format string is `tag`.
+    fn format_tagged(&self, r: &mut Reader, tag: u16) -> Result<String> {
+        let entry = self
+            .get(tag)
+            .ok_or_else(|| format!("unknown format index {}", tag))?;
+        if entry.tag != "defmt_derived" || !entry.format.contains('|') {
+            return self.format(r, &entry.format);
+        }
+
+        // derived enum: `A|B({=u8})|C`, the variant index comes first
+        let variants: Vec<&str> = entry.format.split('|').collect();
+        let discriminant = if variants.len() <= u8::MAX as usize {
+            r.u8()? as usize
+        } else if variants.len() <= u16::MAX as usize {
+            r.u16()? as usize
+        } else {
+            r.u32()? as usize
+        };
+        let variant = variants
+            .get(discriminant)
+            .ok_or_else(|| format!("bad variant {} of {:?}", discriminant, entry.format))?;
+        self.format(r, variant)
+    }
+
+    fn read_arg(&self, r: &mut Reader, ty: &Type) -> Result<Arg> {
+        Ok(match ty {
+            Type::Bool => Arg::Bool(r.u8()? != 0),
+            Type::Char => Arg::Char(std::char::from_u32(r.u32()?).unwrap_or('\u{fffd}')),
+            Type::U8 => Arg::Uxx(r.u8()? as u128),
+            Type::U16 => Arg::Uxx(r.u16()? as u128),
+            Type::U32 | Type::Usize => Arg::Uxx(r.u32()? as u128),
+            Type::U64 => Arg::Uxx(r.u64()? as u128),
+            Type::U128 => Arg::Uxx(r.u128()?),
+            Type::I8 => Arg::Ixx(r.u8()? as i8 as i128),
+            Type::I16 => Arg::Ixx(r.u16()? as i16 as i128),
+            Type::I32 | Type::Isize => Arg::Ixx(r.u32()? as i32 as i128),
+            Type::I64 => Arg::Ixx(r.u64()? as i64 as i128),
+            Type::I128 => Arg::Ixx(r.u128()? as i128),
+            Type::F32 => Arg::F32(f32::from_bits(r.u32()?)),
+            Type::F64 => Arg::F64(f64::from_bits(r.u64()?)),
+            Type::BitField(range) => {
+                // only the bytes holding the ranges are sent, shifted down
+                let lowest_byte = range.start / 8;
+                let size = (range.end - 1) / 8 - lowest_byte + 1;
+                let value = match size {
+                    1 => r.u8()? as u128,
+                    2 => r.u16()? as u128,
+                    3..=4 => r.u32()? as u128,
+                    5..=8 => r.u64()? as u128,
+                    _ => r.u128()?,
+                };
+                Arg::Uxx(value << (lowest_byte * 8))
+            }
+            Type::Str => {
+                let len = r.u32()? as usize;
+                Arg::Str(String::from_utf8_lossy(r.bytes(len)?).into_owned())
+            }
+            Type::IStr => {
+                let index = r.u16()?;
+                let entry = self
+                    .get(index)
+                    .ok_or_else(|| format!("unknown string index {}", index))?;
+                Arg::Str(entry.format.clone())
+            }
+            Type::Debug | Type::Display => {
+                let len =
+                    r.0.iter()
+                        .position(|&b| b == 0xff)
+                        .ok_or("unterminated formatted string")?;
+                let s = String::from_utf8_lossy(r.bytes(len)?).into_owned();
+                r.bytes(1)?;
+                Arg::Str(s)
+            }
+            Type::U8Slice => {
+                let len = r.u32()? as usize;
+                Arg::Bytes(r.bytes(len)?.to_vec())
+            }
+            Type::U8Array(len) => Arg::Bytes(r.bytes(*len)?.to_vec()),
+            Type::Format => {
+                let tag = r.u16()?;
+                Arg::Formatted(self.format_tagged(r, tag)?)
+            }
+            Type::FormatArray(len) => {
+                let tag = r.u16()?;
+                let items = (0..*len)
+                    .map(|_| self.format_tagged(r, tag))
+                    .collect::<Result<_>>()?;
+                Arg::List(items)
+            }
+            Type::FormatSlice => {
+                let len = r.u32()? as usize;
+                let items = if len == 0 {
+                    Vec::new()
+                } else {
+                    let tag = r.u16()?;
+                    (0..len)
+                        .map(|_| self.format_tagged(r, tag))
+                        .collect::<Result<_>>()?
+                };
+                Arg::List(items)
+            }
+            Type::FormatSequence => {
+                // several `write!`s in one `format()`, ends with index 0
+                let mut s = String::new();
+                loop {
+                    let tag = r.u16()?;
+                    if tag == 0 {
+                        break;
+                    }
+                    s.push_str(&self.format_tagged(r, tag)?);
+                }
+                Arg::Formatted(s)
+            }
+        })
+    }
+}
+
+#[derive(Debug)]
+enum Arg {
+    Bool(bool),
+    Char(char),
+    Uxx(u128),
+    Ixx(i128),
+    F32(f32),
+    F64(f64),
+    Str(String),
+    Bytes(Vec<u8>),
+    /// A nested `Format` value, already rendered
+    Formatted(String),
+    List(Vec<String>),
+}
+
+fn render(out: &mut String, arg: &Arg, ty: &Type, hint: Option<&DisplayHint>) {
+    match arg {
+        Arg::Uxx(v) => match ty {
+            Type::BitField(range) => {
+                let width = (range.end - range.start) as usize;
+                let bits = (v >> range.start) & ((1u128 << width) - 1);
+                match hint {
+                    None => write!(out, "0b{:0width$b}", bits, width = width).unwrap(),
+                    Some(hint) => render_uint(out, bits, hint),
+                }
+            }
+            _ => render_uint(
+                out,
+                *v,
+                hint.unwrap_or(&DisplayHint::NoHint { zero_pad: 0 }),
+            ),
+        },
+        Arg::Ixx(v) => match hint {
+            Some(DisplayHint::NoHint { zero_pad }) => {
+                write!(out, "{:0width$}", v, width = *zero_pad).unwrap()
+            }
+            // hex, binary etc. of the two's complement, like core::fmt
+            Some(hint @ DisplayHint::Hexadecimal { .. })
+            | Some(hint @ DisplayHint::Octal { .. })
+            | Some(hint @ DisplayHint::Binary { .. }) => {
+                let bits = match ty {
+                    Type::I8 => 8,
+                    Type::I16 => 16,
+                    Type::I32 | Type::Isize => 32,
+                    Type::I64 => 64,
+                    _ => 128,
+                };
+                let mask = if bits == 128 {
+                    u128::MAX
+                } else {
+                    (1u128 << bits) - 1
+                };
+                render_uint(out, *v as u128 & mask, hint)
+            }
+            _ => write!(out, "{}", v).unwrap(),
+        },
+        Arg::Bool(v) => write!(out, "{}", v).unwrap(),
+        Arg::Char(c) => match hint {
+            Some(DisplayHint::Debug) => write!(out, "{:?}", c).unwrap(),
+            _ => out.push(*c),
+        },
+        Arg::F32(v) => match hint {
+            Some(DisplayHint::Debug) => write!(out, "{:?}", v).unwrap(),
+            _ => write!(out, "{}", v).unwrap(),
+        },
+        Arg::F64(v) => match hint {
+            Some(DisplayHint::Debug) => write!(out, "{:?}", v).unwrap(),
+            _ => write!(out, "{}", v).unwrap(),
+        },
+        Arg::Str(s) => match hint {
+            Some(DisplayHint::Debug) => write!(out, "{:?}", s).unwrap(),
+            _ => out.push_str(s),
+        },
+        Arg::Formatted(s) => out.push_str(s),
+        Arg::Bytes(bytes) => match hint {
+            Some(DisplayHint::Ascii) => {
+                out.push_str("b\"");
+                for &b in bytes {
+                    out.extend(std::ascii::escape_default(b).map(char::from));
+                }
+                out.push('"');
+            }
+            _ => {
+                out.push('[');
+                for (i, &b) in bytes.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    render_uint(
+                        out,
+                        b as u128,
+                        hint.unwrap_or(&DisplayHint::NoHint { zero_pad: 0 }),
+                    );
+                }
+                out.push(']');
+            }
+        },
+        Arg::List(items) => write!(out, "[{}]", items.join(", ")).unwrap(),
+    }
+}
+
+fn render_uint(out: &mut String, v: u128, hint: &DisplayHint) {
+    match hint {
+        DisplayHint::Hexadecimal {
+            alternate,
+            uppercase,
+            zero_pad,
+        } => {
+            let prefix = if *alternate { "0x" } else { "" };
+            let width = zero_pad.saturating_sub(prefix.len());
+            if *uppercase {
+                write!(out, "{}{:0width$X}", prefix, v, width = width).unwrap()
+            } else {
+                write!(out, "{}{:0width$x}", prefix, v, width = width).unwrap()
+            }
+        }
+        DisplayHint::Octal {
+            alternate,
+            zero_pad,
+        } => {
+            let prefix = if *alternate { "0o" } else { "" };
+            let width = zero_pad.saturating_sub(prefix.len());
+            write!(out, "{}{:0width$o}", prefix, v, width = width).unwrap()
+        }
+        DisplayHint::Binary {
+            alternate,
+            zero_pad,
+        } => {
+            let prefix = if *alternate { "0b" } else { "" };
+            let width = zero_pad.saturating_sub(prefix.len());
+            write!(out, "{}{:0width$b}", prefix, v, width = width).unwrap()
+        }
+        DisplayHint::Ascii => match u8::try_from(v) {
+            Ok(b) => out.extend(std::ascii::escape_default(b).map(char::from)),
+            Err(_) => write!(out, "{}", v).unwrap(),
+        },
+        // seconds, with the fraction
+        DisplayHint::Seconds(TimePrecision::Micros) => {
+            write!(out, "{}.{:06}", v / 1_000_000, v % 1_000_000).unwrap()
+        }
+        DisplayHint::Seconds(TimePrecision::Millis) => {
+            write!(out, "{}.{:03}", v / 1_000, v % 1_000).unwrap()
+        }
+        // hh:mm:ss.fraction
+        DisplayHint::Time(precision) => {
+            let (secs, fraction) = match precision {
+                TimePrecision::Micros => (v / 1_000_000, format!(".{:06}", v % 1_000_000)),
+                TimePrecision::Millis => (v / 1_000, format!(".{:03}", v % 1_000)),
+                TimePrecision::Seconds => (v, String::new()),
+            };
+            write!(
+                out,
+                "{:02}:{:02}:{:02}{}",
+                secs / 3600,
+                secs / 60 % 60,
+                secs % 60,
+                fraction
+            )
+            .unwrap()
+        }
+        DisplayHint::NoHint { zero_pad } => {
+            write!(out, "{:0width$}", v, width = *zero_pad).unwrap()
+        }
+        // ISO 8601, bitflags, CBOR: not worth it here, show the raw value
+        _ => write!(out, "{}", v).unwrap(),
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8]> {
+        if self.0.len() < n {
+            return Err("frame too short".into());
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let mut buf = [0; 2];
+        buf.copy_from_slice(self.bytes(2)?);
+        Ok(u16::from_le_bytes(buf))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let mut buf = [0; 4];
+        buf.copy_from_slice(self.bytes(4)?);
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        let mut buf = [0; 8];
+        buf.copy_from_slice(self.bytes(8)?);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    fn u128(&mut self) -> Result<u128> {
+        let mut buf = [0; 16];
+        buf.copy_from_slice(self.bytes(16)?);
+        Ok(u128::from_le_bytes(buf))
+    }
+}
+
+/// rzCOBS decode one frame, without the trailing 0x00.
+///
+/// The encoding is read backwards: the last byte describes the ones before it.
+///
+/// - `0xxxxxxx`: 7 bytes, bit `i` set if byte `i` is 0x00 (not sent)
+/// - `1nnnnnnn`: `n + 7` non-zero bytes, then a 0x00
+/// - `11111111`: 134 non-zero bytes
+///
+/// The result may have some extra 0x00 bytes at the end, padding.
+pub fn rzcobs_decode(encoded: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(encoded.len() * 8 / 7);
+    let mut it = encoded.iter().rev().copied();
+    let mut next = || it.next().ok_or("corrupt rzCOBS frame");
+    while let Ok(x) = next() {
+        match x {
+            0x00 => return Err("corrupt rzCOBS frame".into()),
+            0x01..=0x7f => {
+                for i in (0..7).rev() {
+                    out.push(if x & 1 << i != 0 { 0 } else { next()? });
+                }
+            }
+            0x80..=0xfe => {
+                out.push(0);
+                for _ in 0..(x & 0x7f) + 7 {
+                    out.push(next()?);
+                }
+            }
+            0xff => {
+                for _ in 0..134 {
+                    out.push(next()?);
+                }
+            }
+        }
+    }
+    out.reverse();
+    Ok(out)
+}
+
+/// Read the next rzCOBS encoded frame into `buf`, without its 0x00, skipping
+/// empty ones. `false` at EOF.
+pub fn next_frame(input: &mut impl BufRead, buf: &mut Vec<u8>) -> std::io::Result<bool> {
+    loop {
+        buf.clear();
+        if input.read_until(0, buf)? == 0 {
+            return Ok(false);
+        }
+        if buf.last() == Some(&0) {
+            buf.pop();
+        }
+        // frame separator written before the first frame
+        if !buf.is_empty() {
+            return Ok(true);
+        }
+    }
+}
+
+/// Decode frames from a capture (or a live stream) until EOF, one line per message.
+///
+/// Bad frames are reported and skipped, so a capture that starts mid-frame
+/// is fine.
+pub fn decode_stream(table: &Table, mut input: impl BufRead) -> Result<()> {
+    let mut buf = Vec::new();
+    while next_frame(&mut input, &mut buf)? {
+        match rzcobs_decode(&buf).and_then(|frame| table.decode(&frame)) {
+            Ok(frame) => println!("{}", frame),
+            Err(e) => eprintln!("(bad frame, {} bytes: {})", buf.len(), e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Three frames encoded on the host by `encode(&frames())`, the last one
+    /// long enough for rzCOBS' 134 byte runs. Not a capture from a board.
+    const HOST_ENCODED: &[u8] = include_bytes!("../tests/fixtures/defmt.bin");
+
+    fn table() -> Table {
+        let mut table = Table::default();
+        let strings = [
+            (1, "defmt_info", "hello {=u8}"),
+            (2, "defmt_warn", "{=f32} C on {=str}"),
+            (3, "defmt_println", "{=[u8]}"),
+        ];
+        for &(index, tag, format) in &strings {
+            let entry = Entry {
+                tag: tag.to_string(),
+                format: format.to_string(),
+            };
+            table.entries.insert(index, entry);
+        }
+        table.timestamp = Some("{=u64:us}".to_string());
+        table
+    }
+
+    fn frames() -> Vec<Vec<u8>> {
+        let mut hello = vec![1, 0];
+        hello.extend_from_slice(&1_000_000u64.to_le_bytes());
+        hello.push(42);
+        let mut temperature = vec![2, 0];
+        temperature.extend_from_slice(&2_500_000u64.to_le_bytes());
+        temperature.extend_from_slice(&27.5f32.to_le_bytes());
+        temperature.extend_from_slice(&5u32.to_le_bytes());
+        temperature.extend_from_slice(b"core0");
+        let mut bytes = vec![3, 0];
+        bytes.extend_from_slice(&3_000_000u64.to_le_bytes());
+        bytes.extend_from_slice(&200u32.to_le_bytes());
+        bytes.extend(1..=200u8);
+        vec![hello, temperature, bytes]
+    }
+
+    /// What the firmware sends: `defmt`'s encoder, as used by `defmt_logger`
+    fn encode(frames: &[Vec<u8>]) -> Vec<u8> {
+        let mut encoder = defmt::Encoder::new();
+        let mut out = Vec::new();
+        for frame in frames {
+            encoder.start_frame(|b| out.extend_from_slice(b));
+            encoder.write(frame, |b| out.extend_from_slice(b));
+            encoder.end_frame(|b| out.extend_from_slice(b));
+        }
+        out
+    }
+
+    fn decode_all(table: &Table, mut input: &[u8]) -> Vec<Result<String>> {
+        let mut buf = Vec::new();
+        let mut out = Vec::new();
+        while next_frame(&mut input, &mut buf).unwrap() {
+            let frame = rzcobs_decode(&buf).and_then(|frame| table.decode(&frame));
+            out.push(frame.map(|frame| frame.to_string()));
+        }
+        out
+    }
+
+    #[test]
+    fn host_encoded_frames() {
+        let decoded: Vec<String> = decode_all(&table(), HOST_ENCODED)
+            .into_iter()
+            .map(|frame| frame.unwrap())
+            .collect();
+        let numbers: Vec<String> = (1..=200).map(|n: u32| n.to_string()).collect();
+        assert_eq!(
+            decoded,
+            [
+                "1.000000 INFO  hello 42".to_string(),
+                "2.500000 WARN  27.5 C on core0".to_string(),
+                format!("3.000000 [{}]", numbers.join(", ")),
+            ]
+        );
+    }
+
+    #[test]
+    fn encoder_round_trip() {
+        assert_eq!(encode(&frames()), HOST_ENCODED);
+
+        // zero runs and non-zero runs of every length around rzCOBS' 7 and 134
+        let mut frames = vec![Vec::new(), vec![0], vec![0; 20]];
+        for len in (1..300).step_by(7) {
+            frames.push((0..len).map(|i| (i % 251) as u8 + 1).collect());
+            frames.push(
+                (0..len)
+                    .map(|i| if i % 3 == 0 { 0 } else { i as u8 })
+                    .collect(),
+            );
+        }
+        let encoded = encode(&frames);
+        let mut input = &encoded[..];
+        let mut buf = Vec::new();
+        for frame in &frames {
+            // an empty frame is just a separator, nothing to read
+            if frame.is_empty() {
+                continue;
+            }
+            assert!(next_frame(&mut input, &mut buf).unwrap());
+            let decoded = rzcobs_decode(&buf).unwrap();
+            assert_eq!(&decoded[..frame.len()], &frame[..]);
+            assert!(decoded[frame.len()..].iter().all(|&b| b == 0));
+        }
+        assert!(!next_frame(&mut input, &mut buf).unwrap());
+    }
+
+    #[test]
+    fn starts_mid_frame() {
+        let decoded = decode_all(&table(), &HOST_ENCODED[5..]);
+        assert_eq!(decoded.len(), 3);
+        assert!(decoded[0].is_err());
+        assert_eq!(
+            decoded[1].as_ref().unwrap(),
+            "2.500000 WARN  27.5 C on core0"
+        );
+        assert!(decoded[2].is_ok());
+    }
+
+    #[test]
+    fn truncated() {
+        // cut inside the second frame, no 0x00 after it
+        let decoded = decode_all(&table(), &HOST_ENCODED[..0x14]);
+        assert_eq!(decoded.len(), 2);
+        assert!(decoded[0].is_ok());
+        assert!(decoded[1].is_err());
+
+        assert!(rzcobs_decode(&[0x80]).is_err());
+        assert!(rzcobs_decode(&[1, 2, 0xff]).is_err());
+        assert!(rzcobs_decode(&[1, 0, 0x80]).is_err());
+    }
+
+    #[test]
+    fn corrupted() {
+        // message index 1 -> 9
+        let mut corrupt = HOST_ENCODED.to_vec();
+        assert_eq!(corrupt[1], 1);
+        corrupt[1] = 9;
+        let decoded = decode_all(&table(), &corrupt);
+        let err = decoded[0].as_ref().unwrap_err();
+        assert!(
+            err.to_string().contains("unknown message index 9"),
+            "{}",
+            err
+        );
+        assert!(decoded[1].is_ok() && decoded[2].is_ok());
+
+        // a string length past the end of the frame
+        let mut frame = frames().remove(1);
+        frame[14] = 50;
+        let mut encoded = encode(&[frame]);
+        encoded.extend_from_slice(HOST_ENCODED);
+        let decoded = decode_all(&table(), &encoded);
+        assert_eq!(decoded.len(), 4);
+        assert!(decoded[0].is_err());
+        assert!(decoded[1..].iter().all(|frame| frame.is_ok()));
+    }
+}
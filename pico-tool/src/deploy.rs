@@ -0,0 +1,75 @@
+//! Find a Pico in BOOTSEL mode (RPI-RP2 mass storage) and copy a UF2 onto it.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::Result;
+
+const VOLUME_LABEL: &str = "RPI-RP2";
+const BOARD_ID: &str = "Board-ID: RPI-RP2";
+
+/// A mount point is a Pico if its INFO_UF2.TXT says so.
+fn is_rpi_rp2(mount: &Path) -> bool {
+    fs::read_to_string(mount.join("INFO_UF2.TXT"))
+        .map(|info| info.contains(BOARD_ID))
+        .unwrap_or(false)
+}
+
+/// Mount points from /proc/mounts, with the octal escapes undone.
+fn proc_mounts() -> Vec<PathBuf> {
+    let mounts = match fs::read_to_string("/proc/mounts") {
+        Ok(mounts) => mounts,
+        Err(_) => return Vec::new(),
+    };
+    mounts
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(|path| PathBuf::from(path.replace("\\040", " ")))
+        .collect()
+}
+
+pub fn find_mounts() -> Vec<PathBuf> {
+    let mut candidates = vec![PathBuf::from("/Volumes").join(VOLUME_LABEL)];
+    if let Ok(user) = env::var("USER") {
+        candidates.push(Path::new("/media").join(&user).join(VOLUME_LABEL));
+        candidates.push(Path::new("/run/media").join(&user).join(VOLUME_LABEL));
+    }
+    candidates.extend(proc_mounts());
+
+    let mut found: Vec<PathBuf> = Vec::new();
+    for path in candidates {
+        if !found.contains(&path) && is_rpi_rp2(&path) {
+            found.push(path);
+        }
+    }
+    found
+}
+
+/// Copy `uf2` onto the single detected RPI-RP2 drive, or onto `mount` if given.
+pub fn deploy(uf2: &[u8], name: &str, mount: Option<&Path>) -> Result<PathBuf> {
+    let mount = match mount {
+        Some(mount) => mount.to_path_buf(),
+        None => {
+            let mut mounts = find_mounts();
+            match mounts.len() {
+                0 => {
+                    return Err(
+                        "no RPI-RP2 drive found, hold BOOTSEL while plugging in the Pico".into(),
+                    )
+                }
+                1 => mounts.remove(0),
+                _ => {
+                    return Err(format!(
+                        "multiple RPI-RP2 drives found, pick one with --mount: {:?}",
+                        mounts
+                    )
+                    .into())
+                }
+            }
+        }
+    };
+    let dest = mount.join(name);
+    fs::write(&dest, uf2)?;
+    Ok(dest)
+}
@@ -0,0 +1,194 @@
+//! A program image, loaded from an ELF or UF2 file.
+
+use goblin::elf::program_header::PT_LOAD;
+use goblin::elf::Elf;
+
+use crate::Result;
+
+pub const FLASH_START: u32 = 0x1000_0000;
+pub const FLASH_END: u32 = 0x1100_0000;
+pub const SRAM_START: u32 = 0x2000_0000;
+pub const SRAM_END: u32 = 0x2004_2000;
+/// One past the last 32-bit address
+pub const ADDRESS_SPACE_END: u64 = 1 << 32;
+
+/// A contiguous range of memory
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub addr: u32,
+    pub data: Vec<u8>,
+}
+
+impl Segment {
+    /// `None` if it would run past the end of the 32-bit address space.
+    pub fn new(addr: u32, data: Vec<u8>) -> Option<Segment> {
+        let seg = Segment { addr, data };
+        if seg.end() <= ADDRESS_SPACE_END {
+            Some(seg)
+        } else {
+            None
+        }
+    }
+
+    /// One past the last byte: up to `1 << 32` for a segment ending at the
+    /// top of the address space.
+    pub fn end(&self) -> u64 {
+        u64::from(self.addr) + self.data.len() as u64
+    }
+
+    pub fn contains(&self, addr: u32) -> bool {
+        addr >= self.addr && u64::from(addr) < self.end()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Image {
+    /// Contents as stored, by load address (ELF p_paddr), what goes into the UF2.
+    pub load: Vec<Segment>,
+    /// Contents as seen by the running program (ELF p_vaddr), e.g. `.data` in RAM.
+    pub runtime: Vec<Segment>,
+}
+
+impl Image {
+    /// Loadable segments of an ELF. Segments without file contents (`.bss`) are skipped.
+    pub fn from_elf(bytes: &[u8]) -> Result<Image> {
+        let elf = Elf::parse(bytes)?;
+        if elf.is_64 || elf.header.e_machine != goblin::elf::header::EM_ARM {
+            return Err("not a 32-bit ARM ELF".into());
+        }
+
+        let mut image = Image::default();
+        for ph in elf.program_headers.iter() {
+            if ph.p_type != PT_LOAD || ph.p_filesz == 0 {
+                continue;
+            }
+            let start = ph.p_offset as usize;
+            let data = start
+                .checked_add(ph.p_filesz as usize)
+                .and_then(|end| bytes.get(start..end))
+                .ok_or("segment out of file bounds")?
+                .to_vec();
+
+            let out_of_range = || format!("segment at {:#010x} wraps around 4 GB", ph.p_paddr);
+            image
+                .load
+                .push(Segment::new(ph.p_paddr as u32, data.clone()).ok_or_else(out_of_range)?);
+            image
+                .runtime
+                .push(Segment::new(ph.p_vaddr as u32, data).ok_or_else(out_of_range)?);
+        }
+        if image.load.is_empty() {
+            return Err("no loadable segments".into());
+        }
+        image.load.sort_by_key(|s| s.addr);
+        Ok(image)
+    }
+
+    /// Flash (or RAM) contents of a UF2 file. Only the load view is known.
+    pub fn from_uf2(bytes: &[u8]) -> Result<Image> {
+        let load = crate::uf2::parse(bytes)?;
+        Ok(Image {
+            runtime: load.clone(),
+            load,
+        })
+    }
+
+    pub fn from_file_contents(bytes: &[u8]) -> Result<Image> {
+        if bytes.starts_with(b"\x7fELF") {
+            Image::from_elf(bytes)
+        } else if crate::uf2::is_uf2(bytes) {
+            Image::from_uf2(bytes)
+        } else {
+            Err("unknown file format, expected ELF or UF2".into())
+        }
+    }
+
+    /// Whether the image is loaded into flash, rather than a RAM-only (no_flash) binary.
+    pub fn is_flash(&self) -> bool {
+        self.load
+            .iter()
+            .any(|s| s.addr >= FLASH_START && s.addr < FLASH_END)
+    }
+
+    /// Read `len` bytes at a runtime address, falling back to load addresses.
+    pub fn read(&self, addr: u32, len: usize) -> Option<&[u8]> {
+        self.runtime
+            .iter()
+            .chain(self.load.iter())
+            .find(|s| s.contains(addr) && u64::from(addr) + len as u64 <= s.end())
+            .map(|s| {
+                let off = (addr - s.addr) as usize;
+                &s.data[off..off + len]
+            })
+    }
+
+    /// Read from the load view only, i.e. what is physically in flash.
+    pub fn read_load(&self, addr: u32, len: usize) -> Option<&[u8]> {
+        self.load
+            .iter()
+            .find(|s| s.contains(addr) && u64::from(addr) + len as u64 <= s.end())
+            .map(|s| {
+                let off = (addr - s.addr) as usize;
+                &s.data[off..off + len]
+            })
+    }
+
+    pub fn read_u16(&self, addr: u32) -> Option<u16> {
+        self.read(addr, 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    pub fn read_u32(&self, addr: u32) -> Option<u32> {
+        self.read(addr, 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Read a NUL-terminated string.
+    pub fn read_cstr(&self, addr: u32) -> Option<String> {
+        let seg = self
+            .runtime
+            .iter()
+            .chain(self.load.iter())
+            .find(|s| s.contains(addr))?;
+        let bytes = &seg.data[(addr - seg.addr) as usize..];
+        let len = bytes.iter().position(|&b| b == 0)?;
+        Some(String::from_utf8_lossy(&bytes[..len]).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Written by tests/fixtures/sample_elf.py
+    const SAMPLE_ELF: &[u8] = include_bytes!("../tests/fixtures/sample.elf");
+
+    #[test]
+    fn elf_segments() {
+        let image = Image::from_elf(SAMPLE_ELF).unwrap();
+        assert_eq!(image.load.len(), 2);
+        assert_eq!(image.load[0].addr, FLASH_START);
+        assert_eq!(image.load[0].end(), 0x1000_0400);
+        assert_eq!(image.load[1].addr, 0x1000_0400);
+        assert_eq!(image.read_cstr(SRAM_START).as_deref(), Some("blink"));
+        assert!(image.is_flash());
+    }
+
+    #[test]
+    fn wrapping_segment() {
+        // p_paddr of the first program header
+        let mut elf = SAMPLE_ELF.to_vec();
+        elf[64..68].copy_from_slice(&0xffff_ff00u32.to_le_bytes());
+        let err = Image::from_elf(&elf).unwrap_err();
+        assert!(err.to_string().contains("wraps around"), "{}", err);
+    }
+
+    #[test]
+    fn segment_end() {
+        let seg = Segment::new(0xffff_ff00, vec![0; 0x100]).unwrap();
+        assert_eq!(seg.end(), ADDRESS_SPACE_END);
+        assert!(seg.contains(u32::MAX));
+        assert!(!seg.contains(0xffff_feff));
+        assert!(Segment::new(0xffff_ff00, vec![0; 0x101]).is_none());
+        assert!(Segment::new(0, Vec::new()).is_some());
+    }
+}
@@ -0,0 +1,184 @@
+//! Host-side tool for Raspberry Pi Pico images, replaces elf2uf2 and the `cp` step.
+//!
+//! ```text
+//! pico-tool uf2 <input.elf> [-o output.uf2] [--deploy] [--mount DIR]
+//! pico-tool info <input.elf|input.uf2>
+//! pico-tool deploy <input.elf|input.uf2> [--mount DIR]
+//! pico-tool defmt <input.elf> [capture|-]
+//! ```
+
+use std::env;
+use std::fs;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::process;
+
+mod binary_info;
+mod boot2;
+mod defmt;
+mod deploy;
+mod image;
+mod uf2;
+
+use self::boot2::Boot2Status;
+use self::image::Image;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const USAGE: &str = "\
+usage:
+    pico-tool uf2 <input.elf> [-o output.uf2] [--deploy] [--mount DIR]
+    pico-tool info <input.elf|input.uf2>
+    pico-tool deploy <input.elf|input.uf2> [--mount DIR]
+    pico-tool defmt <input.elf> [capture|-]";
+
+#[derive(Default)]
+struct Args {
+    command: String,
+    input: PathBuf,
+    /// defmt frames, stdin if missing or `-`
+    capture: Option<PathBuf>,
+    output: Option<PathBuf>,
+    deploy: bool,
+    mount: Option<PathBuf>,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut args = Args::default();
+    let mut it = env::args().skip(1);
+    args.command = it.next().ok_or(USAGE)?;
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                args.output = Some(it.next().ok_or("missing value for -o")?.into())
+            }
+            "--deploy" => args.deploy = true,
+            "--mount" => args.mount = Some(it.next().ok_or("missing value for --mount")?.into()),
+            "-h" | "--help" => return Err(USAGE.into()),
+            _ if args.input.as_os_str().is_empty() && !arg.starts_with('-') => {
+                args.input = arg.into()
+            }
+            _ if args.command == "defmt" && args.capture.is_none() => {
+                args.capture = Some(arg.into())
+            }
+            _ => return Err(format!("unexpected argument: {}\n{}", arg, USAGE).into()),
+        }
+    }
+    if args.input.as_os_str().is_empty() {
+        return Err(USAGE.into());
+    }
+    Ok(args)
+}
+
+fn load(path: &Path) -> Result<(Vec<u8>, Image)> {
+    let bytes = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let image = Image::from_file_contents(&bytes)?;
+    Ok((bytes, image))
+}
+
+/// Refuse to produce images the bootrom won't run.
+fn check_boot2(image: &Image) -> Result<()> {
+    match boot2::check(image) {
+        Boot2Status::Valid { .. } | Boot2Status::NotApplicable => Ok(()),
+        Boot2Status::Missing => Err("flash image has no boot2 at 0x10000000".into()),
+        Boot2Status::Invalid { expected, found } => Err(format!(
+            "boot2 checksum mismatch: expected {:#010x}, found {:#010x}",
+            expected, found
+        )
+        .into()),
+    }
+}
+
+/// UF2 inputs are passed through as-is.
+fn to_uf2(bytes: Vec<u8>, image: &Image) -> Result<Vec<u8>> {
+    if uf2::is_uf2(&bytes) {
+        return Ok(bytes);
+    }
+    check_boot2(image)?;
+    uf2::from_image(image, uf2::RP2040_FAMILY_ID)
+}
+
+fn run(args: Args) -> Result<()> {
+    let Args {
+        command,
+        input,
+        capture,
+        output,
+        deploy,
+        mount,
+    } = args;
+    if command == "defmt" {
+        let bytes = fs::read(&input).map_err(|e| format!("{}: {}", input.display(), e))?;
+        let table = defmt::Table::from_elf(&bytes)?;
+        return match capture {
+            Some(path) if path.as_os_str() != "-" => {
+                let file =
+                    fs::File::open(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+                defmt::decode_stream(&table, BufReader::new(file))
+            }
+            _ => defmt::decode_stream(&table, io::stdin().lock()),
+        };
+    }
+
+    let (bytes, image) = load(&input)?;
+    let uf2_name = input
+        .with_extension("uf2")
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "out.uf2".into());
+
+    match command.as_str() {
+        "uf2" => {
+            let uf2 = to_uf2(bytes, &image)?;
+            let output = output.unwrap_or_else(|| input.with_extension("uf2"));
+            fs::write(&output, &uf2)?;
+            println!(
+                "wrote {} ({} blocks)",
+                output.display(),
+                uf2.len() / uf2::BLOCK_SIZE
+            );
+            if deploy {
+                let dest = deploy::deploy(&uf2, &uf2_name, mount.as_deref())?;
+                println!("copied to {}", dest.display());
+            }
+        }
+        "info" => {
+            match boot2::check(&image) {
+                Boot2Status::Valid { crc } => println!("boot2: ok (crc32 {:#010x})", crc),
+                Boot2Status::NotApplicable => println!("boot2: not needed, RAM-only binary"),
+                Boot2Status::Missing => println!("boot2: MISSING"),
+                Boot2Status::Invalid { expected, found } => println!(
+                    "boot2: BAD CHECKSUM (expected {:#010x}, found {:#010x})",
+                    expected, found
+                ),
+            }
+            for seg in &image.load {
+                println!(
+                    "segment: {:#010x}..{:#010x} ({} bytes)",
+                    seg.addr,
+                    seg.end(),
+                    seg.data.len()
+                );
+            }
+            println!();
+            match binary_info::read(&image) {
+                Some(info) => print!("{}", info),
+                None => println!("no binary info found"),
+            }
+        }
+        "deploy" => {
+            let uf2 = to_uf2(bytes, &image)?;
+            let dest = deploy::deploy(&uf2, &uf2_name, mount.as_deref())?;
+            println!("copied to {}", dest.display());
+        }
+        _ => return Err(format!("unknown command: {}\n{}", command, USAGE).into()),
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = parse_args().and_then(run) {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,204 @@
+//! UF2 encoding, see https://github.com/microsoft/uf2
+
+use std::collections::BTreeMap;
+
+use crate::image::{
+    Image, Segment, ADDRESS_SPACE_END, FLASH_END, FLASH_START, SRAM_END, SRAM_START,
+};
+use crate::Result;
+
+pub const MAGIC_START0: u32 = 0x0A32_4655;
+pub const MAGIC_START1: u32 = 0x9E5D_5157;
+pub const MAGIC_END: u32 = 0x0AB1_6F30;
+
+pub const FLAG_NOT_MAIN_FLASH: u32 = 0x0000_0001;
+pub const FLAG_FILE_CONTAINER: u32 = 0x0000_1000;
+pub const FLAG_FAMILY_ID_PRESENT: u32 = 0x0000_2000;
+
+pub const RP2040_FAMILY_ID: u32 = 0xe48b_b556;
+
+pub const BLOCK_SIZE: usize = 512;
+/// The RP2040 bootrom only accepts 256 byte payloads
+pub const PAGE_SIZE: u32 = 256;
+const DATA_SIZE: usize = 476;
+
+pub fn is_uf2(bytes: &[u8]) -> bool {
+    bytes.len() >= BLOCK_SIZE
+        && read_u32(bytes, 0) == MAGIC_START0
+        && read_u32(bytes, 4) == MAGIC_START1
+}
+
+fn read_u32(buf: &[u8], off: usize) -> u32 {
+    u32::from_le_bytes([buf[off], buf[off + 1], buf[off + 2], buf[off + 3]])
+}
+
+/// Convert the load view of an image into UF2 blocks.
+///
+/// Segments are split into 256 byte pages aligned to 256 bytes, gaps inside a
+/// page are zero-filled. All segments must be in flash, or all in SRAM.
+pub fn from_image(image: &Image, family_id: u32) -> Result<Vec<u8>> {
+    let in_flash = |s: &Segment| s.addr >= FLASH_START && s.end() <= u64::from(FLASH_END);
+    let in_sram = |s: &Segment| s.addr >= SRAM_START && s.end() <= u64::from(SRAM_END);
+
+    let flash = image.is_flash();
+    for seg in &image.load {
+        let ok = if flash { in_flash(seg) } else { in_sram(seg) };
+        if !ok {
+            return Err(format!(
+                "segment {:#010x}..{:#010x} is outside of {}",
+                seg.addr,
+                seg.end(),
+                if flash { "flash" } else { "SRAM" }
+            )
+            .into());
+        }
+    }
+
+    // page address => page contents
+    let mut pages: BTreeMap<u32, [u8; PAGE_SIZE as usize]> = BTreeMap::new();
+    for seg in &image.load {
+        for (i, &b) in seg.data.iter().enumerate() {
+            let addr = seg.addr + i as u32;
+            let page = pages
+                .entry(addr & !(PAGE_SIZE - 1))
+                .or_insert([0; PAGE_SIZE as usize]);
+            page[(addr & (PAGE_SIZE - 1)) as usize] = b;
+        }
+    }
+
+    let num_blocks = pages.len() as u32;
+    let mut out = Vec::with_capacity(pages.len() * BLOCK_SIZE);
+    for (block_no, (addr, page)) in pages.iter().enumerate() {
+        let mut block = [0u8; BLOCK_SIZE];
+        let header = [
+            MAGIC_START0,
+            MAGIC_START1,
+            FLAG_FAMILY_ID_PRESENT,
+            *addr,
+            PAGE_SIZE,
+            block_no as u32,
+            num_blocks,
+            family_id,
+        ];
+        for (i, word) in header.iter().enumerate() {
+            block[i * 4..i * 4 + 4].copy_from_slice(&word.to_le_bytes());
+        }
+        block[32..32 + PAGE_SIZE as usize].copy_from_slice(page);
+        block[BLOCK_SIZE - 4..].copy_from_slice(&MAGIC_END.to_le_bytes());
+        out.extend_from_slice(&block);
+    }
+    Ok(out)
+}
+
+/// Parse UF2 blocks back into memory segments, merging adjacent blocks.
+pub fn parse(bytes: &[u8]) -> Result<Vec<Segment>> {
+    if !bytes.chunks_exact(BLOCK_SIZE).remainder().is_empty() {
+        return Err("UF2 file size is not a multiple of 512".into());
+    }
+
+    let mut chunks: BTreeMap<u32, Vec<u8>> = BTreeMap::new();
+    for (i, block) in bytes.chunks(BLOCK_SIZE).enumerate() {
+        if read_u32(block, 0) != MAGIC_START0
+            || read_u32(block, 4) != MAGIC_START1
+            || read_u32(block, BLOCK_SIZE - 4) != MAGIC_END
+        {
+            return Err(format!("block {}: bad magic", i).into());
+        }
+        let flags = read_u32(block, 8);
+        if flags & (FLAG_NOT_MAIN_FLASH | FLAG_FILE_CONTAINER) != 0 {
+            continue;
+        }
+        let addr = read_u32(block, 12);
+        let size = read_u32(block, 16) as usize;
+        if size > DATA_SIZE {
+            return Err(format!("block {}: payload size {} too large", i, size).into());
+        }
+        if u64::from(addr) + size as u64 > ADDRESS_SPACE_END {
+            return Err(format!("block {}: {:#010x} wraps around 4 GB", i, addr).into());
+        }
+        chunks.insert(addr, block[32..32 + size].to_vec());
+    }
+
+    let mut segments: Vec<Segment> = Vec::new();
+    for (addr, data) in chunks {
+        match segments.last_mut() {
+            Some(last) if last.end() == u64::from(addr) => last.data.extend_from_slice(&data),
+            _ => segments.push(Segment { addr, data }),
+        }
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Written by tests/fixtures/sample_elf.py
+    const SAMPLE_ELF: &[u8] = include_bytes!("../tests/fixtures/sample.elf");
+
+    fn sample_uf2() -> Vec<u8> {
+        from_image(&Image::from_elf(SAMPLE_ELF).unwrap(), RP2040_FAMILY_ID).unwrap()
+    }
+
+    #[test]
+    fn from_elf() {
+        let uf2 = sample_uf2();
+        assert_eq!(uf2.len(), 5 * BLOCK_SIZE);
+        assert!(is_uf2(&uf2));
+        for (i, block) in uf2.chunks(BLOCK_SIZE).enumerate() {
+            assert_eq!(read_u32(block, 8), FLAG_FAMILY_ID_PRESENT);
+            assert_eq!(read_u32(block, 12), FLASH_START + i as u32 * PAGE_SIZE);
+            assert_eq!(read_u32(block, 16), PAGE_SIZE);
+            assert_eq!(read_u32(block, 20), i as u32);
+            assert_eq!(read_u32(block, 24), 5);
+            assert_eq!(read_u32(block, 28), RP2040_FAMILY_ID);
+            assert_eq!(read_u32(block, BLOCK_SIZE - 4), MAGIC_END);
+        }
+        // the .data copy, zero-filled to the end of its page
+        let last = &uf2[4 * BLOCK_SIZE + 32..][..PAGE_SIZE as usize];
+        assert_eq!(&last[..8], b"blink\0\0\0");
+        assert!(last[8..].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn round_trip() {
+        let elf = Image::from_elf(SAMPLE_ELF).unwrap();
+        let segments = parse(&sample_uf2()).unwrap();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].addr, FLASH_START);
+        assert_eq!(segments[0].data.len(), 5 * PAGE_SIZE as usize);
+        for seg in &elf.load {
+            let off = (seg.addr - FLASH_START) as usize;
+            assert_eq!(&segments[0].data[off..off + seg.data.len()], &seg.data[..]);
+        }
+    }
+
+    #[test]
+    fn bad_blocks() {
+        let uf2 = sample_uf2();
+        assert!(parse(&uf2[..BLOCK_SIZE + 1]).is_err());
+
+        let mut bad = uf2.clone();
+        bad[BLOCK_SIZE - 1] ^= 0xff;
+        assert!(parse(&bad).is_err());
+
+        let mut bad = uf2.clone();
+        bad[16..20].copy_from_slice(&(DATA_SIZE as u32 + 1).to_le_bytes());
+        assert!(parse(&bad).is_err());
+
+        let mut bad = uf2;
+        bad[12..16].copy_from_slice(&0xffff_ff80u32.to_le_bytes());
+        let err = parse(&bad).unwrap_err();
+        assert!(err.to_string().contains("wraps around"), "{}", err);
+    }
+
+    #[test]
+    fn top_of_address_space() {
+        let mut uf2 = sample_uf2();
+        uf2[12..16].copy_from_slice(&0xffff_ff00u32.to_le_bytes());
+        let segments = parse(&uf2).unwrap();
+        let top = segments.last().unwrap();
+        assert_eq!(top.addr, 0xffff_ff00);
+        assert_eq!(top.end(), ADDRESS_SPACE_END);
+    }
+}
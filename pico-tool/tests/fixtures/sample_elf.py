@@ -0,0 +1,103 @@
+#!/usr/bin/env python3
+"""Writes sample.elf, the ELF fixture of the image, uf2 and binary_info tests.
+
+A hand-laid-out stand-in for a linked pico-rt binary, small enough to check
+by eye with `pico-tool info tests/fixtures/sample.elf`:
+
+- PT_LOAD 0x10000000, 0x400 bytes of flash:
+  - 0x000 boot2: 252 filler bytes and their CRC32 (MPEG-2, as the bootrom)
+  - 0x100 vector table: initial SP and reset vector
+  - 0x110 binary info header: start, end, mapping table
+  - 0x200 four entry pointers, 0x220 the mapping table
+  - 0x240.. entries: the program name, in .data through the mapping table,
+    the version, the binary end and a pin named "LED" on GPIO 25
+  - 0x300.. their strings
+- PT_LOAD .data: "blink", loaded at 0x10000400, run at 0x20000000
+"""
+
+import os
+import struct
+
+FLASH = 0x10000000
+SRAM = 0x20000000
+
+# binary info, see pico/binary_info/defs.h and structure.h
+MARKER_START = 0x7188EBF2
+MARKER_END = 0xE71AA390
+TAG_RASPBERRY_PI = ord("R") | ord("P") << 8
+TYPE_ID_AND_INT = 5
+TYPE_ID_AND_STRING = 6
+TYPE_PINS_WITH_NAME = 9
+ID_RP_PROGRAM_NAME = 0x02031C86
+ID_RP_PROGRAM_VERSION_STRING = 0x11A9BC3A
+ID_RP_BINARY_END = 0x68F465DE
+
+
+def crc32(data):
+    crc = 0xFFFFFFFF
+    for b in data:
+        crc ^= b << 24
+        for _ in range(8):
+            crc = (crc << 1) ^ 0x04C11DB7 if crc & 0x80000000 else crc << 1
+            crc &= 0xFFFFFFFF
+    return crc
+
+
+def main():
+    flash = bytearray(0x400)
+
+    def w32(off, value):
+        flash[off : off + 4] = struct.pack("<I", value)
+
+    boot2 = bytes((i * 7 + 3) & 0xFF for i in range(252))
+    flash[0:252] = boot2
+    w32(252, crc32(boot2))
+
+    w32(0x100, 0x20042000)
+    w32(0x104, FLASH + 0x1C1)
+
+    header = [MARKER_START, FLASH + 0x200, FLASH + 0x210, FLASH + 0x220, MARKER_END]
+    for i, value in enumerate(header):
+        w32(0x110 + i * 4, value)
+
+    for i, entry in enumerate([0x240, 0x250, 0x260, 0x270]):
+        w32(0x200 + i * 4, FLASH + entry)
+
+    # source, destination start, destination end; then a zero source
+    w32(0x220, FLASH + 0x400)
+    w32(0x224, SRAM)
+    w32(0x228, SRAM + 8)
+    w32(0x22C, 0)
+
+    entry = struct.Struct("<HHII")
+    flash[0x240:0x24C] = entry.pack(TYPE_ID_AND_STRING, TAG_RASPBERRY_PI, ID_RP_PROGRAM_NAME, SRAM)
+    flash[0x250:0x25C] = entry.pack(
+        TYPE_ID_AND_STRING, TAG_RASPBERRY_PI, ID_RP_PROGRAM_VERSION_STRING, FLASH + 0x300
+    )
+    flash[0x260:0x26C] = entry.pack(TYPE_ID_AND_INT, TAG_RASPBERRY_PI, ID_RP_BINARY_END, FLASH + 0x408)
+    # pin mask: GPIO 25
+    flash[0x270:0x27C] = entry.pack(TYPE_PINS_WITH_NAME, TAG_RASPBERRY_PI, 1 << 25, FLASH + 0x310)
+    flash[0x300:0x306] = b"0.1.0\0"
+    flash[0x310:0x314] = b"LED\0"
+
+    data = b"blink\0\0\0"
+
+    # ELF32, little endian, EM_ARM, EABI5 soft float
+    ehsize, phsize = 52, 32
+    off_flash = ehsize + 2 * phsize
+    off_data = off_flash + len(flash)
+    ident = b"\x7fELF" + bytes([1, 1, 1, 0]) + bytes(8)
+    ehdr = ident + struct.pack(
+        "<HHIIIIIHHHHHH", 2, 40, 1, FLASH + 0x1C1, ehsize, 0, 0x05000200, ehsize, phsize, 2, 40, 0, 0
+    )
+    # p_type, p_offset, p_vaddr, p_paddr, p_filesz, p_memsz, p_flags, p_align
+    ph_flash = struct.pack("<IIIIIIII", 1, off_flash, FLASH, FLASH, len(flash), len(flash), 5, 4)
+    ph_data = struct.pack("<IIIIIIII", 1, off_data, SRAM, FLASH + 0x400, len(data), len(data) + 8, 6, 4)
+
+    out = os.path.join(os.path.dirname(os.path.abspath(__file__)), "sample.elf")
+    with open(out, "wb") as f:
+        f.write(ehdr + ph_flash + ph_data + bytes(flash) + data)
+
+
+if __name__ == "__main__":
+    main()
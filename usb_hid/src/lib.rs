@@ -0,0 +1,172 @@
+#![no_std]
+
+use embedded_hal::digital::v2::OutputPin;
+use rpi_pico_sdk::gpio::{Button, Gpio};
+use rpi_pico_sdk::hid::{keycode, GamepadReport, HidClass, KeyboardReport, MouseReport};
+use rpi_pico_sdk::usb::UsbBus;
+use rpi_pico_sdk_sys::*;
+use usb_device::bus::UsbBusAllocator;
+use usb_device::prelude::*;
+
+/*
+GP2  => key A
+GP3  => key B
+GP4  => Enter
+GP5  => Left Shift
+GP6  => mouse up
+GP7  => mouse down
+GP8  => mouse left
+GP9  => mouse right
+GP10 => mouse left button
+GP11 => gamepad button 1
+GP12 => gamepad button 2
+GP13 => gamepad button 3
+GP14 => gamepad button 4
+
+Buttons to ground, the internal pull-ups hold the pins high.
+ */
+
+const PIN_LED: uint = 25;
+
+/// Pixels per 10 ms while a direction is held
+const MOUSE_STEP: i8 = 4;
+
+#[derive(Clone, Copy)]
+enum Action {
+    Key(u8),
+    Move(i8, i8),
+    Click(u8),
+    Pad(u8),
+}
+
+const BUTTONS: [(uint, Action); 13] = [
+    (2, Action::Key(keycode::A)),
+    (3, Action::Key(keycode::B)),
+    (4, Action::Key(keycode::ENTER)),
+    (5, Action::Key(keycode::LEFT_SHIFT)),
+    (6, Action::Move(0, -MOUSE_STEP)),
+    (7, Action::Move(0, MOUSE_STEP)),
+    (8, Action::Move(-MOUSE_STEP, 0)),
+    (9, Action::Move(MOUSE_STEP, 0)),
+    (10, Action::Click(MouseReport::LEFT)),
+    (11, Action::Pad(1)),
+    (12, Action::Pad(2)),
+    (13, Action::Pad(3)),
+    (14, Action::Pad(4)),
+];
+
+rpi_pico_sdk::binary_info! {
+    name: "usb_hid",
+    version: env!("CARGO_PKG_VERSION"),
+    description: "USB HID keyboard, mouse and gamepad",
+    pins: [
+        PIN_LED => "Caps Lock LED",
+        2 => "Key A",
+        3 => "Key B",
+        4 => "Key Enter",
+        5 => "Key Shift",
+        6 => "Mouse up",
+        7 => "Mouse down",
+        8 => "Mouse left",
+        9 => "Mouse right",
+        10 => "Mouse button",
+        11 => "Gamepad 1",
+        12 => "Gamepad 2",
+        13 => "Gamepad 3",
+        14 => "Gamepad 4",
+    ],
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn main() -> i32 {
+    let mut led = Gpio::init(PIN_LED).into_push_pull_output();
+    let mut buttons = BUTTONS.map(|(pin, _)| Button::new(Gpio::init(pin).into_pull_up_input()));
+
+    let bus = UsbBusAllocator::new(UsbBus::new());
+    let mut keyboard = HidClass::keyboard(&bus);
+    let mut mouse = HidClass::mouse(&bus);
+    let mut gamepad = HidClass::gamepad(&bus);
+    // pid.codes' test PID, for development only
+    let mut dev = UsbDeviceBuilder::new(&bus, UsbVidPid(0x1209, 0x0001))
+        .strings(&[StringDescriptors::default()
+            .manufacturer("pico-rust-playground")
+            .product("Pico HID")
+            .serial_number("0001")])
+        .unwrap()
+        .build();
+
+    let mut kb = KeyboardReport::default();
+    let mut pointer = MouseReport::default();
+    let mut pad = GamepadReport::default();
+    // pending reports, sent once the endpoint is free
+    let (mut kb_dirty, mut pointer_dirty, mut pad_dirty) = (false, false, false);
+    let mut last_ms = 0;
+
+    loop {
+        dev.poll(&mut [&mut keyboard, &mut mouse, &mut gamepad]);
+        if dev.state() != UsbDeviceState::Configured {
+            continue;
+        }
+
+        // caps lock
+        let leds = keyboard.output_report().first().copied().unwrap_or(0);
+        led.set_state((leds & 0x02 != 0).into()).unwrap();
+
+        let now = time_us_64() / 1000;
+        if now == last_ms {
+            continue;
+        }
+        last_ms = now;
+
+        let mut dx: i8 = 0;
+        let mut dy: i8 = 0;
+        for (button, &(_, action)) in buttons.iter_mut().zip(BUTTONS.iter()) {
+            let changed = button.update();
+            match (action, changed) {
+                (Action::Key(key), Some(true)) => {
+                    kb.press(key);
+                    kb_dirty = true;
+                }
+                (Action::Key(key), Some(false)) => {
+                    kb.release(key);
+                    kb_dirty = true;
+                }
+                (Action::Click(bit), Some(pressed)) => {
+                    if pressed {
+                        pointer.buttons |= bit;
+                    } else {
+                        pointer.buttons &= !bit;
+                    }
+                    pointer_dirty = true;
+                }
+                (Action::Pad(n), Some(pressed)) => {
+                    pad.set_button(n, pressed);
+                    pad_dirty = true;
+                }
+                (Action::Move(x, y), _) if button.is_pressed() => {
+                    dx = dx.saturating_add(x);
+                    dy = dy.saturating_add(y);
+                }
+                _ => {}
+            }
+        }
+        if now.is_multiple_of(10) && (dx != 0 || dy != 0) {
+            // adds up while the endpoint is busy
+            pointer.x = pointer.x.saturating_add(dx);
+            pointer.y = pointer.y.saturating_add(dy);
+            pointer_dirty = true;
+        }
+
+        if kb_dirty && keyboard.write_report(&kb.to_bytes()).is_ok() {
+            kb_dirty = false;
+        }
+        if pointer_dirty && mouse.write_report(&pointer.to_bytes()).is_ok() {
+            pointer.x = 0;
+            pointer.y = 0;
+            pointer_dirty = false;
+        }
+        if pad_dirty && gamepad.write_report(&pad.to_bytes()).is_ok() {
+            pad_dirty = false;
+        }
+    }
+}